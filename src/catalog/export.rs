@@ -0,0 +1,80 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use serde_json::{json, Map, Value};
+use crate::models::{category_name, Category, Product};
+use crate::reports::csv::escape;
+use super::import::Field;
+use super::table::FileFormat;
+
+// One product's cells in `Field::ALL` order, as the import reads them back:
+// the category by name, barcodes separated by `;`.
+fn cells(product: &Product, categories: &[Category]) -> Vec<String> {
+    Field::ALL
+        .iter()
+        .map(|field| match field {
+            Field::Sku => product.sku.clone().unwrap_or_default(),
+            Field::Name => product.name.clone(),
+            Field::Price => product.price.plain_string(),
+            Field::Category => category_name(categories, &product.category).to_string(),
+            Field::Stock => product.stock.to_string(),
+            Field::TaxClass => product.tax_class.clone().unwrap_or_default(),
+            Field::Barcodes => product.barcodes.join(";"),
+            Field::ImageUrl => product.image_url.clone().unwrap_or_default(),
+            Field::Archived => if product.archived { "yes" } else { "no" }.to_string(),
+        })
+        .collect()
+}
+
+// Every product, archived ones included, with a header row.
+pub fn catalog_csv(products: &[Product], categories: &[Category]) -> String {
+    let header: Vec<String> = Field::ALL.iter().map(|f| f.key().to_string()).collect();
+    std::iter::once(header)
+        .chain(products.iter().map(|p| cells(p, categories)))
+        .map(|row| row.iter().map(|f| escape(f)).collect::<Vec<_>>().join(",") + "\n")
+        .collect()
+}
+
+// The same columns as objects. Prices stay decimal strings so no float
+// ever touches them; stock is a number and barcodes a list.
+pub fn catalog_json(products: &[Product], categories: &[Category]) -> String {
+    let items: Vec<Value> = products
+        .iter()
+        .map(|product| {
+            let object: Map<String, Value> = Field::ALL
+                .iter()
+                .zip(cells(product, categories))
+                .map(|(field, text)| {
+                    let value = match field {
+                        Field::Stock => json!(product.stock),
+                        Field::Barcodes => json!(product.barcodes),
+                        Field::Archived => json!(product.archived),
+                        _ if text.is_empty() => Value::Null,
+                        _ => Value::String(text),
+                    };
+                    (field.key().to_string(), value)
+                })
+                .collect();
+            Value::Object(object)
+        })
+        .collect();
+    serde_json::to_string_pretty(&items).unwrap_or_default()
+}
+
+// Writes the catalog to `dir` and returns the file's path.
+pub fn export_catalog(
+    products: &[Product],
+    categories: &[Category],
+    format: FileFormat,
+    dir: &Path,
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("catalog-{}.{}", Local::now().format("%Y%m%d-%H%M%S"), format.extension()));
+    let text = match format {
+        FileFormat::Csv => catalog_csv(products, categories),
+        FileFormat::Json => catalog_json(products, categories),
+    };
+    fs::write(&path, text)?;
+    Ok(path)
+}
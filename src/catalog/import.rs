@@ -0,0 +1,333 @@
+use crate::models::{Category, Currency, Money, Product, TaxClass};
+use super::table::Table;
+
+// What a column of the file fills in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Sku,
+    Name,
+    Price,
+    Category,
+    Stock,
+    TaxClass,
+    Barcodes,
+    ImageUrl,
+    Archived,
+}
+
+// What importing one row would do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowOutcome {
+    Create(Product),
+    // The product with the row's values; `changes` as `Product::changes`.
+    Update { product: Product, changes: Vec<String> },
+    Unchanged(Product),
+    Invalid(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    pub number: usize,
+    pub outcome: RowOutcome,
+}
+
+// The result of checking a whole file. Nothing is saved until it is applied,
+// so building one is the dry run.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportPlan {
+    pub rows: Vec<ImportRow>,
+    // Categories named in the file that don't exist yet.
+    pub new_categories: Vec<Category>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImportCounts {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub invalid: usize,
+}
+
+const NEW_CATEGORY_COLOR: &str = "#667eea";
+
+impl Field {
+    pub const ALL: [Field; 9] = [
+        Field::Sku,
+        Field::Name,
+        Field::Price,
+        Field::Category,
+        Field::Stock,
+        Field::TaxClass,
+        Field::Barcodes,
+        Field::ImageUrl,
+        Field::Archived,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Field::Sku => "SKU",
+            Field::Name => "Name",
+            Field::Price => "Price",
+            Field::Category => "Category",
+            Field::Stock => "Stock",
+            Field::TaxClass => "Tax Class",
+            Field::Barcodes => "Barcodes",
+            Field::ImageUrl => "Image URL",
+            Field::Archived => "Archived",
+        }
+    }
+
+    // Column name used on export; also what `guess` reads back.
+    pub fn key(&self) -> &str {
+        match self {
+            Field::Sku => "sku",
+            Field::Name => "name",
+            Field::Price => "price",
+            Field::Category => "category",
+            Field::Stock => "stock",
+            Field::TaxClass => "tax_class",
+            Field::Barcodes => "barcodes",
+            Field::ImageUrl => "image_url",
+            Field::Archived => "archived",
+        }
+    }
+
+    // The field a column header most likely means, ignoring case, spaces and
+    // underscores.
+    pub fn guess(header: &str) -> Option<Self> {
+        let plain: String = header
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match plain.as_str() {
+            "sku" | "code" | "itemcode" | "productcode" => Some(Field::Sku),
+            "name" | "product" | "productname" | "item" | "title" | "description" => Some(Field::Name),
+            "price" | "unitprice" | "retailprice" | "sellprice" => Some(Field::Price),
+            "category" | "categoryname" | "department" => Some(Field::Category),
+            "stock" | "qty" | "quantity" | "onhand" | "instock" => Some(Field::Stock),
+            "taxclass" | "tax" => Some(Field::TaxClass),
+            "barcode" | "barcodes" | "ean" | "upc" | "gtin" => Some(Field::Barcodes),
+            "image" | "imageurl" | "picture" => Some(Field::ImageUrl),
+            "archived" | "inactive" => Some(Field::Archived),
+            _ => None,
+        }
+    }
+}
+
+// A guess for every column; at most one column per field.
+pub fn guess_mapping(headers: &[String]) -> Vec<Option<Field>> {
+    let mut taken: Vec<Field> = Vec::new();
+    headers
+        .iter()
+        .map(|h| {
+            let field = Field::guess(h).filter(|f| !taken.contains(f))?;
+            taken.push(field);
+            Some(field)
+        })
+        .collect()
+}
+
+// Works out what every row would do. A row whose SKU matches a product
+// (any case) updates it; any other row adds a product. Empty cells leave
+// a field as it is. Rows are checked against the catalog and the rows
+// before them, so duplicates within the file are caught too.
+pub fn plan_import(
+    table: &Table,
+    mapping: &[Option<Field>],
+    products: &[Product],
+    categories: &[Category],
+    tax_classes: &[TaxClass],
+) -> ImportPlan {
+    let mut catalog = products.to_vec();
+    let mut new_categories: Vec<Category> = Vec::new();
+    let mut rows = Vec::new();
+
+    for row in &table.rows {
+        let cell = |field: Field| {
+            mapping
+                .iter()
+                .position(|m| *m == Some(field))
+                .and_then(|i| row.cells.get(i))
+                .map(|c| c.trim())
+                .filter(|c| !c.is_empty())
+        };
+        let mut errors: Vec<String> = Vec::new();
+
+        let sku = cell(Field::Sku);
+        let existing = sku.and_then(|sku| {
+            catalog
+                .iter()
+                .position(|p| p.sku.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(sku)))
+        });
+        let currency = existing.map_or(Currency::default(), |i| catalog[i].price.currency());
+
+        let price = cell(Field::Price).and_then(|text| {
+            let price = Money::parse(&decimal_point(text), currency);
+            if price.is_none() {
+                errors.push(format!("price '{text}' is not an amount"));
+            }
+            price
+        });
+        let stock = cell(Field::Stock).and_then(|text| {
+            let stock = text.parse::<i64>().ok();
+            if stock.is_none() {
+                errors.push(format!("stock '{text}' is not a whole number"));
+            }
+            stock
+        });
+        let archived = cell(Field::Archived).and_then(|text| match text.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Some(true),
+            "false" | "no" | "n" | "0" => Some(false),
+            _ => {
+                errors.push(format!("archived '{text}' should be yes or no"));
+                None
+            }
+        });
+        let tax_class = cell(Field::TaxClass).and_then(|text| {
+            let class = tax_classes
+                .iter()
+                .find(|c| c.id == text || c.name.eq_ignore_ascii_case(text))
+                .map(|c| c.id.clone());
+            if class.is_none() {
+                errors.push(format!("no tax class '{text}'"));
+            }
+            class
+        });
+        // By name or id; an unknown name becomes a new category.
+        let category = cell(Field::Category).map(|text| {
+            let known = categories
+                .iter()
+                .chain(&new_categories)
+                .find(|c| c.id == text || c.name.eq_ignore_ascii_case(text));
+            match known {
+                Some(c) => c.id.clone(),
+                None => {
+                    let created = Category::new(text.to_string(), NEW_CATEGORY_COLOR.to_string());
+                    let id = created.id.clone();
+                    new_categories.push(created);
+                    id
+                }
+            }
+        });
+
+        let mut product = match existing {
+            Some(i) => catalog[i].clone(),
+            None => {
+                if cell(Field::Name).is_none() {
+                    errors.push("a new product needs a name".to_string());
+                }
+                if price.is_none() && cell(Field::Price).is_none() {
+                    errors.push("a new product needs a price".to_string());
+                }
+                if category.is_none() {
+                    errors.push("a new product needs a category".to_string());
+                }
+                // Stock not in the file hasn't been counted yet.
+                let mut product = Product::new(String::new(), Money::zero(currency), String::new());
+                product.stock = 0;
+                product
+            }
+        };
+        if let Some(name) = cell(Field::Name) {
+            product.name = name.to_string();
+        }
+        if let Some(price) = price {
+            product.price = price;
+        }
+        if let Some(category) = category {
+            product.category = category;
+        }
+        if let Some(stock) = stock {
+            product.stock = stock;
+        }
+        if let Some(class) = tax_class {
+            product.tax_class = Some(class);
+        }
+        // A SKU matched in another case keeps its spelling.
+        if let Some(sku) = sku.filter(|_| existing.is_none()) {
+            product.sku = Some(sku.to_string());
+        }
+        if let Some(codes) = cell(Field::Barcodes) {
+            product.barcodes = codes
+                .split([';', ',', '|'])
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(url) = cell(Field::ImageUrl) {
+            product.image_url = Some(url.to_string());
+        }
+        if let Some(archived) = archived {
+            product.archived = archived;
+        }
+        if errors.is_empty() {
+            if let Err(e) = product.validate(&catalog) {
+                errors.push(e.to_string());
+            }
+        }
+
+        let outcome = if !errors.is_empty() {
+            RowOutcome::Invalid(errors)
+        } else if let Some(i) = existing {
+            let changes = catalog[i].changes(&product);
+            let archived_changed = catalog[i].archived != product.archived;
+            catalog[i] = product.clone();
+            if changes.is_empty() && !archived_changed {
+                RowOutcome::Unchanged(product)
+            } else {
+                RowOutcome::Update { product, changes }
+            }
+        } else {
+            catalog.push(product.clone());
+            RowOutcome::Create(product)
+        };
+        rows.push(ImportRow { number: row.number, outcome });
+    }
+
+    // Only categories some valid new or changed product ends up in.
+    new_categories.retain(|c| {
+        rows.iter().any(|r| match &r.outcome {
+            RowOutcome::Create(p) | RowOutcome::Update { product: p, .. } => p.category == c.id,
+            _ => false,
+        })
+    });
+    ImportPlan { rows, new_categories }
+}
+
+// "4,50" from a spreadsheet using a decimal comma becomes "4.50"; "1,250"
+// stays a thousands separator.
+fn decimal_point(text: &str) -> String {
+    match text.rsplit_once(',') {
+        Some((whole, decimals)) if !text.contains('.') && !whole.contains(',') && (1..=2).contains(&decimals.len()) => {
+            format!("{whole}.{decimals}")
+        }
+        _ => text.to_string(),
+    }
+}
+
+impl ImportPlan {
+    pub fn counts(&self) -> ImportCounts {
+        let mut counts = ImportCounts::default();
+        for row in &self.rows {
+            match row.outcome {
+                RowOutcome::Create(_) => counts.created += 1,
+                RowOutcome::Update { .. } => counts.updated += 1,
+                RowOutcome::Unchanged(_) => counts.unchanged += 1,
+                RowOutcome::Invalid(_) => counts.invalid += 1,
+            }
+        }
+        counts
+    }
+}
+
+impl ImportCounts {
+    // "3 new, 12 updated, 40 unchanged, 1 with errors".
+    pub fn summary(&self) -> String {
+        format!(
+            "{} new, {} updated, {} unchanged, {} with errors",
+            self.created, self.updated, self.unchanged, self.invalid
+        )
+    }
+}
@@ -0,0 +1,7 @@
+pub mod table;
+pub mod import;
+pub mod export;
+
+pub use table::*;
+pub use import::*;
+pub use export::*;
@@ -0,0 +1,172 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use serde_json::Value;
+
+// A file read into rows of text cells, before any column means anything.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<TableRow>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRow {
+    // Spreadsheet row for CSV (the header is row 1), item number for JSON.
+    pub number: usize,
+    pub cells: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug)]
+pub enum TableError {
+    Io(std::io::Error),
+    // Neither .csv nor .json.
+    UnknownFormat,
+    Json(serde_json::Error),
+    // JSON that isn't a list of objects.
+    NotAList,
+    Empty,
+}
+
+impl FileFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(FileFormat::Csv),
+            "json" => Some(FileFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            FileFormat::Csv => "csv",
+            FileFormat::Json => "json",
+        }
+    }
+}
+
+pub fn read_table(path: &Path) -> Result<Table, TableError> {
+    let format = FileFormat::from_path(path).ok_or(TableError::UnknownFormat)?;
+    let text = fs::read_to_string(path).map_err(TableError::Io)?;
+    match format {
+        FileFormat::Csv => parse_csv(&text),
+        FileFormat::Json => parse_json(&text),
+    }
+}
+
+// RFC 4180 with quoted fields, doubled quotes and line breaks inside quotes.
+// Files saved by spreadsheets set to a comma decimal use `;` between
+// fields; that is picked up from the header line.
+pub fn parse_csv(text: &str) -> Result<Table, TableError> {
+    let text = text.trim_start_matches('\u{feff}');
+    let first_line = text.lines().next().unwrap_or_default();
+    let separator = if first_line.matches(';').count() > first_line.matches(',').count() { ';' } else { ',' };
+
+    let mut records: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                field.push('\n');
+                line += 1;
+            }
+            '\r' if !quoted => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                records.push((start_line, std::mem::take(&mut fields)));
+                line += 1;
+                start_line = line;
+            }
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start_line, fields));
+    }
+    // Blank lines, including the one a trailing newline leaves.
+    records.retain(|(_, cells)| cells.iter().any(|c| !c.trim().is_empty()));
+
+    let mut records = records.into_iter();
+    let (_, headers) = records.next().ok_or(TableError::Empty)?;
+    Ok(Table {
+        headers: headers.into_iter().map(|h| h.trim().to_string()).collect(),
+        rows: records.map(|(number, cells)| TableRow { number, cells }).collect(),
+    })
+}
+
+// A list of objects, or an object with a `products` list. Keys become the
+// columns in the order first seen; lists are joined with `;`.
+pub fn parse_json(text: &str) -> Result<Table, TableError> {
+    let value: Value = serde_json::from_str(text).map_err(TableError::Json)?;
+    let items = match value {
+        Value::Array(items) => items,
+        Value::Object(mut object) => match object.remove("products") {
+            Some(Value::Array(items)) => items,
+            _ => return Err(TableError::NotAList),
+        },
+        _ => return Err(TableError::NotAList),
+    };
+
+    let mut headers: Vec<String> = Vec::new();
+    for item in &items {
+        let Value::Object(object) = item else {
+            return Err(TableError::NotAList);
+        };
+        for key in object.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let rows = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| TableRow {
+            number: i + 1,
+            cells: headers.iter().map(|h| cell_text(item.get(h).unwrap_or(&Value::Null))).collect(),
+        })
+        .collect();
+    Ok(Table { headers, rows })
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(cell_text).collect::<Vec<_>>().join(";"),
+        other => other.to_string(),
+    }
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::Io(e) => write!(f, "{e}"),
+            TableError::UnknownFormat => write!(f, "only .csv and .json files can be imported"),
+            TableError::Json(e) => write!(f, "not valid JSON: {e}"),
+            TableError::NotAList => write!(f, "expected a list of products"),
+            TableError::Empty => write!(f, "the file is empty"),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
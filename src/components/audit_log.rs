@@ -0,0 +1,185 @@
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+use crate::models::{AuditAction, AuditEntry};
+
+#[component]
+pub fn AuditLog(
+    entries: Signal<Vec<AuditEntry>>,
+    // Outcome of the last verification.
+    status: Option<String>,
+    on_verify: EventHandler<()>,
+) -> Element {
+    let mut action = use_signal(|| None::<AuditAction>);
+    let mut actor = use_signal(String::new);
+    let mut day = use_signal(|| None::<NaiveDate>);
+    let mut search = use_signal(String::new);
+
+    let log = entries.read();
+    let mut actors: Vec<String> = log.iter().filter_map(|e| e.actor_name.clone()).collect();
+    actors.sort();
+    actors.dedup();
+
+    let query = search().to_lowercase();
+    let shown: Vec<AuditEntry> = log
+        .iter()
+        .rev()
+        .filter(|e| action().is_none() || action() == Some(e.action))
+        .filter(|e| actor().is_empty() || e.actor_name.as_deref() == Some(actor().as_str()))
+        .filter(|e| day().is_none() || day() == Some(e.at.date_naive()))
+        .filter(|e| {
+            query.is_empty()
+                || e.detail.to_lowercase().contains(&query)
+                || e.subject.as_deref().is_some_and(|s| s.starts_with(&query))
+        })
+        .cloned()
+        .collect();
+    let head = log.last().map(|e| e.hash[..16].to_string()).unwrap_or_else(|| "—".to_string());
+
+    rsx! {
+        div {
+            class: "order-history-container",
+
+            div {
+                class: "history-header",
+                h2 { "Audit Log" }
+                div {
+                    class: "history-stats",
+                    div {
+                        class: "stat",
+                        span { class: "stat-label", "Entries:" }
+                        span { class: "stat-value", "{log.len()}" }
+                    }
+                    div {
+                        class: "stat",
+                        span { class: "stat-label", "Last hash:" }
+                        span { class: "stat-value audit-hash", "{head}" }
+                    }
+                    button {
+                        class: "btn-secondary",
+                        onclick: move |_| on_verify.call(()),
+                        "Verify"
+                    }
+                }
+            }
+
+            if let Some(message) = status {
+                div { class: "audit-status", "{message}" }
+            }
+
+            div {
+                class: "audit-filters",
+                select {
+                    class: "form-select",
+                    onchange: move |e| action.set(AuditAction::from_label(&e.value())),
+                    option { value: "", "All actions" }
+                    for kind in AuditAction::ALL {
+                        option {
+                            value: "{kind.as_str()}",
+                            selected: action() == Some(kind),
+                            "{kind.as_str()}"
+                        }
+                    }
+                }
+                select {
+                    class: "form-select",
+                    onchange: move |e| actor.set(e.value()),
+                    option { value: "", "All employees" }
+                    for name in actors {
+                        option {
+                            value: "{name}",
+                            selected: actor() == name,
+                            "{name}"
+                        }
+                    }
+                }
+                input {
+                    r#type: "date",
+                    class: "form-input",
+                    oninput: move |e| day.set(NaiveDate::parse_from_str(&e.value(), "%Y-%m-%d").ok()),
+                }
+                input {
+                    r#type: "text",
+                    class: "form-input",
+                    placeholder: "Search details or id",
+                    value: "{search}",
+                    oninput: move |e| search.set(e.value()),
+                }
+            }
+
+            div {
+                class: "history-list",
+                if shown.is_empty() {
+                    div {
+                        class: "empty-history",
+                        p { "No matching entries" }
+                    }
+                }
+                for entry in shown {
+                    AuditLogItem { key: "{entry.seq}", entry }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn AuditLogItem(entry: AuditEntry) -> Element {
+    let mut expanded = use_signal(|| false);
+    let at = entry.at.format("%b %d, %Y %I:%M:%S %p").to_string();
+    let actor = entry.actor_name.clone().unwrap_or_else(|| "—".to_string());
+    let before = entry.before.as_ref().and_then(|v| serde_json::to_string_pretty(v).ok());
+    let after = entry.after.as_ref().and_then(|v| serde_json::to_string_pretty(v).ok());
+
+    rsx! {
+        div {
+            class: "history-item",
+
+            div {
+                class: "history-item-header",
+                onclick: move |_| expanded.set(!expanded()),
+
+                div {
+                    class: "order-info",
+                    div { class: "order-id", "#{entry.seq} {entry.action.as_str()}" }
+                    div { class: "order-time", "{at} · {actor}" }
+                }
+
+                div {
+                    class: "order-summary",
+                    span { class: "order-items", "{entry.detail}" }
+                }
+
+                span {
+                    class: "expand-icon",
+                    {if expanded() { "▼" } else { "▶" }}
+                }
+            }
+
+            if expanded() {
+                div {
+                    class: "history-item-details",
+                    if let Some(subject) = &entry.subject {
+                        p { class: "audit-meta", "Subject: {subject}" }
+                    }
+                    div {
+                        class: "audit-snapshots",
+                        if let Some(before) = before {
+                            div {
+                                h4 { "Before" }
+                                pre { class: "audit-json", "{before}" }
+                            }
+                        }
+                        if let Some(after) = after {
+                            div {
+                                h4 { "After" }
+                                pre { class: "audit-json", "{after}" }
+                            }
+                        }
+                    }
+                    p { class: "audit-meta audit-hash", "Previous: {entry.prev_hash}" }
+                    p { class: "audit-meta audit-hash", "Hash: {entry.hash}" }
+                }
+            }
+        }
+    }
+}
@@ -1,14 +1,49 @@
 use dioxus::prelude::*;
-use crate::models::Order;
+use super::customer_lookup::CustomerLookup;
+use crate::models::{
+    AppliedDiscount, Currency, Customer, Discount, DiscountKind, DiscountTarget, Money, Order, Rate, ReasonCode, TaxMode,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceOverride {
+    pub line_id: String,
+    pub price: Money,
+}
 
 #[component]
 pub fn Cart(
     order: Signal<Order>,
+    on_increment: EventHandler<String>,
+    on_decrement: EventHandler<String>,
     on_checkout: EventHandler<()>,
     on_clear: EventHandler<()>,
+    // Parks the order under the label; a blank label gets a numbered one.
+    on_hold: EventHandler<String>,
+    on_void: EventHandler<ReasonCode>,
+    on_price_override: EventHandler<PriceOverride>,
+    on_discount: EventHandler<Discount>,
+    // Id of the manual discount to take off.
+    on_remove_discount: EventHandler<String>,
+    on_coupon: EventHandler<String>,
+    on_remove_coupon: EventHandler<String>,
+    // For attaching a customer to the order.
+    customers: Signal<Vec<Customer>>,
+    on_attach_customer: EventHandler<Customer>,
+    on_detach_customer: EventHandler<()>,
 ) -> Element {
+    let mut voiding = use_signal(|| false);
+    let mut void_reason = use_signal(|| ReasonCode::CustomerReturn);
+    let mut holding = use_signal(|| false);
+    let mut hold_label = use_signal(String::new);
+    let mut coupon = use_signal(String::new);
     let current_order = order.read();
     let is_empty = current_order.items.is_empty();
+    let tax_included = current_order.tax_mode == TaxMode::Inclusive;
+    let line_discounts = current_order
+        .items
+        .iter()
+        .flat_map(|i| &i.discounts)
+        .fold(Money::zero(current_order.currency), |sum, d| sum + d.amount);
 
     rsx! {
         div {
@@ -18,14 +53,97 @@ pub fn Cart(
                 class: "cart-header",
                 h2 { "Current Order" }
                 if !is_empty {
+                    div {
+                        class: "cart-header-actions",
+                        button {
+                            class: "clear-btn",
+                            onclick: move |_| {
+                                holding.set(!holding());
+                                voiding.set(false);
+                            },
+                            "Hold"
+                        }
+                        button {
+                            class: "clear-btn",
+                            onclick: move |_| {
+                                voiding.set(!voiding());
+                                holding.set(false);
+                            },
+                            "Void"
+                        }
+                        button {
+                            class: "clear-btn",
+                            onclick: move |_| on_clear.call(()),
+                            "Clear"
+                        }
+                    }
+                }
+            }
+
+            if holding() && !is_empty {
+                div {
+                    class: "hold-bar",
+                    input {
+                        class: "form-input",
+                        value: "{hold_label}",
+                        placeholder: "Name or note (optional)",
+                        oninput: move |e| hold_label.set(e.value()),
+                        onkeydown: move |e| {
+                            if e.key() == Key::Enter {
+                                on_hold.call(hold_label());
+                                hold_label.set(String::new());
+                                holding.set(false);
+                            }
+                        },
+                    }
                     button {
-                        class: "clear-btn",
-                        onclick: move |_| on_clear.call(()),
-                        "Clear"
+                        class: "btn-primary",
+                        onclick: move |_| {
+                            on_hold.call(hold_label());
+                            hold_label.set(String::new());
+                            holding.set(false);
+                        },
+                        "Hold Order"
                     }
                 }
             }
 
+            if voiding() && !is_empty {
+                div {
+                    class: "void-bar",
+                    select {
+                        class: "form-select",
+                        onchange: move |e| {
+                            if let Some(code) = ReasonCode::from_label(&e.value()) {
+                                void_reason.set(code);
+                            }
+                        },
+                        for code in ReasonCode::ALL {
+                            option {
+                                value: "{code.as_str()}",
+                                selected: code == void_reason(),
+                                "{code.as_str()}"
+                            }
+                        }
+                    }
+                    button {
+                        class: "btn-danger-small",
+                        onclick: move |_| {
+                            on_void.call(void_reason());
+                            voiding.set(false);
+                        },
+                        "Void Order"
+                    }
+                }
+            }
+
+            CustomerLookup {
+                customers,
+                attached: current_order.customer.clone(),
+                on_attach: move |customer: Customer| on_attach_customer.call(customer),
+                on_detach: move |_| on_detach_customer.call(()),
+            }
+
             div {
                 class: "cart-items",
                 if is_empty {
@@ -37,28 +155,29 @@ pub fn Cart(
                 } else {
                     for item in current_order.items.iter() {
                         {
-                            let product_id = item.product_id.clone();
+                            let line_id = item.line_id.clone();
                             let product_name = item.product_name.clone();
+                            let options: Vec<String> = item.options.iter().map(|o| o.name.clone()).collect();
                             let price = item.price;
                             let quantity = item.quantity;
+                            let discounts = item.discounts.clone();
+                            let fixed = item.gift_card.is_some();
 
                             rsx! {
                                 CartItem {
-                                    key: "{product_id}",
-                                    product_id: product_id.clone(),
+                                    key: "{line_id}",
+                                    line_id: line_id.clone(),
                                     product_name: product_name,
+                                    options: options,
                                     price: price,
                                     quantity: quantity,
-                                    on_add: move |id: String| {
-                                        let mut o = order.write();
-                                        if let Some(item) = o.items.iter_mut().find(|i| i.product_id == id) {
-                                            item.quantity += 1;
-                                            o.calculate_totals();
-                                        }
-                                    },
-                                    on_remove: move |id: String| {
-                                        order.write().remove_item(&id);
-                                    },
+                                    discounts: discounts,
+                                    fixed,
+                                    on_add: move |id: String| on_increment.call(id),
+                                    on_override: move |request: PriceOverride| on_price_override.call(request),
+                                    on_discount: move |discount: Discount| on_discount.call(discount),
+                                    on_remove_discount: move |id: String| on_remove_discount.call(id),
+                                    on_remove: move |id: String| on_decrement.call(id),
                                 }
                             }
                         }
@@ -73,17 +192,90 @@ pub fn Cart(
                     div {
                         class: "summary-row",
                         span { "Subtotal:" }
-                        span { "${current_order.subtotal:.2}" }
+                        span { "{current_order.subtotal}" }
                     }
-                    div {
-                        class: "summary-row",
-                        span { "Tax (8%):" }
-                        span { "${current_order.tax:.2}" }
+                    if line_discounts.minor() != 0 {
+                        div {
+                            class: "summary-row discount-row",
+                            span { "Item discounts:" }
+                            span { "{-line_discounts}" }
+                        }
+                    }
+                    for applied in current_order.order_discounts.iter() {
+                        DiscountRow {
+                            applied: applied.clone(),
+                            on_remove: move |id: String| on_remove_discount.call(id),
+                        }
+                    }
+                    if current_order.is_tax_exempt() {
+                        div {
+                            class: "summary-row",
+                            span { "Tax:" }
+                            span { "Exempt" }
+                        }
+                    }
+                    for line in current_order.tax_lines.iter() {
+                        div {
+                            key: "{line.rate_id}",
+                            class: "summary-row",
+                            span {
+                                if tax_included { "Incl. " }
+                                "{line.name} ({line.rate}):"
+                            }
+                            span { "{line.amount}" }
+                        }
                     }
                     div {
                         class: "summary-row total",
                         span { "Total:" }
-                        span { "${current_order.total:.2}" }
+                        span { "{current_order.total}" }
+                    }
+
+                    OrderDiscountEditor {
+                        on_discount: move |discount: Discount| on_discount.call(discount),
+                    }
+
+                    div {
+                        class: "coupon-bar",
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            placeholder: "Coupon code",
+                            value: "{coupon}",
+                            oninput: move |e| coupon.set(e.value()),
+                        }
+                        button {
+                            class: "btn-secondary-small",
+                            disabled: coupon().trim().is_empty(),
+                            onclick: move |_| {
+                                on_coupon.call(coupon());
+                                coupon.set(String::new());
+                            },
+                            "Apply"
+                        }
+                    }
+                    if !current_order.coupons.is_empty() {
+                        div {
+                            class: "coupon-list",
+                            for code in current_order.coupons.iter() {
+                                {
+                                    let code = code.clone();
+                                    rsx! {
+                                        span {
+                                            key: "{code}",
+                                            class: "coupon-chip",
+                                            "{code}"
+                                            button {
+                                                class: "discount-remove-btn",
+                                                title: "Remove coupon",
+                                                onclick: move |_| on_remove_coupon.call(code.clone()),
+                                                "✕"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
                     }
 
                     button {
@@ -99,15 +291,28 @@ pub fn Cart(
 
 #[component]
 fn CartItem(
-    product_id: String,
+    line_id: String,
     product_name: String,
-    price: f64,
+    // Names of the chosen variants and modifiers.
+    options: Vec<String>,
+    price: Money,
     quantity: u32,
+    discounts: Vec<AppliedDiscount>,
+    // A gift card: sold at face value, one per line.
+    fixed: bool,
     on_add: EventHandler<String>,
     on_remove: EventHandler<String>,
+    on_override: EventHandler<PriceOverride>,
+    on_discount: EventHandler<Discount>,
+    on_remove_discount: EventHandler<String>,
 ) -> Element {
-    let id = product_id.clone();
-    let id2 = product_id.clone();
+    let id = line_id.clone();
+    let id2 = line_id.clone();
+    let mut editing = use_signal(|| false);
+    let mut mode = use_signal(|| EditMode::Price);
+    let mut new_price = use_signal(String::new);
+    let net = discounts.iter().fold(price * quantity, |sum, d| sum - d.amount);
+    let discounted = !discounts.is_empty();
 
     rsx! {
         div {
@@ -116,7 +321,78 @@ fn CartItem(
             div {
                 class: "item-info",
                 div { class: "item-name", "{product_name}" }
-                div { class: "item-price", "${price:.2}" }
+                if !options.is_empty() {
+                    div { class: "item-options", {options.join(", ")} }
+                }
+                if editing() {
+                    div {
+                        class: "price-edit",
+                        select {
+                            class: "form-select",
+                            onchange: move |e| {
+                                if let Some(m) = EditMode::from_label(&e.value()) {
+                                    mode.set(m);
+                                    new_price.set(String::new());
+                                }
+                            },
+                            for m in EditMode::ALL {
+                                option {
+                                    value: "{m.as_str()}",
+                                    selected: m == mode(),
+                                    "{m.as_str()}"
+                                }
+                            }
+                        }
+                        input {
+                            r#type: "number",
+                            class: "form-input",
+                            value: "{new_price}",
+                            oninput: move |e| new_price.set(e.value()),
+                            step: "0.01",
+                        }
+                        button {
+                            class: "btn-secondary-small",
+                            onclick: move |_| {
+                                let target = DiscountTarget::Line(line_id.clone());
+                                let done = match mode() {
+                                    EditMode::Price => Money::parse(&new_price(), Currency::default())
+                                        .filter(|p| p.minor() >= 0)
+                                        .map(|price| on_override.call(PriceOverride { line_id: line_id.clone(), price })),
+                                    m => m
+                                        .discount(&new_price())
+                                        .map(|kind| on_discount.call(Discount::new(kind, target))),
+                                };
+                                if done.is_some() {
+                                    editing.set(false);
+                                }
+                            },
+                            "Set"
+                        }
+                    }
+                } else {
+                    div {
+                        class: "item-price",
+                        "{price}"
+                        if !fixed {
+                            button {
+                                class: "price-edit-btn",
+                                title: "Override price or discount",
+                                onclick: move |_| {
+                                    mode.set(EditMode::Price);
+                                    new_price.set(price.plain_string());
+                                    editing.set(true);
+                                },
+                                "✎"
+                            }
+                        }
+                    }
+                }
+                for applied in discounts {
+                    DiscountRow {
+                        applied,
+                        on_remove: move |id: String| on_remove_discount.call(id),
+                    }
+                }
             }
 
             div {
@@ -129,6 +405,7 @@ fn CartItem(
                 span { class: "qty", "{quantity}" }
                 button {
                     class: "qty-btn",
+                    disabled: fixed,
                     onclick: move |_| on_add.call(id2.clone()),
                     "+"
                 }
@@ -136,8 +413,117 @@ fn CartItem(
 
             div {
                 class: "item-total",
-                "${price * quantity as f64:.2}"
+                if discounted {
+                    span { class: "original-price", "{price * quantity}" }
+                    " "
+                }
+                "{net}"
             }
         }
     }
 }
+
+// A discount as a separate line, with a remove button if it was keyed in.
+#[component]
+fn DiscountRow(applied: AppliedDiscount, on_remove: EventHandler<String>) -> Element {
+    let manual_id = applied.manual_id().map(str::to_string);
+
+    rsx! {
+        div {
+            class: "summary-row discount-row",
+            span {
+                "{applied.name}"
+                if let Some(id) = manual_id {
+                    button {
+                        class: "discount-remove-btn",
+                        title: "Remove discount",
+                        onclick: move |_| on_remove.call(id.clone()),
+                        "✕"
+                    }
+                }
+            }
+            span { "{-applied.amount}" }
+        }
+    }
+}
+
+#[component]
+fn OrderDiscountEditor(on_discount: EventHandler<Discount>) -> Element {
+    let mut mode = use_signal(|| EditMode::Percent);
+    let mut value = use_signal(String::new);
+
+    rsx! {
+        div {
+            class: "order-discount-bar",
+            select {
+                class: "form-select",
+                onchange: move |e| {
+                    if let Some(m) = EditMode::from_label(&e.value()) {
+                        mode.set(m);
+                    }
+                },
+                for m in [EditMode::Percent, EditMode::Amount] {
+                    option {
+                        value: "{m.as_str()}",
+                        selected: m == mode(),
+                        "{m.as_str()}"
+                    }
+                }
+            }
+            input {
+                r#type: "number",
+                class: "form-input",
+                placeholder: "Order discount",
+                value: "{value}",
+                oninput: move |e| value.set(e.value()),
+                step: "0.01",
+            }
+            button {
+                class: "btn-secondary-small",
+                onclick: move |_| {
+                    if let Some(kind) = mode().discount(&value()) {
+                        on_discount.call(Discount::new(kind, DiscountTarget::Order));
+                        value.set(String::new());
+                    }
+                },
+                "Discount"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditMode {
+    Price,
+    Percent,
+    Amount,
+}
+
+impl EditMode {
+    const ALL: [EditMode; 3] = [EditMode::Price, EditMode::Percent, EditMode::Amount];
+
+    fn as_str(&self) -> &str {
+        match self {
+            EditMode::Price => "Price",
+            EditMode::Percent => "% off",
+            EditMode::Amount => "$ off",
+        }
+    }
+
+    fn from_label(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == value)
+    }
+
+    // The discount typed in, if it is valid for this mode.
+    fn discount(&self, input: &str) -> Option<DiscountKind> {
+        match self {
+            EditMode::Price => None,
+            EditMode::Percent => Rate::parse_percent(input)
+                .filter(|r| r.0 > 0 && r.0 <= 1_000_000)
+                .map(DiscountKind::Percent),
+            EditMode::Amount => Money::parse(input, Currency::default())
+                .filter(|m| m.minor() > 0)
+                .map(DiscountKind::Amount),
+        }
+    }
+}
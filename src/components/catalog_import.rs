@@ -0,0 +1,265 @@
+use std::path::Path;
+use dioxus::prelude::*;
+use crate::catalog::{guess_mapping, plan_import, read_table, Field, FileFormat, ImportPlan, RowOutcome, Table};
+use crate::models::{category_name, Category, Product, TaxConfig};
+
+// Rows listed in the preview besides the ones with errors, which are
+// always listed.
+const PREVIEW_ROWS: usize = 100;
+
+#[component]
+pub fn CatalogImport(
+    products: Signal<Vec<Product>>,
+    categories: Signal<Vec<Category>>,
+    tax_config: Signal<TaxConfig>,
+    // Outcome of the last import or export.
+    status: Option<String>,
+    // The checked file, applied as it was previewed.
+    on_import: EventHandler<ImportPlan>,
+    on_export: EventHandler<FileFormat>,
+) -> Element {
+    let mut open = use_signal(|| false);
+    let mut path = use_signal(String::new);
+    let mut table = use_signal(|| None::<Table>);
+    let mut mapping = use_signal(Vec::<Option<Field>>::new);
+    let mut load_error = use_signal(|| None::<String>);
+    let mut dry_run = use_signal(|| true);
+    let mut dry_run_result = use_signal(|| None::<String>);
+
+    // Checked again whenever the mapping or the catalog changes.
+    let import_plan = use_memo(move || {
+        table.read().as_ref().map(|t| {
+            plan_import(t, &mapping.read(), &products.read(), &categories.read(), &tax_config.read().classes)
+        })
+    });
+
+    let mut load = move || {
+        dry_run_result.set(None);
+        match read_table(Path::new(path().trim())) {
+            Ok(loaded) => {
+                mapping.set(guess_mapping(&loaded.headers));
+                table.set(Some(loaded));
+                load_error.set(None);
+            }
+            Err(e) => {
+                table.set(None);
+                load_error.set(Some(e.to_string()));
+            }
+        }
+    };
+
+    let current_plan = import_plan();
+    let counts = current_plan.as_ref().map(|p| p.counts()).unwrap_or_default();
+    let can_run = counts.created + counts.updated > 0;
+    let headers = table.read().as_ref().map(|t| t.headers.clone()).unwrap_or_default();
+    let category_list = categories.read();
+
+    rsx! {
+        div {
+            class: "catalog-import",
+
+            div {
+                class: "manager-header",
+                h3 { "Import / Export" }
+                div {
+                    class: "manager-actions",
+                    button {
+                        class: "btn-secondary",
+                        onclick: move |_| on_export.call(FileFormat::Csv),
+                        "Export CSV"
+                    }
+                    button {
+                        class: "btn-secondary",
+                        onclick: move |_| on_export.call(FileFormat::Json),
+                        "Export JSON"
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| open.set(!open()),
+                        if open() { "Close Import" } else { "Import File" }
+                    }
+                }
+            }
+            if let Some(message) = status {
+                p { class: "hint", "{message}" }
+            }
+
+            if open() {
+                div {
+                    class: "catalog-import-load",
+                    input {
+                        class: "form-input",
+                        value: "{path}",
+                        placeholder: "Path to a .csv or .json file",
+                        oninput: move |e| path.set(e.value()),
+                        onkeydown: move |e| {
+                            if e.key() == Key::Enter {
+                                load();
+                            }
+                        },
+                    }
+                    button {
+                        class: "btn-secondary",
+                        disabled: path().trim().is_empty(),
+                        onclick: move |_| load(),
+                        "Load"
+                    }
+                }
+                if let Some(message) = load_error() {
+                    p { class: "form-error", "{message}" }
+                }
+
+                if !headers.is_empty() {
+                    // Column mapping
+                    div {
+                        class: "catalog-mapping",
+                        for (i, header) in headers.iter().enumerate() {
+                            div {
+                                key: "{i}",
+                                class: "form-group",
+                                label { "{header}" }
+                                select {
+                                    class: "form-select",
+                                    onchange: move |e| {
+                                        let field = Field::ALL.iter().copied().find(|f| f.key() == e.value());
+                                        let mut columns = mapping.write();
+                                        // A field comes from one column only.
+                                        for column in columns.iter_mut() {
+                                            if field.is_some() && *column == field {
+                                                *column = None;
+                                            }
+                                        }
+                                        if let Some(column) = columns.get_mut(i) {
+                                            *column = field;
+                                        }
+                                        dry_run_result.set(None);
+                                    },
+                                    option { value: "", selected: mapping.read().get(i).copied().flatten().is_none(), "Skip" }
+                                    for field in Field::ALL {
+                                        option {
+                                            value: "{field.key()}",
+                                            selected: mapping.read().get(i).copied().flatten() == Some(field),
+                                            "{field.as_str()}"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if let Some(plan) = current_plan {
+                    p { class: "catalog-summary", "{counts.summary()}" }
+                    if !plan.new_categories.is_empty() {
+                        p {
+                            class: "hint",
+                            "New categories: "
+                            {plan.new_categories.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")}
+                        }
+                    }
+
+                    // Preview
+                    table {
+                        class: "ledger-table catalog-preview",
+                        thead {
+                            tr {
+                                th { "Row" }
+                                th { "Result" }
+                                th { "Name" }
+                                th { "SKU" }
+                                th { "Price" }
+                                th { "Details" }
+                            }
+                        }
+                        tbody {
+                            for (i, row) in plan.rows.iter().enumerate().filter(|(i, r)| *i < PREVIEW_ROWS || matches!(r.outcome, RowOutcome::Invalid(_))) {
+                                tr {
+                                    key: "{i}",
+                                    class: match row.outcome {
+                                        RowOutcome::Invalid(_) => "preview-invalid",
+                                        RowOutcome::Create(_) => "preview-create",
+                                        RowOutcome::Update { .. } => "preview-update",
+                                        RowOutcome::Unchanged(_) => "",
+                                    },
+                                    td { "{row.number}" }
+                                    match &row.outcome {
+                                        RowOutcome::Invalid(errors) => rsx! {
+                                            td { "Error" }
+                                            td { colspan: "3" }
+                                            td { {errors.join("; ")} }
+                                        },
+                                        RowOutcome::Create(product) | RowOutcome::Update { product, .. } | RowOutcome::Unchanged(product) => {
+                                            let (result, details) = match &row.outcome {
+                                                RowOutcome::Create(_) => (
+                                                    "New",
+                                                    format!(
+                                                        "{} · stock {}",
+                                                        category_name(&category_list, &product.category),
+                                                        product.stock
+                                                    ),
+                                                ),
+                                                RowOutcome::Update { changes, .. } if changes.is_empty() => ("Update", "archived".to_string()),
+                                                RowOutcome::Update { changes, .. } => ("Update", changes.join(", ")),
+                                                _ => ("Unchanged", String::new()),
+                                            };
+                                            rsx! {
+                                                td { "{result}" }
+                                                td { "{product.name}" }
+                                                td { {product.sku.as_deref().unwrap_or("—")} }
+                                                td { "{product.price}" }
+                                                td { "{details}" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    if plan.rows.len() > PREVIEW_ROWS {
+                        p { class: "hint", "Showing the first {PREVIEW_ROWS} of {plan.rows.len()} rows and every row with errors" }
+                    }
+
+                    div {
+                        class: "catalog-import-actions",
+                        label {
+                            class: "checkbox-label",
+                            input {
+                                r#type: "checkbox",
+                                checked: dry_run(),
+                                onchange: move |e| {
+                                    dry_run.set(e.checked());
+                                    dry_run_result.set(None);
+                                },
+                            }
+                            "Dry run (nothing is saved)"
+                        }
+                        button {
+                            class: "btn-primary",
+                            disabled: !can_run,
+                            onclick: move |_| {
+                                if dry_run() {
+                                    dry_run_result.set(Some(format!("Dry run: would import {}", counts.summary())));
+                                    return;
+                                }
+                                if let Some(plan) = import_plan() {
+                                    on_import.call(plan);
+                                }
+                                table.set(None);
+                                mapping.set(Vec::new());
+                                path.set(String::new());
+                                open.set(false);
+                            },
+                            if dry_run() { "Check Import" } else { "Import" }
+                        }
+                        if counts.invalid > 0 {
+                            span { class: "hint", "Rows with errors are skipped" }
+                        }
+                    }
+                    if let Some(message) = dry_run_result() {
+                        p { class: "hint", "{message}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use dioxus::prelude::*;
+use crate::models::{
+    category_name, category_tree, check_category_removable, in_category, validate_category, Category, Product,
+    TaxConfig,
+};
+
+// Add, edit and remove the store's product categories. The same form edits
+// an existing category after "Edit".
+#[component]
+pub fn CategoryManager(
+    categories: Signal<Vec<Category>>,
+    products: Signal<Vec<Product>>,
+    tax_config: Signal<TaxConfig>,
+    on_save: EventHandler<Category>,
+    on_delete: EventHandler<String>,
+) -> Element {
+    let mut show_form = use_signal(|| false);
+    let mut form = use_signal(CategoryForm::default);
+    let mut error = use_signal(|| None::<String>);
+
+    let list = categories.read().clone();
+    let catalog = products.read();
+    let classes = tax_config.read().classes.clone();
+    let editing_id = form.read().id.clone();
+    // A category can't move under itself or its own sub-categories.
+    let parents: Vec<(String, String)> = category_tree(&list)
+        .into_iter()
+        .filter(|(_, c)| match editing_id.as_deref() {
+            Some(id) => !in_category(&list, &c.id, id),
+            None => true,
+        })
+        .map(|(depth, c)| (c.id.clone(), format!("{}{}", "— ".repeat(depth), c.name)))
+        .collect();
+    let rows: Vec<(usize, Category, usize)> = category_tree(&list)
+        .into_iter()
+        .map(|(depth, c)| (depth, c.clone(), catalog.iter().filter(|p| p.category == c.id).count()))
+        .collect();
+    let save_list = list.clone();
+    let delete_list = list.clone();
+    let delete_products = catalog.to_vec();
+
+    rsx! {
+        div {
+            class: "product-manager-container",
+
+            div {
+                class: "manager-header",
+                h2 { "Categories" }
+                button {
+                    class: "btn-primary",
+                    onclick: move |_| {
+                        form.set(CategoryForm::default());
+                        error.set(None);
+                        show_form.set(!show_form());
+                    },
+                    if show_form() { "Cancel" } else { "+ Add Category" }
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "lock-error", "{message}" }
+            }
+
+            if show_form() {
+                div {
+                    class: "add-product-form",
+                    div {
+                        class: "form-group",
+                        label { "Name" }
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            value: "{form.read().name}",
+                            oninput: move |e| form.write().name = e.value(),
+                            placeholder: "e.g. Hot Drinks",
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Color" }
+                        input {
+                            r#type: "color",
+                            class: "form-input color-input",
+                            value: "{form.read().color}",
+                            oninput: move |e| form.write().color = e.value(),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Sort Order" }
+                        input {
+                            r#type: "number",
+                            class: "form-input",
+                            value: "{form.read().sort_order}",
+                            oninput: move |e| form.write().sort_order = e.value(),
+                            step: "1",
+                            min: "0",
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Parent" }
+                        select {
+                            class: "form-select",
+                            onchange: move |e| form.write().parent_id = e.value(),
+                            option { value: "", "None (top level)" }
+                            for (id, name) in parents.iter() {
+                                option { value: "{id}", selected: form.read().parent_id == *id, "{name}" }
+                            }
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Tax Class" }
+                        select {
+                            class: "form-select",
+                            onchange: move |e| form.write().tax_class = e.value(),
+                            option { value: "", "Parent or store default" }
+                            for class in classes.iter() {
+                                option { value: "{class.id}", selected: form.read().tax_class == class.id, "{class.name}" }
+                            }
+                        }
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| {
+                            let category = match form.read().build() {
+                                Ok(category) => category,
+                                Err(message) => {
+                                    error.set(Some(message));
+                                    return;
+                                }
+                            };
+                            if let Err(e) = validate_category(&save_list, &category) {
+                                error.set(Some(e.to_string()));
+                                return;
+                            }
+                            on_save.call(category);
+                            form.set(CategoryForm::default());
+                            error.set(None);
+                            show_form.set(false);
+                        },
+                        if editing_id.is_some() { "Save Category" } else { "Add Category" }
+                    }
+                }
+            }
+
+            div {
+                class: "products-table",
+                table {
+                    thead {
+                        tr {
+                            th { "Name" }
+                            th { "Parent" }
+                            th { "Order" }
+                            th { "Tax Class" }
+                            th { "Products" }
+                            th { "Actions" }
+                        }
+                    }
+                    tbody {
+                        if rows.is_empty() {
+                            tr {
+                                td { colspan: "6", class: "empty-row", "No categories yet" }
+                            }
+                        }
+                        for (depth, category, count) in rows {
+                            tr {
+                                key: "{category.id}",
+                                td {
+                                    span { class: "category-indent", {"— ".repeat(depth)} }
+                                    span {
+                                        class: "category-badge",
+                                        style: "background-color: {category.color};",
+                                        "{category.name}"
+                                    }
+                                }
+                                td {
+                                    {category.parent_id.as_deref().map_or_else(|| "—".to_string(), |id| category_name(&list, id).to_string())}
+                                }
+                                td { "{category.sort_order}" }
+                                td {
+                                    {category
+                                        .tax_class
+                                        .as_deref()
+                                        .map_or_else(
+                                            || "Inherited".to_string(),
+                                            |id| classes.iter().find(|c| c.id == id).map_or_else(|| id.to_string(), |c| c.name.clone()),
+                                        )}
+                                }
+                                td { "{count}" }
+                                td {
+                                    button {
+                                        class: "btn-secondary-small",
+                                        onclick: {
+                                            let category = category.clone();
+                                            move |_| {
+                                                form.set(CategoryForm::editing(&category));
+                                                error.set(None);
+                                                show_form.set(true);
+                                            }
+                                        },
+                                        "Edit"
+                                    }
+                                    button {
+                                        class: "btn-danger-small",
+                                        onclick: {
+                                            let id = category.id.clone();
+                                            let list = delete_list.clone();
+                                            let products = delete_products.clone();
+                                            move |_| match check_category_removable(&list, &products, &id) {
+                                                Ok(()) => {
+                                                    error.set(None);
+                                                    on_delete.call(id.clone());
+                                                }
+                                                Err(e) => error.set(Some(format!("Can't delete: {e}"))),
+                                            }
+                                        },
+                                        "Delete"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Text fields of the add/edit form.
+#[derive(Debug, Clone, PartialEq)]
+struct CategoryForm {
+    // Set when editing an existing category.
+    id: Option<String>,
+    name: String,
+    color: String,
+    sort_order: String,
+    // Empty for none.
+    parent_id: String,
+    tax_class: String,
+}
+
+impl Default for CategoryForm {
+    fn default() -> Self {
+        Self {
+            id: None,
+            name: String::new(),
+            color: "#667eea".to_string(),
+            sort_order: "0".to_string(),
+            parent_id: String::new(),
+            tax_class: String::new(),
+        }
+    }
+}
+
+impl CategoryForm {
+    fn editing(category: &Category) -> Self {
+        Self {
+            id: Some(category.id.clone()),
+            name: category.name.clone(),
+            color: category.color.clone(),
+            sort_order: category.sort_order.to_string(),
+            parent_id: category.parent_id.clone().unwrap_or_default(),
+            tax_class: category.tax_class.clone().unwrap_or_default(),
+        }
+    }
+
+    fn build(&self) -> Result<Category, String> {
+        let sort_order = self
+            .sort_order
+            .trim()
+            .parse()
+            .map_err(|_| "Sort order must be a whole number".to_string())?;
+        let mut category = Category::new(self.name.trim().to_string(), self.color.clone());
+        if let Some(id) = &self.id {
+            category.id = id.clone();
+        }
+        category.sort_order = sort_order;
+        category.parent_id = Some(self.parent_id.clone()).filter(|p| !p.is_empty());
+        category.tax_class = Some(self.tax_class.clone()).filter(|c| !c.is_empty());
+        Ok(category)
+    }
+}
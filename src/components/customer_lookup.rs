@@ -0,0 +1,83 @@
+use dioxus::prelude::*;
+use crate::models::{find_customers, Customer, OrderCustomer};
+
+// Matches listed while typing.
+const MAX_MATCHES: usize = 5;
+
+// The order's customer, or a box to find one by phone or name.
+#[component]
+pub fn CustomerLookup(
+    customers: Signal<Vec<Customer>>,
+    attached: Option<OrderCustomer>,
+    on_attach: EventHandler<Customer>,
+    on_detach: EventHandler<()>,
+) -> Element {
+    let mut query = use_signal(String::new);
+
+    if let Some(customer) = attached {
+        return rsx! {
+            div {
+                class: "order-customer",
+                span { class: "order-customer-name", "👤 {customer.name}" }
+                if let Some(certificate) = &customer.exemption_certificate {
+                    span { class: "tax-exempt-badge", "Tax exempt · {certificate}" }
+                }
+                button {
+                    class: "discount-remove-btn",
+                    title: "Remove customer",
+                    onclick: move |_| on_detach.call(()),
+                    "✕"
+                }
+            }
+        };
+    }
+
+    let list = customers.read();
+    let matches: Vec<Customer> = find_customers(&list, &query()).into_iter().take(MAX_MATCHES).cloned().collect();
+    let first = matches.first().cloned();
+    let searching = !query().trim().is_empty();
+
+    rsx! {
+        div {
+            class: "customer-lookup",
+            input {
+                r#type: "search",
+                class: "form-input",
+                value: "{query}",
+                placeholder: "Customer phone or name",
+                oninput: move |e| query.set(e.value()),
+                onkeydown: move |e: KeyboardEvent| {
+                    if e.key() == Key::Enter {
+                        if let Some(customer) = first.clone() {
+                            on_attach.call(customer);
+                            query.set(String::new());
+                        }
+                    }
+                },
+            }
+            if searching {
+                div {
+                    class: "customer-matches",
+                    if matches.is_empty() {
+                        span { class: "hint", "No customer found; add them under Customers" }
+                    }
+                    for customer in matches {
+                        button {
+                            key: "{customer.id}",
+                            class: "customer-match",
+                            onclick: {
+                                let customer = customer.clone();
+                                move |_| {
+                                    on_attach.call(customer.clone());
+                                    query.set(String::new());
+                                }
+                            },
+                            span { "{customer.name}" }
+                            span { class: "hint", {customer.phone.clone().unwrap_or_default()} }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use dioxus::prelude::*;
+use crate::models::{
+    check_points_ledger, customer_stats, find_customers, lifetime_points, points_balance, points_history, purchase_history,
+    Currency, Customer, LoyaltyConfig, Order, PointsEntry,
+};
+
+// The customer list with search, the add/edit form and a customer's
+// purchase history and points ledger.
+#[component]
+pub fn CustomerManager(
+    customers: Signal<Vec<Customer>>,
+    orders: Signal<Vec<Order>>,
+    ledger: Signal<Vec<PointsEntry>>,
+    loyalty: LoyaltyConfig,
+    // Whether the operator may mark customers tax exempt.
+    can_exempt: bool,
+    // A new or edited customer, already validated.
+    on_save: EventHandler<Customer>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    let mut show_form = use_signal(|| false);
+    let mut form = use_signal(CustomerForm::default);
+    let mut error = use_signal(|| None::<String>);
+    // Customer whose purchase history is open.
+    let mut viewing = use_signal(|| None::<String>);
+
+    let list = customers.read().clone();
+    let history = orders.read();
+    let points = ledger.read();
+    let currency = Currency::default();
+    let shown: Vec<Customer> = if query().trim().is_empty() {
+        let mut all = list.clone();
+        all.sort_by_key(|c| c.name.to_lowercase());
+        all
+    } else {
+        find_customers(&list, &query()).into_iter().cloned().collect()
+    };
+    let editing = form.read().original.is_some();
+    let save_list = list.clone();
+    let viewed = viewing().and_then(|id| {
+        let customer = list.iter().find(|c| c.id == id)?.clone();
+        let purchases: Vec<Order> = purchase_history(&history, &id).into_iter().cloned().collect();
+        let entries: Vec<PointsEntry> = points_history(&points, &id).into_iter().cloned().collect();
+        Some((customer, purchases, entries))
+    });
+    // Ids of ledger entries whose balance doesn't add up.
+    let broken: Vec<String> = check_points_ledger(&points).into_iter().map(|e| e.id.clone()).collect();
+
+    rsx! {
+        div {
+            class: "product-manager-container",
+
+            div {
+                class: "manager-header",
+                h2 { "Customers" }
+                button {
+                    class: "btn-primary",
+                    onclick: move |_| {
+                        form.set(CustomerForm::default());
+                        error.set(None);
+                        show_form.set(!show_form());
+                    },
+                    if show_form() { "Cancel" } else { "+ Add Customer" }
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "lock-error", "{message}" }
+            }
+
+            if show_form() {
+                div {
+                    class: "add-product-form",
+                    div {
+                        class: "form-group",
+                        label { "Name" }
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            value: "{form.read().name}",
+                            oninput: move |e| form.write().name = e.value(),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Phone" }
+                        input {
+                            r#type: "tel",
+                            class: "form-input",
+                            value: "{form.read().phone}",
+                            oninput: move |e| form.write().phone = e.value(),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Email" }
+                        input {
+                            r#type: "email",
+                            class: "form-input",
+                            value: "{form.read().email}",
+                            oninput: move |e| form.write().email = e.value(),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Notes" }
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            value: "{form.read().notes}",
+                            oninput: move |e| form.write().notes = e.value(),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label {
+                            input {
+                                r#type: "checkbox",
+                                checked: form.read().tax_exempt,
+                                disabled: !can_exempt,
+                                onchange: move |e| form.write().tax_exempt = e.checked(),
+                            }
+                            " Tax exempt"
+                        }
+                        if form.read().tax_exempt {
+                            input {
+                                r#type: "text",
+                                class: "form-input",
+                                value: "{form.read().certificate}",
+                                disabled: !can_exempt,
+                                placeholder: "Exemption certificate id",
+                                oninput: move |e| form.write().certificate = e.value(),
+                            }
+                        }
+                        if !can_exempt {
+                            span { class: "hint", "A supervisor sets tax exemptions" }
+                        }
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| {
+                            let customer = form.read().build();
+                            if let Err(e) = customer.validate(&save_list) {
+                                error.set(Some(e.to_string()));
+                                return;
+                            }
+                            on_save.call(customer);
+                            form.set(CustomerForm::default());
+                            error.set(None);
+                            show_form.set(false);
+                        },
+                        if editing { "Save Customer" } else { "Add Customer" }
+                    }
+                }
+            }
+
+            input {
+                r#type: "search",
+                class: "form-input customer-search",
+                value: "{query}",
+                placeholder: "Search by phone, name or email",
+                oninput: move |e| query.set(e.value()),
+            }
+
+            div {
+                class: "products-table",
+                table {
+                    thead {
+                        tr {
+                            th { "Name" }
+                            th { "Phone" }
+                            th { "Email" }
+                            th { "Orders" }
+                            th { "Spent" }
+                            th { "Last Visit" }
+                            th { "Points" }
+                            th { "Actions" }
+                        }
+                    }
+                    tbody {
+                        if shown.is_empty() {
+                            tr {
+                                td {
+                                    colspan: "8",
+                                    class: "empty-row",
+                                    if list.is_empty() { "No customers yet" } else { "No customers match" }
+                                }
+                            }
+                        }
+                        for customer in shown {
+                            {
+                                let stats = customer_stats(&history, &customer.id, currency);
+                                let balance = points_balance(&points, &customer.id);
+                                let tier = loyalty.tier(lifetime_points(&points, &customer.id)).map(|t| t.name.clone());
+                                rsx! {
+                                    tr {
+                                        key: "{customer.id}",
+                                        td {
+                                            "{customer.name}"
+                                            if customer.tax_exempt {
+                                                span { class: "hint", " · tax exempt" }
+                                            }
+                                        }
+                                        td { {customer.phone.as_deref().unwrap_or("—")} }
+                                        td { {customer.email.as_deref().unwrap_or("—")} }
+                                        td { "{stats.orders}" }
+                                        td { "{stats.spent}" }
+                                        td {
+                                            {stats.last_visit.map_or_else(|| "—".to_string(), |t| t.format("%b %d, %Y").to_string())}
+                                        }
+                                        td {
+                                            "{balance}"
+                                            if let Some(tier) = tier {
+                                                span { class: "hint", " · {tier}" }
+                                            }
+                                        }
+                                        td {
+                                            button {
+                                                class: "btn-secondary-small",
+                                                onclick: {
+                                                    let customer = customer.clone();
+                                                    move |_| {
+                                                        form.set(CustomerForm::editing(&customer));
+                                                        error.set(None);
+                                                        show_form.set(true);
+                                                    }
+                                                },
+                                                "Edit"
+                                            }
+                                            button {
+                                                class: "btn-secondary-small",
+                                                onclick: {
+                                                    let id = customer.id.clone();
+                                                    move |_| {
+                                                        let open = viewing().as_deref() == Some(id.as_str());
+                                                        viewing.set(if open { None } else { Some(id.clone()) });
+                                                    }
+                                                },
+                                                "History"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some((customer, purchases, entries)) = viewed {
+                CustomerHistory {
+                    customer,
+                    orders: purchases,
+                    points: entries,
+                    broken,
+                    on_close: move |_| viewing.set(None),
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn CustomerHistory(
+    customer: Customer,
+    orders: Vec<Order>,
+    // Newest first.
+    points: Vec<PointsEntry>,
+    broken: Vec<String>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let unbalanced = points.iter().any(|e| broken.contains(&e.id));
+
+    rsx! {
+        div {
+            class: "stock-adjust-panel",
+            div {
+                class: "manager-header",
+                h3 { "Purchases by {customer.name}" }
+                button {
+                    class: "btn-secondary",
+                    onclick: move |_| on_close.call(()),
+                    "Close"
+                }
+            }
+            if !customer.notes.is_empty() {
+                p { class: "hint", "{customer.notes}" }
+            }
+            if orders.is_empty() {
+                p { class: "hint", "No purchases yet" }
+            } else {
+                table {
+                    class: "ledger-table",
+                    thead {
+                        tr {
+                            th { "When" }
+                            th { "Order" }
+                            th { "Items" }
+                            th { "Total" }
+                            th { "Status" }
+                        }
+                    }
+                    tbody {
+                        for order in orders.iter() {
+                            tr {
+                                key: "{order.id}",
+                                td { {order.paid_at().unwrap_or(order.created_at).format("%b %d, %Y %I:%M %p").to_string()} }
+                                td { "#{&order.id[..8]}" }
+                                td { {order.items.iter().map(|i| format!("{} × {}", i.quantity, i.label())).collect::<Vec<_>>().join(", ")} }
+                                td { "{order.total}" }
+                                td { "{order.status.as_str()}" }
+                            }
+                        }
+                    }
+                }
+            }
+
+            h3 { "Points" }
+            if unbalanced {
+                p { class: "form-error", "Entries marked ⚠ don't follow from the balance before them; the ledger may have been edited" }
+            }
+            if points.is_empty() {
+                p { class: "hint", "No points yet" }
+            } else {
+                table {
+                    class: "ledger-table",
+                    thead {
+                        tr {
+                            th { "When" }
+                            th { "Entry" }
+                            th { "Order" }
+                            th { "Points" }
+                            th { "Balance" }
+                            th { "Note" }
+                        }
+                    }
+                    tbody {
+                        for entry in points.iter() {
+                            tr {
+                                key: "{entry.id}",
+                                td { {entry.created_at.format("%b %d, %Y %I:%M %p").to_string()} }
+                                td {
+                                    "{entry.kind.as_str()}"
+                                    if broken.contains(&entry.id) {
+                                        " ⚠"
+                                    }
+                                }
+                                td {
+                                    {entry.order_id.as_deref().map_or_else(|| "—".to_string(), |id| format!("#{}", &id[..8.min(id.len())]))}
+                                }
+                                td { {format!("{:+}", entry.points)} }
+                                td { "{entry.balance}" }
+                                td { {entry.note.as_deref().unwrap_or("")} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Text fields of the add/edit form.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct CustomerForm {
+    // The customer being edited.
+    original: Option<Customer>,
+    name: String,
+    phone: String,
+    email: String,
+    notes: String,
+    tax_exempt: bool,
+    certificate: String,
+}
+
+impl CustomerForm {
+    fn editing(customer: &Customer) -> Self {
+        Self {
+            original: Some(customer.clone()),
+            name: customer.name.clone(),
+            phone: customer.phone.clone().unwrap_or_default(),
+            email: customer.email.clone().unwrap_or_default(),
+            notes: customer.notes.clone(),
+            tax_exempt: customer.tax_exempt,
+            certificate: customer.exemption_certificate.clone().unwrap_or_default(),
+        }
+    }
+
+    fn build(&self) -> Customer {
+        let text = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        let mut customer = self.original.clone().unwrap_or_else(|| Customer::new(String::new()));
+        customer.name = self.name.trim().to_string();
+        customer.phone = text(&self.phone);
+        customer.email = text(&self.email);
+        customer.notes = self.notes.trim().to_string();
+        customer.tax_exempt = self.tax_exempt;
+        customer.exemption_certificate = text(&self.certificate).filter(|_| self.tax_exempt);
+        customer
+    }
+}
@@ -0,0 +1,310 @@
+use dioxus::prelude::*;
+use crate::models::{
+    counted_total, tips_by_employee, Currency, DenominationCount, DrawerEntryKind, DrawerSession, Employee, Money,
+    Order, TipTotal,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawerEntryRequest {
+    pub kind: DrawerEntryKind,
+    pub amount: Money,
+    pub note: Option<String>,
+}
+
+#[component]
+pub fn DrawerManager(
+    sessions: Signal<Vec<DrawerSession>>,
+    orders: Signal<Vec<Order>>,
+    employees: Signal<Vec<Employee>>,
+    denominations: Vec<Money>,
+    status: Option<String>,
+    on_open: EventHandler<Money>,
+    on_entry: EventHandler<DrawerEntryRequest>,
+    on_close: EventHandler<Vec<DenominationCount>>,
+) -> Element {
+    let mut float = use_signal(String::new);
+    let mut entry_kind = use_signal(|| DrawerEntryKind::Drop);
+    let mut entry_amount = use_signal(String::new);
+    let mut entry_note = use_signal(String::new);
+    // Count per denomination, as typed.
+    let blank = move |denominations: &[Money]| -> Vec<(Money, String)> {
+        denominations.iter().map(|d| (*d, String::new())).collect()
+    };
+    let mut counts = use_signal(|| blank(&denominations));
+
+    let all_sessions = sessions.read();
+    let open = all_sessions.iter().find(|s| s.is_open()).cloned();
+    let last_closed = all_sessions.iter().rev().find(|s| !s.is_open()).cloned();
+    // Tips on the orders rung up while `session` was open.
+    let shift_tips = move |session: &DrawerSession| -> Vec<TipTotal> {
+        let all_orders = orders.read();
+        let session_orders = all_orders.iter().filter(|o| o.session_id.as_deref() == Some(session.id.as_str()));
+        tips_by_employee(session_orders, &employees.read())
+    };
+    let last_tips = last_closed.as_ref().map(shift_tips).unwrap_or_default();
+
+    let count_list = move || -> Vec<DenominationCount> {
+        counts
+            .read()
+            .iter()
+            .map(|(value, text)| DenominationCount {
+                value: *value,
+                count: text.trim().parse().unwrap_or(0),
+            })
+            .collect()
+    };
+    let counted = counted_total(&count_list(), Currency::default());
+
+    rsx! {
+        div {
+            class: "order-history-container drawer-container",
+
+            div {
+                class: "history-header",
+                h2 { "Cash Drawer" }
+                if let Some(session) = &open {
+                    div {
+                        class: "history-stats",
+                        div {
+                            class: "stat",
+                            span { class: "stat-label", "Opened:" }
+                            span { class: "stat-value", {session.opened_at.format("%b %d, %I:%M %p").to_string()} }
+                        }
+                        div {
+                            class: "stat",
+                            span { class: "stat-label", "Opening Float:" }
+                            span { class: "stat-value", "{session.opening_float}" }
+                        }
+                    }
+                }
+            }
+
+            if let Some(message) = status {
+                p { class: "hint", "{message}" }
+            }
+
+            match open {
+                None => rsx! {
+                    div {
+                        class: "drawer-panel",
+                        h3 { "Open Drawer" }
+                        div {
+                            class: "form-group",
+                            label { "Opening float" }
+                            input {
+                                r#type: "number",
+                                class: "form-input",
+                                value: "{float}",
+                                oninput: move |e| float.set(e.value()),
+                                placeholder: "0.00",
+                                step: "0.01",
+                            }
+                        }
+                        button {
+                            class: "btn-primary",
+                            onclick: move |_| {
+                                let amount = if float().trim().is_empty() {
+                                    Some(Money::zero(Currency::default()))
+                                } else {
+                                    Money::parse(&float(), Currency::default())
+                                };
+                                if let Some(amount) = amount.filter(|a| a.minor() >= 0) {
+                                    on_open.call(amount);
+                                    float.set(String::new());
+                                }
+                            },
+                            "Open Drawer"
+                        }
+                    }
+                },
+                Some(session) => rsx! {
+                    ShiftTips { title: "Tips This Shift", tips: shift_tips(&session) }
+
+                    div {
+                        class: "drawer-panel",
+                        h3 { "Paid In / Paid Out / Drop" }
+                        div {
+                            class: "drawer-entry-form",
+                            select {
+                                class: "form-select",
+                                onchange: move |e| {
+                                    if let Some(kind) = DrawerEntryKind::from_label(&e.value()) {
+                                        entry_kind.set(kind);
+                                    }
+                                },
+                                for kind in DrawerEntryKind::ALL {
+                                    option {
+                                        value: "{kind.as_str()}",
+                                        selected: kind == entry_kind(),
+                                        "{kind.as_str()}"
+                                    }
+                                }
+                            }
+                            input {
+                                r#type: "number",
+                                class: "form-input",
+                                value: "{entry_amount}",
+                                oninput: move |e| entry_amount.set(e.value()),
+                                placeholder: "0.00",
+                                step: "0.01",
+                            }
+                            input {
+                                r#type: "text",
+                                class: "form-input",
+                                value: "{entry_note}",
+                                oninput: move |e| entry_note.set(e.value()),
+                                placeholder: "Note (optional)",
+                            }
+                            button {
+                                class: "btn-secondary",
+                                onclick: move |_| {
+                                    if let Some(amount) = Money::parse(&entry_amount(), Currency::default()) {
+                                        let note = entry_note().trim().to_string();
+                                        on_entry.call(DrawerEntryRequest {
+                                            kind: entry_kind(),
+                                            amount,
+                                            note: (!note.is_empty()).then_some(note),
+                                        });
+                                        entry_amount.set(String::new());
+                                        entry_note.set(String::new());
+                                    }
+                                },
+                                "Record"
+                            }
+                        }
+                        if !session.entries.is_empty() {
+                            div {
+                                class: "drawer-entries",
+                                for entry in session.entries.iter().rev() {
+                                    div {
+                                        key: "{entry.id}",
+                                        class: "drawer-entry",
+                                        span { {entry.created_at.format("%I:%M %p").to_string()} }
+                                        span { "{entry.kind.as_str()}" }
+                                        span { class: "drawer-entry-note", {entry.note.clone().unwrap_or_default()} }
+                                        span { class: "drawer-entry-amount", "{entry.amount}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "drawer-panel",
+                        h3 { "Count & Close" }
+                        p { class: "hint", "Count the drawer. The expected amount is shown after closing." }
+                        div {
+                            class: "denomination-grid",
+                            for (i, (value, text)) in counts.read().iter().cloned().enumerate() {
+                                div {
+                                    key: "{i}",
+                                    class: "denomination-row",
+                                    label { "{value}" }
+                                    input {
+                                        r#type: "number",
+                                        class: "form-input",
+                                        min: "0",
+                                        value: "{text}",
+                                        oninput: move |e| {
+                                            if let Some(entry) = counts.write().get_mut(i) {
+                                                entry.1 = e.value();
+                                            }
+                                        },
+                                        placeholder: "0",
+                                    }
+                                }
+                            }
+                        }
+                        div {
+                            class: "summary-row total",
+                            span { "Counted:" }
+                            span { "{counted}" }
+                        }
+                        button {
+                            class: "btn-primary",
+                            onclick: move |_| {
+                                on_close.call(count_list());
+                                counts.set(blank(&denominations));
+                            },
+                            "Close Drawer"
+                        }
+                    }
+                },
+            }
+
+            if let Some(session) = last_closed {
+                if let Some(close) = session.close {
+                    div {
+                        class: "drawer-panel",
+                        h3 { {format!("Last Close — {}", close.closed_at.format("%b %d, %I:%M %p"))} }
+                        div { class: "summary-row", span { "Opening float:" } span { "{close.summary.opening_float}" } }
+                        div { class: "summary-row", span { "Cash sales:" } span { "{close.summary.cash_sales}" } }
+                        if close.summary.cash_tips.minor() != 0 {
+                            div { class: "summary-row", span { "Cash tips:" } span { "{close.summary.cash_tips}" } }
+                        }
+                        div { class: "summary-row", span { "Cash refunds:" } span { "{-close.summary.cash_refunds}" } }
+                        div { class: "summary-row", span { "Paid in:" } span { "{close.summary.paid_in}" } }
+                        div { class: "summary-row", span { "Paid out:" } span { "{-close.summary.paid_out}" } }
+                        div { class: "summary-row", span { "Drops:" } span { "{-close.summary.drops}" } }
+                        div { class: "summary-row total", span { "Expected:" } span { "{close.summary.expected}" } }
+                        div { class: "summary-row total", span { "Counted:" } span { "{close.counted}" } }
+                        div {
+                            class: if close.variance.minor() == 0 { "summary-row variance" } else { "summary-row variance off" },
+                            span {
+                                match close.variance.minor() {
+                                    0 => "Balanced",
+                                    v if v > 0 => "Over:",
+                                    _ => "Short:",
+                                }
+                            }
+                            span { "{close.variance}" }
+                        }
+                    }
+                    if !last_tips.is_empty() {
+                        ShiftTips { title: "Tips Last Shift", tips: last_tips }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Tips per employee for one drawer session. Card tips are owed to the
+// employee; cash tips are already in the drawer.
+#[component]
+fn ShiftTips(title: String, tips: Vec<TipTotal>) -> Element {
+    rsx! {
+        div {
+            class: "drawer-panel shift-tips",
+            h3 { "{title}" }
+            if tips.is_empty() {
+                p { class: "hint", "No tips yet" }
+            } else {
+                table {
+                    class: "ledger-table",
+                    thead {
+                        tr {
+                            th { "Employee" }
+                            th { "Tips" }
+                            th { "Cash" }
+                            th { "Card" }
+                            th { "Total" }
+                        }
+                    }
+                    tbody {
+                        for tip in tips.iter() {
+                            tr {
+                                key: "{tip.employee_id.clone().unwrap_or_default()}",
+                                td { "{tip.employee}" }
+                                td { "{tip.count}" }
+                                td { "{tip.cash}" }
+                                td { "{tip.card}" }
+                                td { {(tip.cash + tip.card).to_string()} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
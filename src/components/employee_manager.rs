@@ -0,0 +1,192 @@
+use dioxus::prelude::*;
+use crate::models::{Employee, Role};
+
+#[component]
+pub fn EmployeeManager(
+    employees: Signal<Vec<Employee>>,
+    // The signed-in admin, who can't demote or deactivate themselves.
+    operator_id: String,
+    on_save: EventHandler<Employee>,
+) -> Element {
+    let mut show_add_form = use_signal(|| false);
+    let mut new_name = use_signal(String::new);
+    let mut new_role = use_signal(|| Role::Cashier);
+    let mut new_pin = use_signal(String::new);
+    let mut resetting = use_signal(|| None::<String>);
+    let mut reset_pin = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let staff = employees.read().clone();
+
+    rsx! {
+        div {
+            class: "product-manager-container",
+
+            div {
+                class: "manager-header",
+                h2 { "Employees" }
+                button {
+                    class: "btn-primary",
+                    onclick: move |_| show_add_form.set(!show_add_form()),
+                    if show_add_form() { "Cancel" } else { "+ Add Employee" }
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "lock-error", "{message}" }
+            }
+
+            if show_add_form() {
+                div {
+                    class: "add-product-form",
+                    div {
+                        class: "form-group",
+                        label { "Name" }
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            value: "{new_name}",
+                            oninput: move |e| new_name.set(e.value()),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Role" }
+                        select {
+                            class: "form-select",
+                            onchange: move |e| {
+                                if let Some(role) = Role::from_label(&e.value()) {
+                                    new_role.set(role);
+                                }
+                            },
+                            for role in Role::ALL {
+                                option { value: "{role.as_str()}", selected: role == new_role(), "{role.as_str()}" }
+                            }
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "PIN" }
+                        input {
+                            r#type: "password",
+                            inputmode: "numeric",
+                            class: "form-input",
+                            value: "{new_pin}",
+                            oninput: move |e| new_pin.set(e.value()),
+                            placeholder: "4-8 digits",
+                        }
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| {
+                            match Employee::new(new_name(), new_role(), &new_pin()) {
+                                Ok(employee) => {
+                                    on_save.call(employee);
+                                    new_name.set(String::new());
+                                    new_pin.set(String::new());
+                                    error.set(None);
+                                    show_add_form.set(false);
+                                }
+                                Err(e) => error.set(Some(e.to_string())),
+                            }
+                        },
+                        "Add Employee"
+                    }
+                }
+            }
+
+            div {
+                class: "products-table",
+                table {
+                    thead {
+                        tr {
+                            th { "Name" }
+                            th { "Role" }
+                            th { "Status" }
+                            th { "Actions" }
+                        }
+                    }
+                    tbody {
+                        for employee in staff.iter() {
+                            tr {
+                                key: "{employee.id}",
+                                td { "{employee.name}" }
+                                td {
+                                    select {
+                                        class: "form-select",
+                                        disabled: employee.id == operator_id,
+                                        onchange: {
+                                            let employee = employee.clone();
+                                            move |e: FormEvent| {
+                                                if let Some(role) = Role::from_label(&e.value()) {
+                                                    on_save.call(Employee { role, ..employee.clone() });
+                                                }
+                                            }
+                                        },
+                                        for role in Role::ALL {
+                                            option { value: "{role.as_str()}", selected: role == employee.role, "{role.as_str()}" }
+                                        }
+                                    }
+                                }
+                                td { if employee.active { "Active" } else { "Inactive" } }
+                                td {
+                                    if resetting() == Some(employee.id.clone()) {
+                                        input {
+                                            r#type: "password",
+                                            inputmode: "numeric",
+                                            class: "form-input pin-reset-input",
+                                            value: "{reset_pin}",
+                                            oninput: move |e| reset_pin.set(e.value()),
+                                            placeholder: "New PIN",
+                                        }
+                                        button {
+                                            class: "btn-secondary-small",
+                                            onclick: {
+                                                let employee = employee.clone();
+                                                move |_| {
+                                                    let mut updated = employee.clone();
+                                                    match updated.set_pin(&reset_pin()) {
+                                                        Ok(()) => {
+                                                            on_save.call(updated);
+                                                            resetting.set(None);
+                                                            error.set(None);
+                                                        }
+                                                        Err(e) => error.set(Some(e.to_string())),
+                                                    }
+                                                    reset_pin.set(String::new());
+                                                }
+                                            },
+                                            "Save PIN"
+                                        }
+                                    } else {
+                                        button {
+                                            class: "btn-secondary-small",
+                                            onclick: {
+                                                let id = employee.id.clone();
+                                                move |_| {
+                                                    reset_pin.set(String::new());
+                                                    resetting.set(Some(id.clone()));
+                                                }
+                                            },
+                                            "Reset PIN"
+                                        }
+                                    }
+                                    if employee.id != operator_id {
+                                        button {
+                                            class: "btn-danger-small",
+                                            onclick: {
+                                                let employee = employee.clone();
+                                                move |_| on_save.call(Employee { active: !employee.active, ..employee.clone() })
+                                            },
+                                            if employee.active { "Deactivate" } else { "Reactivate" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
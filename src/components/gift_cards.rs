@@ -0,0 +1,236 @@
+use chrono::Utc;
+use dioxus::prelude::*;
+use crate::models::{
+    card_balance, card_history, check_load, find_card, new_card_number, CardEntry, Currency, Customer, GiftCard,
+    GiftCardConfig, Money,
+};
+
+// A gift card to sell or reload, added to the cart as a line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GiftCardLoad {
+    pub number: String,
+    pub amount: Money,
+}
+
+// Selling and reloading gift cards, balance checks and each card's ledger.
+#[component]
+pub fn GiftCards(
+    cards: Signal<Vec<GiftCard>>,
+    ledger: Signal<Vec<CardEntry>>,
+    customers: Signal<Vec<Customer>>,
+    config: GiftCardConfig,
+    // Checked against the card's limit; the parent adds it to the cart.
+    on_load: EventHandler<GiftCardLoad>,
+) -> Element {
+    let mut number = use_signal(String::new);
+    let mut amount = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    let mut lookup = use_signal(String::new);
+    // Card whose ledger is open.
+    let mut viewing = use_signal(|| None::<String>);
+
+    let list = cards.read().clone();
+    let entries = ledger.read();
+    let people = customers.read();
+    let now = Utc::now();
+    let found = find_card(&list, &lookup()).cloned();
+    let viewed = viewing().and_then(|id| list.iter().find(|c| c.id == id).cloned());
+    let owner = |card: &GiftCard| {
+        card.customer_id
+            .as_ref()
+            .and_then(|id| people.iter().find(|c| &c.id == id))
+            .map_or_else(|| "—".to_string(), |c| c.name.clone())
+    };
+    let expiry = |card: &GiftCard| match card.expires_at {
+        Some(at) if card.is_expired(now) => format!("Expired {}", at.format("%b %d, %Y")),
+        Some(at) => at.format("%b %d, %Y").to_string(),
+        None => "Never".to_string(),
+    };
+    let load_cards = list.clone();
+    let load_ledger = entries.clone();
+    let new_cards = list.clone();
+
+    rsx! {
+        div {
+            class: "product-manager-container",
+
+            div {
+                class: "manager-header",
+                h2 { "Gift Cards" }
+            }
+
+            div {
+                class: "add-product-form gift-card-load",
+                h3 { "Sell or Reload" }
+                div {
+                    class: "form-group",
+                    label { "Card number" }
+                    div {
+                        class: "gift-card-number-row",
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            inputmode: "numeric",
+                            value: "{number}",
+                            placeholder: "Scan the card, or pick a new number",
+                            oninput: move |e| number.set(e.value()),
+                        }
+                        button {
+                            class: "btn-secondary-small",
+                            onclick: move |_| number.set(new_card_number(&new_cards)),
+                            "New number"
+                        }
+                    }
+                }
+                div {
+                    class: "form-group",
+                    label { "Amount" }
+                    input {
+                        r#type: "text",
+                        class: "form-input",
+                        inputmode: "decimal",
+                        value: "{amount}",
+                        oninput: move |e| amount.set(e.value()),
+                    }
+                }
+                if let Some(message) = error() {
+                    p { class: "form-error", "{message}" }
+                }
+                button {
+                    class: "btn-primary",
+                    onclick: move |_| {
+                        let Some(value) = Money::parse(&amount(), Currency::default()) else {
+                            error.set(Some("Enter an amount to load".to_string()));
+                            return;
+                        };
+                        match check_load(&load_cards, &load_ledger, &config, &number(), value) {
+                            Ok(checked) => {
+                                on_load.call(GiftCardLoad { number: checked, amount: value });
+                                number.set(String::new());
+                                amount.set(String::new());
+                                error.set(None);
+                            }
+                            Err(e) => error.set(Some(format!("Can't load this card: {e}"))),
+                        }
+                    },
+                    "Add to Cart"
+                }
+            }
+
+            div {
+                class: "gift-card-lookup",
+                h3 { "Balance" }
+                input {
+                    r#type: "search",
+                    class: "form-input",
+                    inputmode: "numeric",
+                    value: "{lookup}",
+                    placeholder: "Card number",
+                    oninput: move |e| lookup.set(e.value()),
+                }
+                match (&found, lookup().trim().is_empty()) {
+                    (_, true) => rsx! {},
+                    (Some(card), _) => rsx! {
+                        div {
+                            class: "gift-card-balance",
+                            span { "{card.kind.as_str()} {card.masked()}" }
+                            span { class: "gift-card-amount", {card_balance(&entries, &card.id).to_string()} }
+                            span { class: "hint", "Expires: {expiry(card)}" }
+                        }
+                    },
+                    (None, _) => rsx! {
+                        p { class: "hint", "No card with that number" }
+                    },
+                }
+            }
+
+            div {
+                class: "products-table",
+                table {
+                    thead {
+                        tr {
+                            th { "Card" }
+                            th { "Kind" }
+                            th { "Customer" }
+                            th { "Balance" }
+                            th { "Expires" }
+                            th { "Actions" }
+                        }
+                    }
+                    tbody {
+                        if list.is_empty() {
+                            tr {
+                                td { colspan: "6", class: "empty-row", "No gift cards or store credit yet" }
+                            }
+                        }
+                        for card in list.iter().rev() {
+                            tr {
+                                key: "{card.id}",
+                                class: if card.is_expired(now) { "expired-row" } else { "" },
+                                td { "{card.masked()}" }
+                                td { "{card.kind.as_str()}" }
+                                td { {owner(card)} }
+                                td { {card_balance(&entries, &card.id).to_string()} }
+                                td { {expiry(card)} }
+                                td {
+                                    button {
+                                        class: "btn-secondary-small",
+                                        onclick: {
+                                            let id = card.id.clone();
+                                            move |_| {
+                                                let open = viewing().as_deref() == Some(id.as_str());
+                                                viewing.set(if open { None } else { Some(id.clone()) });
+                                            }
+                                        },
+                                        "Ledger"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(card) = viewed {
+                div {
+                    class: "stock-adjust-panel",
+                    div {
+                        class: "manager-header",
+                        h3 { "Ledger for {card.kind.as_str()} {card.masked()}" }
+                        button {
+                            class: "btn-secondary",
+                            onclick: move |_| viewing.set(None),
+                            "Close"
+                        }
+                    }
+                    table {
+                        class: "ledger-table",
+                        thead {
+                            tr {
+                                th { "When" }
+                                th { "Entry" }
+                                th { "Order" }
+                                th { "Amount" }
+                                th { "Balance" }
+                            }
+                        }
+                        tbody {
+                            for entry in card_history(&entries, &card.id) {
+                                tr {
+                                    key: "{entry.id}",
+                                    td { {entry.created_at.format("%b %d, %Y %I:%M %p").to_string()} }
+                                    td { "{entry.kind.as_str()}" }
+                                    td {
+                                        {entry.order_id.as_deref().map_or_else(|| "—".to_string(), |id| format!("#{}", &id[..8.min(id.len())]))}
+                                    }
+                                    td { "{entry.amount}" }
+                                    td { "{entry.balance}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::Utc;
+use dioxus::prelude::*;
+use crate::models::HeldOrder;
+
+// Orders parked at the till, above the cart. Renders nothing while none are.
+#[component]
+pub fn HeldOrders(
+    held: Signal<Vec<HeldOrder>>,
+    // Id of the held order to bring back into the cart.
+    on_recall: EventHandler<String>,
+) -> Element {
+    let mut expanded = use_signal(|| true);
+    let list = held.read();
+    if list.is_empty() {
+        return rsx! {};
+    }
+    let now = Utc::now();
+
+    rsx! {
+        div {
+            class: "held-orders",
+            button {
+                class: "held-orders-toggle",
+                onclick: move |_| expanded.set(!expanded()),
+                span { "Held orders ({list.len()})" }
+                span { if expanded() { "▾" } else { "▸" } }
+            }
+            if expanded() {
+                for hold in list.iter() {
+                    {
+                        let order_id = hold.order.id.clone();
+                        let units = hold.order.items.iter().map(|i| i.quantity).sum::<u32>();
+                        let by = hold.held_by.as_deref().map(|name| format!(" · {name}")).unwrap_or_default();
+                        rsx! {
+                            div {
+                                key: "{hold.order.id}",
+                                class: "held-order",
+                                div {
+                                    class: "held-order-info",
+                                    span { class: "held-order-label", "{hold.label}" }
+                                    span { class: "hint", "{units} items · {hold.order.total} · {hold.age(now)}{by}" }
+                                }
+                                button {
+                                    class: "btn-secondary-small",
+                                    onclick: move |_| on_recall.call(order_id.clone()),
+                                    "Recall"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use dioxus::prelude::*;
+use crate::models::{Employee, Role};
+
+// Shown whenever nobody is signed in. With no employees on file yet it
+// asks for the first administrator instead.
+#[component]
+pub fn LockScreen(
+    employees: Signal<Vec<Employee>>,
+    on_unlock: EventHandler<Employee>,
+    on_failed: EventHandler<Employee>,
+    on_setup: EventHandler<Employee>,
+) -> Element {
+    let mut selected = use_signal(|| None::<String>);
+    let mut pin = use_signal(String::new);
+    let mut name = use_signal(String::new);
+    let mut confirm = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let staff: Vec<Employee> = employees.read().iter().filter(|e| e.active).cloned().collect();
+    let first_run = employees.read().is_empty();
+
+    let mut unlock = move || {
+        let Some(id) = selected() else {
+            error.set(Some("Choose your name".to_string()));
+            return;
+        };
+        let employee = employees.read().iter().find(|e| e.id == id).cloned();
+        match employee {
+            Some(employee) if employee.verify_pin(&pin()) => {
+                pin.set(String::new());
+                error.set(None);
+                on_unlock.call(employee);
+            }
+            employee => {
+                pin.set(String::new());
+                error.set(Some("Wrong PIN".to_string()));
+                if let Some(employee) = employee {
+                    on_failed.call(employee);
+                }
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            class: "lock-screen",
+            div {
+                class: "lock-card",
+                h1 { "TREZZA TERMINAL" }
+
+                if first_run {
+                    p { class: "hint", "Create the administrator account to get started." }
+                    div {
+                        class: "form-group",
+                        label { "Name" }
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            value: "{name}",
+                            oninput: move |e| name.set(e.value()),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "PIN (4-8 digits)" }
+                        input {
+                            r#type: "password",
+                            inputmode: "numeric",
+                            class: "form-input",
+                            value: "{pin}",
+                            oninput: move |e| pin.set(e.value()),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Confirm PIN" }
+                        input {
+                            r#type: "password",
+                            inputmode: "numeric",
+                            class: "form-input",
+                            value: "{confirm}",
+                            oninput: move |e| confirm.set(e.value()),
+                        }
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| {
+                            if pin() != confirm() {
+                                error.set(Some("PINs don't match".to_string()));
+                                return;
+                            }
+                            match Employee::new(name(), Role::Admin, &pin()) {
+                                Ok(admin) => {
+                                    pin.set(String::new());
+                                    confirm.set(String::new());
+                                    error.set(None);
+                                    on_setup.call(admin);
+                                }
+                                Err(e) => error.set(Some(e.to_string())),
+                            }
+                        },
+                        "Create Administrator"
+                    }
+                } else {
+                    div {
+                        class: "lock-staff",
+                        for employee in staff {
+                            button {
+                                key: "{employee.id}",
+                                class: if selected() == Some(employee.id.clone()) { "lock-staff-btn active" } else { "lock-staff-btn" },
+                                onclick: move |_| {
+                                    selected.set(Some(employee.id.clone()));
+                                    error.set(None);
+                                },
+                                span { "{employee.name}" }
+                                span { class: "lock-role", "{employee.role.as_str()}" }
+                            }
+                        }
+                    }
+                    PinPad { pin, on_enter: move |_| unlock() }
+                }
+
+                if let Some(message) = error() {
+                    p { class: "lock-error", "{message}" }
+                }
+            }
+        }
+    }
+}
+
+// Masked PIN display with a numeric keypad; Enter submits.
+#[component]
+pub fn PinPad(pin: Signal<String>, on_enter: EventHandler<()>) -> Element {
+    let masked = "•".repeat(pin.read().len());
+
+    rsx! {
+        div {
+            class: "pin-pad",
+            div { class: "pin-display", "{masked}" }
+            div {
+                class: "pin-keys",
+                for key in ["1", "2", "3", "4", "5", "6", "7", "8", "9", "⌫", "0", "Enter"] {
+                    button {
+                        key: "{key}",
+                        class: if key == "Enter" { "pin-key enter" } else { "pin-key" },
+                        onclick: move |_| match key {
+                            "⌫" => {
+                                pin.write().pop();
+                            }
+                            "Enter" => on_enter.call(()),
+                            digit => {
+                                if pin.read().len() < 8 {
+                                    pin.write().push_str(digit);
+                                }
+                            }
+                        },
+                        "{key}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -3,9 +3,42 @@ pub mod cart;
 pub mod payment_modal;
 pub mod order_history;
 pub mod product_manager;
+pub mod receipt_modal;
+pub mod sales_reports;
+pub mod drawer_manager;
+pub mod lock_screen;
+pub mod override_modal;
+pub mod employee_manager;
+pub mod audit_log;
+pub mod promotion_manager;
+pub mod option_picker;
+pub mod category_manager;
+pub mod scan_listener;
+pub mod unknown_code;
+pub mod catalog_import;
+pub mod held_orders;
+pub mod customer_manager;
+pub mod customer_lookup;
+pub mod gift_cards;
 
 pub use product_grid::*;
 pub use cart::*;
 pub use payment_modal::*;
 pub use order_history::*;
 pub use product_manager::*;
+pub use receipt_modal::*;
+pub use sales_reports::*;
+pub use drawer_manager::*;
+pub use lock_screen::*;
+pub use override_modal::*;
+pub use employee_manager::*;
+pub use audit_log::*;
+pub use promotion_manager::*;
+pub use option_picker::*;
+pub use category_manager::*;
+pub use scan_listener::*;
+pub use unknown_code::*;
+pub use catalog_import::*;
+pub use held_orders::*;
+pub use customer_manager::*;
+pub use gift_cards::*;
@@ -0,0 +1,131 @@
+use dioxus::prelude::*;
+use crate::models::{ChosenOption, GroupKind, Product};
+
+// Asks for a product's variants and modifiers before it goes in the cart.
+#[component]
+pub fn OptionPicker(
+    product: Product,
+    on_confirm: EventHandler<Vec<ChosenOption>>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    // Variants start on their first option.
+    let defaults: Vec<String> = product
+        .options
+        .iter()
+        .filter(|g| g.kind == GroupKind::Variant)
+        .filter_map(|g| g.options.first().map(|o| o.id.clone()))
+        .collect();
+    let mut selected = use_signal(move || defaults);
+    let mut error = use_signal(|| None::<String>);
+
+    let picked = selected();
+    let price = product
+        .options
+        .iter()
+        .flat_map(|g| &g.options)
+        .filter(|o| picked.contains(&o.id))
+        .fold(product.price, |sum, o| sum + o.price_delta);
+    let confirm_product = product.clone();
+
+    rsx! {
+        div {
+            class: "modal-overlay",
+            onclick: move |_| on_cancel.call(()),
+
+            div {
+                class: "modal-content option-picker",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "{product.name}" }
+                    button {
+                        class: "close-btn",
+                        onclick: move |_| on_cancel.call(()),
+                        "✕"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+                    for group in product.options.iter().filter(|g| !g.options.is_empty()) {
+                        div {
+                            key: "{group.id}",
+                            class: "option-group",
+                            div {
+                                class: "option-group-header",
+                                h3 { "{group.name}" }
+                                span {
+                                    class: "hint",
+                                    if group.is_required() { "Required · " }
+                                    "{group.rule_label()}"
+                                }
+                            }
+                            div {
+                                class: "option-choices",
+                                for option in group.options.iter() {
+                                    {
+                                        let id = option.id.clone();
+                                        let group_ids: Vec<String> = group.options.iter().map(|o| o.id.clone()).collect();
+                                        let max = group.max as usize;
+                                        let active = picked.contains(&id);
+                                        rsx! {
+                                            button {
+                                                key: "{option.id}",
+                                                class: if active { "option-btn active" } else { "option-btn" },
+                                                onclick: move |_| {
+                                                    let mut ids = selected.write();
+                                                    if ids.contains(&id) {
+                                                        ids.retain(|i| i != &id);
+                                                    } else {
+                                                        // A pick-one group swaps its choice; others
+                                                        // stop at their maximum.
+                                                        let in_group = ids.iter().filter(|i| group_ids.contains(i)).count();
+                                                        if max == 1 {
+                                                            ids.retain(|i| !group_ids.contains(i));
+                                                        } else if in_group >= max {
+                                                            return;
+                                                        }
+                                                        ids.push(id.clone());
+                                                    }
+                                                    error.set(None);
+                                                },
+                                                span { "{option.name}" }
+                                                if option.price_delta.minor() > 0 {
+                                                    span { class: "option-delta", "+{option.price_delta}" }
+                                                } else if option.price_delta.minor() < 0 {
+                                                    span { class: "option-delta", "{option.price_delta}" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if let Some(message) = error() {
+                        p { class: "form-error", "{message}" }
+                    }
+                }
+
+                div {
+                    class: "modal-footer",
+                    button {
+                        class: "btn-secondary",
+                        onclick: move |_| on_cancel.call(()),
+                        "Cancel"
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| match confirm_product.choose(&selected()) {
+                            Ok(options) => on_confirm.call(options),
+                            Err(e) => error.set(Some(e.to_string())),
+                        },
+                        "Add {price}"
+                    }
+                }
+            }
+        }
+    }
+}
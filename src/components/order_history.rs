@@ -1,9 +1,52 @@
 use dioxus::prelude::*;
-use crate::models::Order;
+use crate::models::{group_number, mask_number, Money, Order, OrderStatus, ReasonCode};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefundRequest {
+    pub order_id: String,
+    pub quantities: Vec<(String, u32)>,
+    pub reason: ReasonCode,
+    pub note: Option<String>,
+    pub restock: bool,
+    // Pay the refund out as store credit instead of to the original tender.
+    pub store_credit: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoidRequest {
+    pub order_id: String,
+    pub reason: ReasonCode,
+    pub note: Option<String>,
+}
+
+// A new tip for a card payment, e.g. from the signed slip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TipAdjustment {
+    pub order_id: String,
+    pub payment_id: String,
+    pub tip: Money,
+}
 
 #[component]
-pub fn OrderHistory(orders: Signal<Vec<Order>>) -> Element {
+pub fn OrderHistory(
+    orders: Signal<Vec<Order>>,
+    // Open drawer session; card tips on its orders can still be adjusted.
+    open_session: Option<String>,
+    status: Option<String>,
+    on_refund: EventHandler<RefundRequest>,
+    on_void: EventHandler<VoidRequest>,
+    on_reprint: EventHandler<String>,
+    on_adjust_tip: EventHandler<TipAdjustment>,
+) -> Element {
     let all_orders = orders.read();
+    let sales: Vec<&Order> = all_orders
+        .iter()
+        .filter(|o| o.status != OrderStatus::Cancelled)
+        .collect();
+    let gross = sales.iter().map(|o| o.total).sum::<Money>();
+    let refunds = sales.iter().map(|o| o.refunded_total()).sum::<Money>();
+    let net = sales.iter().map(|o| o.net_total()).sum::<Money>();
+    let tips = sales.iter().map(|o| o.tip_total()).sum::<Money>();
 
     rsx! {
         div {
@@ -17,18 +60,35 @@ pub fn OrderHistory(orders: Signal<Vec<Order>>) -> Element {
                     div {
                         class: "stat",
                         span { class: "stat-label", "Total Orders:" }
-                        span { class: "stat-value", "{all_orders.len()}" }
+                        span { class: "stat-value", "{sales.len()}" }
                     }
                     div {
                         class: "stat",
-                        span { class: "stat-label", "Total Revenue:" }
-                        span { class: "stat-value",
-                            "${all_orders.iter().map(|o| o.total).sum::<f64>():.2}"
-                        }
+                        span { class: "stat-label", "Gross Sales:" }
+                        span { class: "stat-value", "{gross}" }
+                    }
+                    div {
+                        class: "stat",
+                        span { class: "stat-label", "Refunds:" }
+                        span { class: "stat-value", "{refunds}" }
+                    }
+                    div {
+                        class: "stat",
+                        span { class: "stat-label", "Net Revenue:" }
+                        span { class: "stat-value", "{net}" }
+                    }
+                    div {
+                        class: "stat",
+                        span { class: "stat-label", "Tips:" }
+                        span { class: "stat-value", "{tips}" }
                     }
                 }
             }
 
+            if let Some(message) = status {
+                p { class: "hint", "{message}" }
+            }
+
             div {
                 class: "history-list",
                 if all_orders.is_empty() {
@@ -37,22 +97,17 @@ pub fn OrderHistory(orders: Signal<Vec<Order>>) -> Element {
                         p { "📋 No orders yet" }
                     }
                 }
-                for (idx, order) in all_orders.iter().enumerate().rev() {
+                for order in all_orders.iter().rev() {
                     OrderHistoryItem {
-                        key: "{idx}",
-                        order_id: order.id.clone(),
-                        created_at: format!("{}", order.created_at.format("%b %d, %Y %I:%M %p")),
-                        items_count: order.items.len(),
-                        total: order.total,
-                        status: order.status.as_str().to_string(),
-                        items_display: order.items.iter().map(|item| {
-                            format!("{} × {}: ${:.2}", item.product_name, item.quantity, item.price * item.quantity as f64)
-                        }).collect::<Vec<_>>(),
-                        subtotal: order.subtotal,
-                        tax: order.tax,
-                        payment_info: order.payment.as_ref().map(|p| {
-                            format!("{} {} - Processed: {}", p.method.icon(), p.method.as_str(), p.processed_at.format("%I:%M %p"))
-                        }),
+                        key: "{order.id}",
+                        order: order.clone(),
+                        tips_adjustable: order.status != OrderStatus::Cancelled
+                            && open_session.is_some()
+                            && order.session_id == open_session,
+                        on_refund: move |request| on_refund.call(request),
+                        on_void: move |request| on_void.call(request),
+                        on_reprint: move |order_id| on_reprint.call(order_id),
+                        on_adjust_tip: move |request| on_adjust_tip.call(request),
                     }
                 }
             }
@@ -60,20 +115,31 @@ pub fn OrderHistory(orders: Signal<Vec<Order>>) -> Element {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum Action {
+    Refund,
+    Void,
+}
+
 #[component]
 fn OrderHistoryItem(
-    order_id: String,
-    created_at: String,
-    items_count: usize,
-    total: f64,
-    status: String,
-    items_display: Vec<String>,
-    subtotal: f64,
-    tax: f64,
-    payment_info: Option<String>,
+    order: Order,
+    tips_adjustable: bool,
+    on_refund: EventHandler<RefundRequest>,
+    on_void: EventHandler<VoidRequest>,
+    on_reprint: EventHandler<String>,
+    on_adjust_tip: EventHandler<TipAdjustment>,
 ) -> Element {
     let mut expanded = use_signal(|| false);
-    let status_class = format!("order-status status-{}", status.to_lowercase());
+    let mut action = use_signal(|| None::<Action>);
+    // Card payment whose tip is being edited, with the amount as typed.
+    let mut tip_edit = use_signal(|| None::<(String, String)>);
+    let tip_total = order.tip_total();
+    let status = order.status.as_str();
+    let status_class = format!("order-status status-{}", status.to_lowercase().replace(' ', "-"));
+    let created_at = order.created_at.format("%b %d, %Y %I:%M %p").to_string();
+    let can_refund = matches!(order.status, OrderStatus::Paid | OrderStatus::PartiallyRefunded);
+    let can_void = order.status == OrderStatus::Paid;
 
     rsx! {
         div {
@@ -85,14 +151,17 @@ fn OrderHistoryItem(
 
                 div {
                     class: "order-info",
-                    div { class: "order-id", "Order #{&order_id[..8]}" }
+                    div { class: "order-id", "Order #{&order.id[..8]}" }
                     div { class: "order-time", "{created_at}" }
+                    if let Some(customer) = &order.customer {
+                        div { class: "order-time", "{customer.name}" }
+                    }
                 }
 
                 div {
                     class: "order-summary",
-                    span { class: "order-items", "{items_count} items" }
-                    span { class: "order-total", "${total:.2}" }
+                    span { class: "order-items", "{order.items.len()} items" }
+                    span { class: "order-total", "{order.total}" }
                     span {
                         class: "{status_class}",
                         "{status}"
@@ -111,10 +180,16 @@ fn OrderHistoryItem(
 
                     div {
                         class: "order-items-list",
-                        for item_str in items_display.iter() {
+                        for item in order.items.iter() {
                             div {
                                 class: "detail-item",
-                                span { "{item_str}" }
+                                span { "{item.label()} × {item.quantity}: {item.line_total()}" }
+                            }
+                            for discount in item.discounts.iter() {
+                                div {
+                                    class: "detail-item discount-row",
+                                    span { "  {discount.name}: {-discount.amount}" }
+                                }
                             }
                         }
                     }
@@ -124,28 +199,432 @@ fn OrderHistoryItem(
                         div {
                             class: "total-row",
                             span { "Subtotal:" }
-                            span { "${subtotal:.2}" }
+                            span { "{order.subtotal}" }
                         }
-                        div {
-                            class: "total-row",
-                            span { "Tax:" }
-                            span { "${tax:.2}" }
+                        for discount in order.order_discounts.iter() {
+                            div {
+                                class: "total-row discount-row",
+                                span { "{discount.name}:" }
+                                span { "{-discount.amount}" }
+                            }
+                        }
+                        for line in order.tax_lines.iter() {
+                            div {
+                                class: "total-row",
+                                span { "{line.name} ({line.rate}):" }
+                                span { "{line.amount}" }
+                            }
                         }
                         div {
                             class: "total-row total",
                             span { "Total:" }
-                            span { "${total:.2}" }
+                            span { "{order.total}" }
+                        }
+                        for refund in order.refunds.iter() {
+                            div {
+                                class: "total-row refund-row",
+                                span {
+                                    "Refund #{&refund.id[..8]} ({refund.reason.as_str()}"
+                                    if let Some(note) = &refund.note { ": {note}" }
+                                    ")"
+                                    if let Some(number) = &refund.store_credit {
+                                        " to store credit {group_number(number)}"
+                                    }
+                                }
+                                span { "-{refund.total}" }
+                            }
+                        }
+                        if !order.refunds.is_empty() {
+                            div {
+                                class: "total-row total",
+                                span { "Net:" }
+                                span { "{order.net_total()}" }
+                            }
+                        }
+                        if tip_total.minor() > 0 {
+                            div {
+                                class: "total-row",
+                                span { "Tips:" }
+                                span { "{tip_total}" }
+                            }
                         }
                     }
 
-                    {payment_info.as_ref().map(|info| rsx! {
+                    for payment in order.payments.iter() {
                         div {
+                            key: "{payment.id}",
                             class: "payment-info",
-                            span { "{info}" }
+                            span {
+                                {format!("{} {} {} - Processed: {}", payment.method.icon(), payment.method.as_str(), payment.amount, payment.processed_at.format("%I:%M %p"))}
+                            }
+                            if let (Some(tendered), Some(change)) = (payment.tendered, payment.change) {
+                                span { class: "tender-info", "Tendered {tendered} · Change {change}" }
+                            }
+                            if let Some(card) = &payment.card {
+                                span { class: "tender-info", {format!("{} · Auth {}", card.summary(), card.auth_code)} }
+                            }
+                            if let Some(number) = &payment.gift_card {
+                                span { class: "tender-info", {mask_number(number)} }
+                            }
+                            if let Some(tip) = payment.tip {
+                                span { class: "tender-info", "Tip {tip}" }
+                            }
+                            if tips_adjustable && payment.card.is_some() {
+                                match tip_edit().filter(|(id, _)| id == &payment.id) {
+                                    Some((payment_id, text)) => {
+                                        let order_id = order.id.clone();
+                                        let tip = Money::parse(&text, order.currency);
+                                        rsx! {
+                                            div {
+                                                class: "tip-adjust",
+                                                input {
+                                                    r#type: "text",
+                                                    class: "form-input",
+                                                    inputmode: "decimal",
+                                                    value: "{text}",
+                                                    placeholder: "Tip",
+                                                    oninput: {
+                                                        let payment_id = payment_id.clone();
+                                                        move |e: FormEvent| tip_edit.set(Some((payment_id.clone(), e.value())))
+                                                    },
+                                                }
+                                                button {
+                                                    class: "btn-secondary-small",
+                                                    disabled: tip.is_none(),
+                                                    onclick: move |_| {
+                                                        if let Some(tip) = tip {
+                                                            on_adjust_tip.call(TipAdjustment {
+                                                                order_id: order_id.clone(),
+                                                                payment_id: payment_id.clone(),
+                                                                tip,
+                                                            });
+                                                            tip_edit.set(None);
+                                                        }
+                                                    },
+                                                    "Set tip"
+                                                }
+                                                button {
+                                                    class: "btn-secondary-small",
+                                                    onclick: move |_| tip_edit.set(None),
+                                                    "Cancel"
+                                                }
+                                            }
+                                        }
+                                    }
+                                    None => {
+                                        let payment_id = payment.id.clone();
+                                        let text = payment.tip.map(|t| t.plain_string()).unwrap_or_default();
+                                        rsx! {
+                                            button {
+                                                class: "btn-secondary-small",
+                                                onclick: move |_| tip_edit.set(Some((payment_id.clone(), text.clone()))),
+                                                "Adjust tip"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if let Some(void) = &order.void {
+                        div {
+                            class: "payment-info void-info",
+                            span {
+                                {format!("Voided {}: {}", void.voided_at.format("%I:%M %p"), void.reason.as_str())}
+                                if let Some(note) = &void.note { " ({note})" }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "history-actions",
+                        {
+                            let order_id = order.id.clone();
+                            rsx! {
+                                button {
+                                    class: "btn-secondary-small",
+                                    onclick: move |_| on_reprint.call(order_id.clone()),
+                                    "Reprint"
+                                }
+                            }
                         }
-                    })}
+                        if can_refund {
+                            button {
+                                class: "btn-secondary-small",
+                                onclick: move |_| action.set(Some(Action::Refund)),
+                                "Refund"
+                            }
+                        }
+                        if can_void {
+                            button {
+                                class: "btn-danger-small",
+                                onclick: move |_| action.set(Some(Action::Void)),
+                                "Void"
+                            }
+                        }
+                    }
+
+                    match action() {
+                        Some(Action::Refund) => rsx! {
+                            RefundPanel {
+                                order: order.clone(),
+                                on_submit: move |request| {
+                                    on_refund.call(request);
+                                    action.set(None);
+                                },
+                                on_cancel: move |_| action.set(None),
+                            }
+                        },
+                        Some(Action::Void) => rsx! {
+                            VoidPanel {
+                                order_id: order.id.clone(),
+                                on_submit: move |request| {
+                                    on_void.call(request);
+                                    action.set(None);
+                                },
+                                on_cancel: move |_| action.set(None),
+                            }
+                        },
+                        None => rsx! {},
+                    }
                 }
             })}
         }
     }
 }
+
+#[component]
+fn RefundPanel(
+    order: Order,
+    on_submit: EventHandler<RefundRequest>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut quantities = use_signal(|| {
+        order
+            .items
+            .iter()
+            .map(|i| (i.line_id.clone(), 0u32))
+            .collect::<Vec<_>>()
+    });
+    let mut reason = use_signal(|| ReasonCode::CustomerReturn);
+    let mut note = use_signal(String::new);
+    let mut restock = use_signal(|| true);
+    let mut store_credit = use_signal(|| false);
+
+    let request = RefundRequest {
+        order_id: order.id.clone(),
+        quantities: quantities(),
+        reason: reason(),
+        note: Some(note().trim().to_string()).filter(|n| !n.is_empty()),
+        restock: restock(),
+        store_credit: store_credit(),
+    };
+    // Dry run on a copy so the cashier sees the exact amount, or why not.
+    let preview = order.clone().refund(
+        &request.quantities,
+        request.reason,
+        request.note.clone(),
+        request.restock,
+    );
+
+    rsx! {
+        div {
+            class: "refund-panel",
+
+            h4 { "Refund items" }
+            for (idx, item) in order.items.iter().enumerate() {
+                {
+                    let refundable = order.refundable_quantity(&item.line_id);
+                    let selected = quantities.read()[idx].1;
+                    rsx! {
+                        div {
+                            key: "{item.line_id}",
+                            class: "refund-line",
+                            span { class: "refund-line-name", "{item.label()}" }
+                            span { class: "hint", "{refundable} of {item.quantity} refundable" }
+                            div {
+                                class: "item-controls",
+                                button {
+                                    class: "qty-btn",
+                                    disabled: selected == 0,
+                                    onclick: move |_| quantities.write()[idx].1 = selected.saturating_sub(1),
+                                    "−"
+                                }
+                                span { class: "qty", "{selected}" }
+                                button {
+                                    class: "qty-btn",
+                                    disabled: selected >= refundable,
+                                    onclick: move |_| quantities.write()[idx].1 = (selected + 1).min(refundable),
+                                    "+"
+                                }
+                                button {
+                                    class: "btn-secondary-small",
+                                    onclick: move |_| quantities.write()[idx].1 = refundable,
+                                    "All"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "form-group",
+                label { "Reason" }
+                select {
+                    class: "form-select",
+                    onchange: move |e| {
+                        if let Some(code) = ReasonCode::from_label(&e.value()) {
+                            reason.set(code);
+                        }
+                    },
+                    for code in ReasonCode::ALL {
+                        option {
+                            value: "{code.as_str()}",
+                            selected: code == reason(),
+                            "{code.as_str()}"
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "form-group",
+                label { "Note" }
+                input {
+                    r#type: "text",
+                    class: "form-input",
+                    value: "{note}",
+                    oninput: move |e| note.set(e.value()),
+                    placeholder: "Optional",
+                }
+            }
+
+            label {
+                class: "checkbox-label",
+                input {
+                    r#type: "checkbox",
+                    checked: restock(),
+                    onchange: move |e| restock.set(e.checked()),
+                }
+                "Return items to stock"
+            }
+
+            label {
+                class: "checkbox-label",
+                input {
+                    r#type: "checkbox",
+                    checked: store_credit(),
+                    onchange: move |e| store_credit.set(e.checked()),
+                }
+                "Give store credit instead"
+            }
+
+            match &preview {
+                Ok(refund) => rsx! {
+                    p {
+                        class: "refund-preview",
+                        if store_credit() {
+                            "Refund {refund.total} as store credit"
+                            if let Some(customer) = &order.customer {
+                                " for {customer.name}"
+                            }
+                        } else {
+                            {format!("Refund {} to {}", refund.total, refund.tender_summary())}
+                        }
+                    }
+                },
+                Err(e) => rsx! {
+                    p { class: "hint", "{e}" }
+                },
+            }
+
+            div {
+                class: "history-actions",
+                button {
+                    class: "btn-secondary-small",
+                    onclick: move |_| on_cancel.call(()),
+                    "Cancel"
+                }
+                button {
+                    class: "btn-danger-small",
+                    disabled: preview.is_err(),
+                    onclick: move |_| on_submit.call(request.clone()),
+                    "Issue Refund"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn VoidPanel(
+    order_id: String,
+    on_submit: EventHandler<VoidRequest>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut reason = use_signal(|| ReasonCode::OperatorError);
+    let mut note = use_signal(String::new);
+
+    rsx! {
+        div {
+            class: "refund-panel",
+
+            h4 { "Void entire order" }
+            p { class: "hint", "The full amount is reversed and all items return to stock." }
+
+            div {
+                class: "form-group",
+                label { "Reason" }
+                select {
+                    class: "form-select",
+                    onchange: move |e| {
+                        if let Some(code) = ReasonCode::from_label(&e.value()) {
+                            reason.set(code);
+                        }
+                    },
+                    for code in ReasonCode::ALL {
+                        option {
+                            value: "{code.as_str()}",
+                            selected: code == reason(),
+                            "{code.as_str()}"
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "form-group",
+                label { "Note" }
+                input {
+                    r#type: "text",
+                    class: "form-input",
+                    value: "{note}",
+                    oninput: move |e| note.set(e.value()),
+                    placeholder: "Optional",
+                }
+            }
+
+            div {
+                class: "history-actions",
+                button {
+                    class: "btn-secondary-small",
+                    onclick: move |_| on_cancel.call(()),
+                    "Cancel"
+                }
+                button {
+                    class: "btn-danger-small",
+                    onclick: move |_| {
+                        on_submit.call(VoidRequest {
+                            order_id: order_id.clone(),
+                            reason: reason(),
+                            note: Some(note().trim().to_string()).filter(|n| !n.is_empty()),
+                        });
+                    },
+                    "Void Order"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use dioxus::prelude::*;
+use crate::models::{Employee, Permission};
+use super::lock_screen::PinPad;
+
+// Asks someone allowed to do `permission` to approve it with their PIN,
+// without signing out the current operator.
+#[component]
+pub fn OverrideModal(
+    employees: Signal<Vec<Employee>>,
+    permission: Permission,
+    on_approve: EventHandler<Employee>,
+    on_failed: EventHandler<Employee>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut approver = use_signal(|| None::<String>);
+    let pin = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let approvers: Vec<Employee> = employees
+        .read()
+        .iter()
+        .filter(|e| e.can(permission))
+        .cloned()
+        .collect();
+
+    let mut approve = move || {
+        let mut pin = pin;
+        let employee = approver().and_then(|id| employees.read().iter().find(|e| e.id == id).cloned());
+        match employee {
+            Some(employee) if employee.can(permission) && employee.verify_pin(&pin()) => {
+                pin.set(String::new());
+                on_approve.call(employee);
+            }
+            Some(employee) => {
+                pin.set(String::new());
+                error.set(Some("Wrong PIN".to_string()));
+                on_failed.call(employee);
+            }
+            None => error.set(Some("Choose who is approving".to_string())),
+        }
+    };
+
+    rsx! {
+        div {
+            class: "modal-overlay",
+            onclick: move |_| on_cancel.call(()),
+
+            div {
+                class: "modal-content override-modal",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "Approval Needed" }
+                    button {
+                        class: "close-btn",
+                        onclick: move |_| on_cancel.call(()),
+                        "✕"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+                    p { {format!("A {} or above must approve: {}.", permission.min_role().as_str().to_lowercase(), permission.as_str())} }
+                    if approvers.is_empty() {
+                        p { class: "hint", "No employee on file can approve this." }
+                    }
+                    div {
+                        class: "lock-staff",
+                        for employee in approvers {
+                            button {
+                                key: "{employee.id}",
+                                class: if approver() == Some(employee.id.clone()) { "lock-staff-btn active" } else { "lock-staff-btn" },
+                                onclick: move |_| {
+                                    approver.set(Some(employee.id.clone()));
+                                    error.set(None);
+                                },
+                                span { "{employee.name}" }
+                                span { class: "lock-role", "{employee.role.as_str()}" }
+                            }
+                        }
+                    }
+                    PinPad { pin, on_enter: move |_| approve() }
+                    if let Some(message) = error() {
+                        p { class: "lock-error", "{message}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,24 +1,196 @@
+use std::sync::Arc;
 use dioxus::prelude::*;
-use crate::models::{Order, Payment, PaymentMethod};
+use chrono::Utc;
+use crate::models::{
+    customer_credit, mask_number, points_tendered, redeemable, CardEntry, CardKind, GiftCard, Money, Order, Payment,
+    PaymentMethod, Rate, Settings,
+};
+use crate::payments::{self, PaymentProcessor, ProcessorError};
+
+// How much of the balance the next tender is for.
+#[derive(Debug, Clone, PartialEq)]
+enum Split {
+    Full,
+    // Remaining payers sharing the balance equally.
+    Evenly(u32),
+    // Lines the current payer is settling.
+    ByItem(Vec<String>),
+}
+
+// Tip the customer chose for the current tender.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TipChoice {
+    NoTip,
+    Preset(Rate),
+    Custom,
+}
+
+// Progress of the current card tender on the terminal.
+#[derive(Debug, Clone, PartialEq)]
+enum TerminalState {
+    Idle,
+    Waiting,
+    Approved(String),
+    Declined(String),
+    TimedOut,
+    Failed(String),
+}
 
 #[component]
 pub fn PaymentModal(
     order: Signal<Order>,
+    settings: Signal<Settings>,
+    processor: Signal<Arc<dyn PaymentProcessor>>,
     show: Signal<bool>,
-    on_complete: EventHandler<Payment>,
+    // Points balance of the order's customer; `None` offers no points tender.
+    points_balance: Option<i64>,
+    gift_cards: Signal<Vec<GiftCard>>,
+    card_ledger: Signal<Vec<CardEntry>>,
+    on_payment: EventHandler<Payment>,
+    on_remove_payment: EventHandler<String>,
     on_cancel: EventHandler<()>,
 ) -> Element {
     let mut selected_method = use_signal(|| PaymentMethod::Cash);
+    let mut tendered = use_signal(String::new);
+    let mut amount_input = use_signal(String::new);
+    // Number of the gift card or store credit being paid from.
+    let mut card_input = use_signal(String::new);
+    let mut split = use_signal(|| Split::Full);
+    // Lines already paid for in a by-item split.
+    let mut settled = use_signal(Vec::<String>::new);
+    let mut terminal = use_signal(|| TerminalState::Idle);
+    let mut tip_choice = use_signal(|| TipChoice::NoTip);
+    let mut tip_input = use_signal(String::new);
     let current_order = order.read().clone();
 
     if !show() {
         return rsx! { div {} };
     }
 
+    let currency = current_order.currency;
+    let balance = current_order.balance_due();
+    let target = match split() {
+        Split::Full => balance,
+        Split::Evenly(parts) => current_order.split_evenly(parts)[0],
+        Split::ByItem(ids) => {
+            let share = current_order.lines_total(&ids);
+            if share > balance { balance } else { share }
+        }
+    };
+
+    let is_cash = selected_method() == PaymentMethod::Cash;
+    let is_points = selected_method() == PaymentMethod::Points;
+    let loyalty = settings.read().loyalty.clone();
+    // Points already tendered on this order are still in the balance.
+    let points_left = points_balance.map_or(0, |b| b - loyalty.points_for(points_tendered(&current_order)));
+    let points_worth = loyalty.points_value(points_left.max(0), target);
+    let card_kind = match selected_method() {
+        PaymentMethod::GiftCard => Some(CardKind::GiftCard),
+        PaymentMethod::StoreCredit => Some(CardKind::StoreCredit),
+        _ => None,
+    };
+    let now = Utc::now();
+    // Store credit defaults to the customer's own.
+    let card_number = match (card_kind, &current_order.customer) {
+        (Some(CardKind::StoreCredit), Some(customer)) if card_input().trim().is_empty() => {
+            customer_credit(&gift_cards.read(), &customer.id)
+                .into_iter()
+                .find(|c| !c.is_expired(now))
+                .map(|c| c.number.clone())
+                .unwrap_or_default()
+        }
+        _ => card_input(),
+    };
+    let card = card_kind.filter(|_| !card_number.trim().is_empty()).map(|kind| {
+        redeemable(&gift_cards.read(), &card_ledger.read(), &card_number, kind, &current_order, now)
+            .map(|(card, available)| (card.clone(), available))
+    });
+    let available = card.as_ref().and_then(|c| c.as_ref().ok()).map(|(_, available)| *available);
+    let suggested = match available {
+        Some(available) if available < target => available,
+        _ if is_points && points_worth < target => points_worth,
+        _ => target,
+    };
+    let amount_due = if is_cash { settings.read().cash_due(target) } else { target };
+    let tips = settings.read().tips.clone();
+    let offers_tip = tips.enabled && selected_method().takes_tip();
+    // Tips are a share of what this tender pays towards the order.
+    let tip_base = if is_cash {
+        target
+    } else {
+        Money::parse(&amount_input(), currency).unwrap_or(suggested)
+    };
+    let zero = Money::zero(currency);
+    let tip = match tip_choice() {
+        _ if !offers_tip => zero,
+        TipChoice::NoTip => zero,
+        TipChoice::Preset(rate) => tips.tip_for(tip_base, rate),
+        TipChoice::Custom => Money::parse(&tip_input(), currency).unwrap_or(zero),
+    };
+    let tip_error = tips.check(tip, tip_base).err();
+    let payment = if tip_error.is_some() {
+        None
+    } else if is_cash {
+        // The customer hands over the tip with the cash, so the change
+        // comes out of what is left after it.
+        Money::parse(&tendered(), currency)
+            .and_then(|t| Payment::cash(target, amount_due, t - tip))
+            .map(|p| p.with_tip(tip))
+    } else {
+        let amount = Money::parse(&amount_input(), currency).unwrap_or(suggested);
+        let points = loyalty.points_for(amount);
+        let covered = match (card_kind, available) {
+            (Some(_), Some(available)) => amount <= available,
+            (Some(_), None) => false,
+            (None, _) => !is_points || (points <= points_left && points >= loyalty.min_redeem as i64),
+        };
+        (amount > zero && amount <= balance && covered).then(|| Payment {
+            gift_card: card.as_ref().and_then(|c| c.as_ref().ok()).map(|(c, _)| c.number.clone()),
+            ..Payment::new(selected_method(), amount).with_tip(tip)
+        })
+    };
+    let settles = payment.as_ref().is_some_and(|p| p.amount >= balance);
+    let waiting = terminal() == TerminalState::Waiting;
+
+    let mut reset = move || {
+        tendered.set(String::new());
+        amount_input.set(String::new());
+        card_input.set(String::new());
+        tip_choice.set(TipChoice::NoTip);
+        tip_input.set(String::new());
+    };
+    let mut cancel = move || {
+        // The card may already be charged; wait for the terminal's answer.
+        if terminal() == TerminalState::Waiting {
+            return;
+        }
+        reset();
+        terminal.set(TerminalState::Idle);
+        on_cancel.call(());
+    };
+    // Moves the split on to the next payer once a tender is taken.
+    let mut advance = move |settles: bool| {
+        if settles {
+            split.set(Split::Full);
+            settled.set(Vec::new());
+            terminal.set(TerminalState::Idle);
+            return;
+        }
+        match split() {
+            Split::Evenly(parts) if parts > 2 => split.set(Split::Evenly(parts - 1)),
+            Split::Evenly(_) => split.set(Split::Full),
+            Split::ByItem(ids) => {
+                settled.write().extend(ids);
+                split.set(Split::ByItem(Vec::new()));
+            }
+            Split::Full => {}
+        }
+    };
+
     rsx! {
         div {
             class: "modal-overlay",
-            onclick: move |_| on_cancel.call(()),
+            onclick: move |_| cancel(),
 
             div {
                 class: "modal-content payment-modal",
@@ -29,7 +201,7 @@ pub fn PaymentModal(
                     h2 { "Payment" }
                     button {
                         class: "close-btn",
-                        onclick: move |_| on_cancel.call(()),
+                        onclick: move |_| cancel(),
                         "✕"
                     }
                 }
@@ -45,15 +217,154 @@ pub fn PaymentModal(
                             for item in current_order.items.iter() {
                                 div {
                                     class: "summary-item",
-                                    span { "{item.product_name} × {item.quantity}" }
-                                    span { "${item.price * item.quantity as f64:.2}" }
+                                    span { "{item.label()} × {item.quantity}" }
+                                    span { "{item.line_total()}" }
+                                }
+                                for discount in item.discounts.iter() {
+                                    div {
+                                        class: "summary-item discount-row",
+                                        span { "  {discount.name}" }
+                                        span { "{-discount.amount}" }
+                                    }
+                                }
+                            }
+                            for discount in current_order.order_discounts.iter() {
+                                div {
+                                    class: "summary-item discount-row",
+                                    span { "{discount.name}" }
+                                    span { "{-discount.amount}" }
+                                }
+                            }
+                        }
+                        div {
+                            class: "summary-item",
+                            span { "Total" }
+                            span { "{current_order.total}" }
+                        }
+                        for paid in current_order.payments.iter() {
+                            {
+                                let id = paid.id.clone();
+                                rsx! {
+                                    div {
+                                        key: "{paid.id}",
+                                        class: "summary-item paid-row",
+                                        span {
+                                            {format!("{} {}", paid.method.icon(), paid.method.as_str())}
+                                            if let Some(card) = &paid.card {
+                                                span { class: "hint", " {card.summary()}" }
+                                            }
+                                            if let Some(number) = &paid.gift_card {
+                                                span { class: "hint", " {mask_number(number)}" }
+                                            }
+                                            if let Some(tip) = paid.tip {
+                                                span { class: "hint", " + {tip} tip" }
+                                            }
+                                        }
+                                        span {
+                                            "-{paid.amount}"
+                                            button {
+                                                class: "remove-payment-btn",
+                                                title: "Remove payment",
+                                                onclick: move |_| {
+                                                    settled.set(Vec::new());
+                                                    on_remove_payment.call(id.clone());
+                                                },
+                                                "✕"
+                                            }
+                                        }
+                                    }
                                 }
                             }
                         }
                         div {
                             class: "summary-total",
-                            span { "Total:" }
-                            span { class: "total-amount", "${current_order.total:.2}" }
+                            span { if current_order.payments.is_empty() { "Total:" } else { "Balance Due:" } }
+                            span { class: "total-amount", "{balance}" }
+                        }
+                        if amount_due != target {
+                            div {
+                                class: "summary-item hint",
+                                span { "Cash rounding" }
+                                span { "{amount_due - target}" }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "split-options",
+                        h3 { "Split" }
+                        div {
+                            class: "split-modes",
+                            button {
+                                class: if split() == Split::Full { "split-btn selected" } else { "split-btn" },
+                                onclick: move |_| split.set(Split::Full),
+                                "Full balance"
+                            }
+                            button {
+                                class: if matches!(split(), Split::Evenly(_)) { "split-btn selected" } else { "split-btn" },
+                                onclick: move |_| split.set(Split::Evenly(2)),
+                                "Evenly"
+                            }
+                            button {
+                                class: if matches!(split(), Split::ByItem(_)) { "split-btn selected" } else { "split-btn" },
+                                onclick: move |_| split.set(Split::ByItem(Vec::new())),
+                                "By item"
+                            }
+                        }
+
+                        match split() {
+                            Split::Full => rsx! {},
+                            Split::Evenly(parts) => rsx! {
+                                div {
+                                    class: "item-controls split-evenly",
+                                    button {
+                                        class: "qty-btn",
+                                        disabled: parts <= 2,
+                                        onclick: move |_| split.set(Split::Evenly(parts - 1)),
+                                        "−"
+                                    }
+                                    span { class: "qty", "{parts}" }
+                                    button {
+                                        class: "qty-btn",
+                                        onclick: move |_| split.set(Split::Evenly(parts + 1)),
+                                        "+"
+                                    }
+                                    span { class: "hint", "ways · {target} this payment" }
+                                }
+                            },
+                            Split::ByItem(ids) => rsx! {
+                                div {
+                                    class: "split-items",
+                                    for item in current_order.items.iter() {
+                                        {
+                                            let line_id = item.line_id.clone();
+                                            let checked = ids.contains(&line_id);
+                                            let paid = settled.read().contains(&line_id);
+                                            rsx! {
+                                                label {
+                                                    key: "{item.line_id}",
+                                                    class: "checkbox-label",
+                                                    input {
+                                                        r#type: "checkbox",
+                                                        checked: checked || paid,
+                                                        disabled: paid,
+                                                        onchange: move |e| {
+                                                            if let Split::ByItem(ids) = &mut *split.write() {
+                                                                ids.retain(|id| id != &line_id);
+                                                                if e.checked() {
+                                                                    ids.push(line_id.clone());
+                                                                }
+                                                            }
+                                                        },
+                                                    }
+                                                    "{item.label()} × {item.quantity}"
+                                                }
+                                            }
+                                        }
+                                    }
+                                    span { class: "hint", "{target} this payment" }
+                                }
+                            },
                         }
                     }
 
@@ -83,24 +394,199 @@ pub fn PaymentModal(
                                 selected: selected_method() == PaymentMethod::MobilePay,
                                 on_select: move |m| selected_method.set(m),
                             }
+                            if points_balance.is_some() {
+                                PaymentMethodButton {
+                                    method: PaymentMethod::Points,
+                                    selected: is_points,
+                                    on_select: move |m| selected_method.set(m),
+                                }
+                            }
+                            PaymentMethodButton {
+                                method: PaymentMethod::GiftCard,
+                                selected: card_kind == Some(CardKind::GiftCard),
+                                on_select: move |m| {
+                                    card_input.set(String::new());
+                                    selected_method.set(m);
+                                },
+                            }
+                            PaymentMethodButton {
+                                method: PaymentMethod::StoreCredit,
+                                selected: card_kind == Some(CardKind::StoreCredit),
+                                on_select: move |m| {
+                                    card_input.set(String::new());
+                                    selected_method.set(m);
+                                },
+                            }
                         }
                     }
+
+                    if offers_tip {
+                        div {
+                            class: "tip-options",
+                            h3 { "Tip" }
+                            div {
+                                class: "split-modes",
+                                button {
+                                    class: if tip_choice() == TipChoice::NoTip { "split-btn selected" } else { "split-btn" },
+                                    onclick: move |_| tip_choice.set(TipChoice::NoTip),
+                                    "No tip"
+                                }
+                                for rate in tips.presets.iter().copied() {
+                                    button {
+                                        key: "{rate}",
+                                        class: if tip_choice() == TipChoice::Preset(rate) { "split-btn selected" } else { "split-btn" },
+                                        onclick: move |_| tip_choice.set(TipChoice::Preset(rate)),
+                                        "{rate}"
+                                        span { class: "tip-preset-amount", {tips.tip_for(tip_base, rate).to_string()} }
+                                    }
+                                }
+                                button {
+                                    class: if tip_choice() == TipChoice::Custom { "split-btn selected" } else { "split-btn" },
+                                    onclick: move |_| tip_choice.set(TipChoice::Custom),
+                                    "Custom"
+                                }
+                            }
+                            if tip_choice() == TipChoice::Custom {
+                                input {
+                                    r#type: "text",
+                                    class: "form-input",
+                                    inputmode: "decimal",
+                                    value: "{tip_input}",
+                                    placeholder: "Tip amount",
+                                    oninput: move |e| tip_input.set(e.value()),
+                                }
+                            }
+                            if let Some(e) = &tip_error {
+                                span { class: "form-error", "Can't add this tip: {e}" }
+                            } else if tip.minor() > 0 {
+                                span { class: "hint", "{tip} tip on top of {tip_base}" }
+                            }
+                        }
+                    }
+
+                    if is_cash {
+                        CashTender {
+                            amount_due: amount_due + tip,
+                            quick_cash: settings.read().quick_cash.clone(),
+                            tendered,
+                        }
+                    } else {
+                        if card_kind.is_some() {
+                            div {
+                                class: "form-group gift-card-number",
+                                label { "Card number" }
+                                input {
+                                    r#type: "text",
+                                    class: "form-input",
+                                    inputmode: "numeric",
+                                    value: "{card_input}",
+                                    placeholder: if card_number.is_empty() { "Scan or type the card number".to_string() } else { card_number.clone() },
+                                    oninput: move |e| card_input.set(e.value()),
+                                }
+                                match &card {
+                                    Some(Ok((card, available))) => rsx! {
+                                        span {
+                                            class: "hint",
+                                            "{card.kind.as_str()} {card.masked()}: {available} available"
+                                            if let Some(at) = card.expires_at {
+                                                {format!(", expires {}", at.format("%b %d, %Y"))}
+                                            }
+                                        }
+                                    },
+                                    Some(Err(e)) => rsx! {
+                                        span { class: "form-error", "Can't pay from this card: {e}" }
+                                    },
+                                    None => rsx! {},
+                                }
+                            }
+                        }
+                        div {
+                            class: "form-group payment-amount",
+                            label { "Amount" }
+                            input {
+                                r#type: "text",
+                                class: "form-input",
+                                inputmode: "decimal",
+                                value: "{amount_input}",
+                                placeholder: "{suggested.plain_string()}",
+                                oninput: move |e| amount_input.set(e.value()),
+                            }
+                            if is_points {
+                                span {
+                                    class: "hint",
+                                    "{points_left} points available, worth {points_worth}. At least {loyalty.min_redeem} points per payment."
+                                }
+                            }
+                        }
+                    }
+                }
+
+                match terminal() {
+                    TerminalState::Idle => rsx! {},
+                    TerminalState::Waiting => rsx! {
+                        div { class: "terminal-status waiting", "Waiting for the card terminal… ask the customer to tap, insert or swipe." }
+                    },
+                    TerminalState::Approved(summary) => rsx! {
+                        div { class: "terminal-status approved", "Approved: {summary}" }
+                    },
+                    TerminalState::Declined(reason) => rsx! {
+                        div { class: "terminal-status declined", "Declined: {reason}. Try another card or method." }
+                    },
+                    TerminalState::TimedOut => rsx! {
+                        div { class: "terminal-status declined", "The terminal did not respond in time. Any hold on the card has been released; try again." }
+                    },
+                    TerminalState::Failed(message) => rsx! {
+                        div { class: "terminal-status declined", "{message}" }
+                    },
                 }
 
                 div {
                     class: "modal-footer",
                     button {
                         class: "btn-secondary",
-                        onclick: move |_| on_cancel.call(()),
+                        disabled: waiting,
+                        onclick: move |_| cancel(),
                         "Cancel"
                     }
                     button {
                         class: "btn-primary",
+                        disabled: payment.is_none() || waiting,
                         onclick: move |_| {
-                            let payment = Payment::new(selected_method(), current_order.total);
-                            on_complete.call(payment);
+                            let Some(payment) = payment.clone() else {
+                                return;
+                            };
+                            reset();
+                            if !payment.method.uses_terminal() {
+                                terminal.set(TerminalState::Idle);
+                                advance(settles);
+                                on_payment.call(payment);
+                                return;
+                            }
+
+                            terminal.set(TerminalState::Waiting);
+                            let processor = processor();
+                            let tip = payment.tip_amount();
+                            spawn(async move {
+                                match payments::charge(processor, payment.method, payment.amount, tip).await {
+                                    Ok(charged) => {
+                                        if let Some(card) = &charged.card {
+                                            terminal.set(TerminalState::Approved(format!("{} · Auth {}", card.summary(), card.auth_code)));
+                                        }
+                                        advance(settles);
+                                        on_payment.call(charged);
+                                    }
+                                    Err(ProcessorError::Declined(reason)) => terminal.set(TerminalState::Declined(reason)),
+                                    Err(ProcessorError::Timeout) => terminal.set(TerminalState::TimedOut),
+                                    Err(e) => terminal.set(TerminalState::Failed(e.to_string())),
+                                }
+                            });
                         },
-                        "Complete Payment"
+                        match &payment {
+                            _ if waiting => rsx! { "Waiting for card…" },
+                            Some(p) if p.tip.is_some() && !settles => rsx! { "Apply {p.amount} + {p.tip_amount()} tip" },
+                            Some(p) if !settles => rsx! { "Apply {p.amount}" },
+                            _ => rsx! { "Complete Payment" },
+                        }
                     }
                 }
             }
@@ -132,3 +618,86 @@ fn PaymentMethodButton(
         }
     }
 }
+
+#[component]
+fn CashTender(amount_due: Money, quick_cash: Vec<Money>, tendered: Signal<String>) -> Element {
+    let currency = amount_due.currency();
+    let amount = Money::parse(&tendered(), currency);
+
+    // Exact, the next whole unit up, then every configured note that covers the bill.
+    let mut quick: Vec<Money> = vec![amount_due];
+    for option in std::iter::once(amount_due.ceil_major()).chain(quick_cash.iter().copied()) {
+        if option > amount_due && !quick.contains(&option) {
+            quick.push(option);
+        }
+    }
+
+    let mut press = move |key: &str| {
+        let mut next = tendered();
+        match key {
+            "⌫" => {
+                next.pop();
+            }
+            _ => next.push_str(key),
+        }
+        // Ignore keys that would make the amount unparseable (e.g. a third decimal).
+        if next.is_empty() || Money::parse(&next, currency).is_some() {
+            tendered.set(next);
+        }
+    };
+
+    rsx! {
+        div {
+            class: "cash-tender",
+            h3 { "Cash Tendered" }
+
+            div {
+                class: "quick-cash",
+                for (idx, option) in quick.into_iter().enumerate() {
+                    button {
+                        key: "{option}",
+                        class: "quick-cash-btn",
+                        onclick: move |_| tendered.set(option.plain_string()),
+                        if idx == 0 { "Exact" } else { "{option}" }
+                    }
+                }
+            }
+
+            div {
+                class: "tender-display",
+                span { class: "tender-label", "Tendered" }
+                span { class: "tender-value", if tendered().is_empty() { "—" } else { "{tendered}" } }
+            }
+
+            div {
+                class: "keypad",
+                for key in ["1", "2", "3", "4", "5", "6", "7", "8", "9", ".", "0", "⌫"] {
+                    button {
+                        key: "{key}",
+                        class: "keypad-btn",
+                        onclick: move |_| press(key),
+                        "{key}"
+                    }
+                }
+            }
+
+            match amount {
+                Some(amount) if amount >= amount_due => rsx! {
+                    div {
+                        class: "change-due",
+                        span { "Change Due:" }
+                        span { class: "change-amount", "{amount - amount_due}" }
+                    }
+                },
+                Some(amount) => rsx! {
+                    div {
+                        class: "change-due short",
+                        span { "Left to pay:" }
+                        span { class: "change-amount", "{amount_due - amount}" }
+                    }
+                },
+                None => rsx! {},
+            }
+        }
+    }
+}
@@ -1,77 +1,222 @@
 use dioxus::prelude::*;
-use crate::models::{Product, ProductCategory};
+use crate::models::{
+    category_color, category_lineage, category_name, category_tree, in_category, recently_sold, top_sellers, Category,
+    Order, Product, SearchIndex,
+};
+
+// Cards drawn at once; a bigger catalog is narrowed down by searching.
+const MAX_CARDS: usize = 200;
+// Buttons in each shortcut row.
+const SHORTCUTS: usize = 8;
 
 #[component]
 pub fn ProductGrid(
     products: Signal<Vec<Product>>,
-    selected_category: Signal<Option<ProductCategory>>,
+    categories: Signal<Vec<Category>>,
+    // Category id; its sub-categories are shown too.
+    selected_category: Signal<Option<String>>,
+    // Order history, for the top seller and recently sold shortcuts.
+    orders: Signal<Vec<Order>>,
     on_product_click: EventHandler<Product>,
 ) -> Element {
+    let mut query = use_signal(String::new);
+    // Search result picked with the arrow keys.
+    let mut highlighted = use_signal(|| 0usize);
+
+    // Rebuilt only when the catalog changes, not on every keystroke.
+    let index = use_memo(move || SearchIndex::build(&products.read()));
     let filtered_products = use_memo(move || {
         let prods = products.read();
+        let text = query();
+        let matches: Vec<&Product> = if text.trim().is_empty() {
+            prods.iter().filter(|p| !p.archived).collect()
+        } else {
+            index.read().search(&text).into_iter().filter_map(|i| prods.get(i)).collect()
+        };
         match selected_category() {
-            Some(cat) => prods.iter().filter(|p| p.category == cat).cloned().collect::<Vec<_>>(),
-            None => prods.clone(),
+            Some(id) => {
+                let list = categories.read();
+                matches.into_iter().filter(|p| in_category(&list, &p.category, &id)).cloned().collect::<Vec<_>>()
+            }
+            None => matches.into_iter().cloned().collect(),
         }
     });
+    let shortcuts = use_memo(move || {
+        let prods = products.read();
+        let history = orders.read();
+        let pick = |ids: Vec<String>| -> Vec<Product> {
+            ids.iter()
+                .filter_map(|id| prods.iter().find(|p| &p.id == id && !p.archived))
+                .cloned()
+                .collect()
+        };
+        (pick(top_sellers(&history, SHORTCUTS)), pick(recently_sold(&history, SHORTCUTS)))
+    });
+
+    let list = categories.read();
+    let tree = category_tree(&list);
+    let selected = selected_category();
+    // Top-level category the selection sits under; its sub-categories get a
+    // second row of filters.
+    let branch = selected
+        .as_deref()
+        .and_then(|id| category_lineage(&list, id).last().map(|c| c.id.clone()));
+    let top_level: Vec<Category> = tree.iter().filter(|(depth, _)| *depth == 0).map(|(_, c)| (*c).clone()).collect();
+    let sub_categories: Vec<(usize, Category)> = match &branch {
+        Some(top) => tree
+            .iter()
+            .filter(|(depth, c)| *depth > 0 && in_category(&list, &c.id, top))
+            .map(|(depth, c)| (*depth, (*c).clone()))
+            .collect(),
+        None => Vec::new(),
+    };
+    let results = filtered_products();
+    let shown = results.len().min(MAX_CARDS);
+    let current = highlighted().min(shown.saturating_sub(1));
+    let searching = !query().trim().is_empty();
+    let (top, recent) = shortcuts();
+    let has_picks = !top.is_empty() || !recent.is_empty();
 
     rsx! {
         div {
             class: "product-grid-container",
 
+            // Search
+            div {
+                class: "product-search",
+                input {
+                    r#type: "search",
+                    class: "form-input product-search-input",
+                    value: "{query}",
+                    placeholder: "Search name, SKU or barcode",
+                    oninput: move |e| {
+                        query.set(e.value());
+                        highlighted.set(0);
+                    },
+                    onkeydown: move |e| match e.key() {
+                        Key::ArrowDown => {
+                            e.prevent_default();
+                            highlighted.set((current + 1).min(shown.saturating_sub(1)));
+                        }
+                        Key::ArrowUp => {
+                            e.prevent_default();
+                            highlighted.set(current.saturating_sub(1));
+                        }
+                        Key::Enter => {
+                            let picked = filtered_products.read().get(current).cloned();
+                            if let Some(product) = picked {
+                                on_product_click.call(product);
+                                query.set(String::new());
+                                highlighted.set(0);
+                            }
+                        }
+                        Key::Escape => {
+                            query.set(String::new());
+                            highlighted.set(0);
+                        }
+                        _ => {}
+                    },
+                }
+                if searching {
+                    span { class: "hint", "{results.len()} found · ↑ ↓ to pick, Enter to add" }
+                }
+            }
+
+            // Shortcuts
+            if !searching && has_picks {
+                div {
+                    class: "quick-picks",
+                    for (label, picks) in [("Top sellers", top), ("Recent", recent)] {
+                        if !picks.is_empty() {
+                            div {
+                                key: "{label}",
+                                class: "quick-pick-row",
+                                span { class: "quick-pick-label", "{label}" }
+                                for product in picks {
+                                    button {
+                                        key: "{product.id}",
+                                        class: "quick-pick-btn",
+                                        onclick: move |_| on_product_click.call(product.clone()),
+                                        "{product.name}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
             // Category Filter
             div {
                 class: "category-filter",
                 button {
-                    class: if selected_category().is_none() { "category-btn active" } else { "category-btn" },
+                    class: if selected.is_none() { "category-btn active" } else { "category-btn" },
                     onclick: move |_| selected_category.set(None),
                     "All"
                 }
-                button {
-                    class: if selected_category() == Some(ProductCategory::Food) { "category-btn active" } else { "category-btn" },
-                    onclick: move |_| selected_category.set(Some(ProductCategory::Food)),
-                    "Food"
-                }
-                button {
-                    class: if selected_category() == Some(ProductCategory::Beverage) { "category-btn active" } else { "category-btn" },
-                    onclick: move |_| selected_category.set(Some(ProductCategory::Beverage)),
-                    "Beverage"
-                }
-                button {
-                    class: if selected_category() == Some(ProductCategory::Retail) { "category-btn active" } else { "category-btn" },
-                    onclick: move |_| selected_category.set(Some(ProductCategory::Retail)),
-                    "Retail"
+                for category in top_level {
+                    button {
+                        key: "{category.id}",
+                        class: if branch.as_deref() == Some(category.id.as_str()) { "category-btn active" } else { "category-btn" },
+                        style: "border-left: 4px solid {category.color};",
+                        onclick: move |_| selected_category.set(Some(category.id.clone())),
+                        "{category.name}"
+                    }
                 }
-                button {
-                    class: if selected_category() == Some(ProductCategory::Service) { "category-btn active" } else { "category-btn" },
-                    onclick: move |_| selected_category.set(Some(ProductCategory::Service)),
-                    "Service"
+            }
+            if !sub_categories.is_empty() {
+                div {
+                    class: "category-filter sub-category-filter",
+                    for (depth, category) in sub_categories {
+                        button {
+                            key: "{category.id}",
+                            class: if selected.as_deref() == Some(category.id.as_str()) { "category-btn active" } else { "category-btn" },
+                            onclick: move |_| selected_category.set(Some(category.id.clone())),
+                            {format!("{}{}", "› ".repeat(depth - 1), category.name)}
+                        }
+                    }
                 }
             }
 
             // Products Grid
             div {
                 class: "products-grid",
-                for product in filtered_products().iter() {
+                for (i, product) in results.iter().take(MAX_CARDS).enumerate() {
                     ProductCard {
                         key: "{product.id}",
+                        category: category_name(&list, &product.category).to_string(),
+                        color: category_color(&list, &product.category).to_string(),
+                        highlighted: searching && i == current,
                         product: product.clone(),
                         on_click: move |p| on_product_click.call(p),
                     }
                 }
             }
+            if searching && results.is_empty() {
+                p { class: "hint", "No products match \"{query}\"" }
+            }
+            if results.len() > MAX_CARDS {
+                p { class: "hint", "Showing {MAX_CARDS} of {results.len()} products; search or pick a category to narrow it down" }
+            }
         }
     }
 }
 
 #[component]
-fn ProductCard(product: Product, on_click: EventHandler<Product>) -> Element {
+fn ProductCard(
+    product: Product,
+    category: String,
+    color: String,
+    // The search result Enter would add.
+    highlighted: bool,
+    on_click: EventHandler<Product>,
+) -> Element {
     let product_clone = product.clone();
 
     rsx! {
         div {
-            class: "product-card",
-            style: "border-left: 4px solid {product.category.color()};",
+            class: if highlighted { "product-card highlighted" } else { "product-card" },
+            style: "border-left: 4px solid {color};",
             onclick: move |_| on_click.call(product_clone.clone()),
 
             div {
@@ -79,13 +224,13 @@ fn ProductCard(product: Product, on_click: EventHandler<Product>) -> Element {
                 h3 { class: "product-name", "{product.name}" }
                 div {
                     class: "product-meta",
-                    span { class: "product-category", "{product.category.as_str()}" }
+                    span { class: "product-category", "{category}" }
                     span { class: "product-stock", "Stock: {product.stock}" }
                 }
             }
             div {
                 class: "product-price",
-                "${product.price:.2}"
+                "{product.price}"
             }
         }
     }
@@ -1,18 +1,62 @@
 use dioxus::prelude::*;
-use crate::models::{Product, ProductCategory};
+use crate::models::{
+    category_color, category_name, category_tree, Category, Currency, GroupKind, Money, MovementKind, OptionGroup,
+    Product, ProductOption, StockMovement, TaxConfig,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockAdjustment {
+    pub product_id: String,
+    pub kind: MovementKind,
+    // Signed change to on-hand stock.
+    pub quantity: i64,
+    pub note: Option<String>,
+}
 
 #[component]
 pub fn ProductManager(
     products: Signal<Vec<Product>>,
+    categories: Signal<Vec<Category>>,
+    tax_config: Signal<TaxConfig>,
+    ledger: Signal<Vec<StockMovement>>,
     on_add: EventHandler<Product>,
-    on_delete: EventHandler<String>,
+    on_adjust: EventHandler<StockAdjustment>,
+    // Product edited in the edit or options panel.
+    on_update: EventHandler<Product>,
+    on_archive: EventHandler<String>,
+    on_restore: EventHandler<String>,
 ) -> Element {
     let mut show_add_form = use_signal(|| false);
-    let mut new_name = use_signal(|| String::new());
-    let mut new_price = use_signal(|| String::new());
-    let mut new_category = use_signal(|| ProductCategory::Food);
+    let mut new_name = use_signal(String::new);
+    let mut new_price = use_signal(String::new);
+    let mut new_category = use_signal(String::new);
+    let mut new_tax_class = use_signal(|| None::<String>);
+    let mut new_sku = use_signal(String::new);
+    let mut add_error = use_signal(|| None::<String>);
+    let mut adjusting = use_signal(|| None::<String>);
+    let mut configuring = use_signal(|| None::<String>);
+    let mut editing = use_signal(|| None::<String>);
+    let mut show_archived = use_signal(|| false);
 
     let all_products = products.read();
+    let archived_count = all_products.iter().filter(|p| p.archived).count();
+    let listed: Vec<Product> = all_products
+        .iter()
+        .filter(|p| show_archived() || !p.archived)
+        .cloned()
+        .collect();
+    let add_catalog = all_products.to_vec();
+    let taxes = tax_config.read();
+    let category_list = categories.read();
+    let category_options: Vec<(String, String)> = category_tree(&category_list)
+        .into_iter()
+        .map(|(depth, c)| (c.id.clone(), format!("{}{}", "— ".repeat(depth), c.name)))
+        .collect();
+    // Until one is picked, the first category in the list.
+    let chosen_category = Some(new_category())
+        .filter(|id| category_options.iter().any(|(c, _)| c == id))
+        .or_else(|| category_options.first().map(|(id, _)| id.clone()));
+    let add_category = chosen_category.clone();
 
     rsx! {
         div {
@@ -21,10 +65,23 @@ pub fn ProductManager(
             div {
                 class: "manager-header",
                 h2 { "Product Management" }
-                button {
-                    class: "btn-primary",
-                    onclick: move |_| show_add_form.set(!show_add_form()),
-                    if show_add_form() { "Cancel" } else { "+ Add Product" }
+                div {
+                    class: "manager-actions",
+                    if archived_count > 0 {
+                        button {
+                            class: "btn-secondary",
+                            onclick: move |_| show_archived.set(!show_archived()),
+                            if show_archived() { "Hide archived" } else { "Show archived ({archived_count})" }
+                        }
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| {
+                            add_error.set(None);
+                            show_add_form.set(!show_add_form());
+                        },
+                        if show_add_form() { "Cancel" } else { "+ Add Product" }
+                    }
                 }
             }
 
@@ -60,45 +117,121 @@ pub fn ProductManager(
                     div {
                         class: "form-group",
                         label { "Category" }
+                        select {
+                            class: "form-select",
+                            onchange: move |e| new_category.set(e.value()),
+
+                            for (id, label) in category_options.iter() {
+                                option {
+                                    value: "{id}",
+                                    selected: chosen_category.as_deref() == Some(id.as_str()),
+                                    "{label}"
+                                }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "form-group",
+                        label { "Tax Class" }
                         select {
                             class: "form-select",
                             onchange: move |e| {
-                                let cat = match e.value().as_str() {
-                                    "Food" => ProductCategory::Food,
-                                    "Beverage" => ProductCategory::Beverage,
-                                    "Retail" => ProductCategory::Retail,
-                                    "Service" => ProductCategory::Service,
-                                    _ => ProductCategory::Food,
-                                };
-                                new_category.set(cat);
+                                let id = e.value();
+                                new_tax_class.set((!id.is_empty()).then_some(id));
                             },
 
-                            option { value: "Food", "Food" }
-                            option { value: "Beverage", "Beverage" }
-                            option { value: "Retail", "Retail" }
-                            option { value: "Service", "Service" }
+                            option { value: "", "Category default" }
+                            for class in taxes.classes.iter() {
+                                option { value: "{class.id}", "{class.name}" }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "form-group",
+                        label { "SKU" }
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            value: "{new_sku}",
+                            oninput: move |e| new_sku.set(e.value()),
+                            placeholder: "Optional",
                         }
                     }
 
                     button {
                         class: "btn-primary",
                         onclick: move |_| {
-                            if !new_name().is_empty() && !new_price().is_empty() {
-                                if let Ok(price) = new_price().parse::<f64>() {
-                                    let product = Product::new(
-                                        new_name(),
-                                        price,
-                                        new_category(),
-                                    );
-                                    on_add.call(product);
-                                    new_name.set(String::new());
-                                    new_price.set(String::new());
-                                    show_add_form.set(false);
-                                }
+                            let Some(category) = add_category.clone() else {
+                                add_error.set(Some("Add a category first".to_string()));
+                                return;
+                            };
+                            let Some(price) = Money::parse(&new_price(), Currency::default()) else {
+                                add_error.set(Some("Enter a price".to_string()));
+                                return;
+                            };
+                            let mut product = Product::new(new_name().trim().to_string(), price, category);
+                            product.tax_class = new_tax_class();
+                            product.sku = Some(new_sku().trim().to_string()).filter(|s| !s.is_empty());
+                            if let Err(e) = product.validate(&add_catalog) {
+                                add_error.set(Some(e.to_string()));
+                                return;
                             }
+                            on_add.call(product);
+                            new_name.set(String::new());
+                            new_price.set(String::new());
+                            new_sku.set(String::new());
+                            add_error.set(None);
+                            show_add_form.set(false);
                         },
                         "Add Product"
                     }
+
+                    if let Some(message) = add_error() {
+                        p { class: "form-error", "{message}" }
+                    }
+                }
+            }
+
+            if let Some(product) = editing().and_then(|id| all_products.iter().find(|p| p.id == id).cloned()) {
+                ProductEditPanel {
+                    key: "{product.id}",
+                    catalog: all_products.to_vec(),
+                    categories: category_options.clone(),
+                    tax_classes: taxes.classes.iter().map(|c| (c.id.clone(), c.name.clone())).collect::<Vec<_>>(),
+                    product,
+                    on_save: move |product| {
+                        on_update.call(product);
+                        editing.set(None);
+                    },
+                    on_close: move |_| editing.set(None),
+                }
+            }
+
+            if let Some(product) = adjusting().and_then(|id| all_products.iter().find(|p| p.id == id).cloned()) {
+                StockAdjustPanel {
+                    key: "{product.id}",
+                    movements: ledger
+                        .read()
+                        .iter()
+                        .rev()
+                        .filter(|m| m.product_id == product.id)
+                        .take(10)
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                    product,
+                    on_apply: move |adjustment| on_adjust.call(adjustment),
+                    on_close: move |_| adjusting.set(None),
+                }
+            }
+
+            if let Some(product) = configuring().and_then(|id| all_products.iter().find(|p| p.id == id).cloned()) {
+                OptionsPanel {
+                    key: "{product.id}",
+                    product,
+                    on_save: move |product| on_update.call(product),
+                    on_close: move |_| configuring.set(None),
                 }
             }
 
@@ -110,32 +243,86 @@ pub fn ProductManager(
                             th { "Name" }
                             th { "Price" }
                             th { "Category" }
+                            th { "Tax" }
                             th { "Stock" }
                             th { "Actions" }
                         }
                     }
                     tbody {
-                        for product in all_products.iter() {
+                        for product in listed.iter() {
                             tr {
                                 key: "{product.id}",
-                                td { "{product.name}" }
-                                td { "${product.price:.2}" }
+                                class: if product.archived { "archived-row" } else { "" },
+                                td {
+                                    "{product.name}"
+                                    if let Some(sku) = &product.sku {
+                                        span { class: "hint", " · {sku}" }
+                                    }
+                                    if product.archived {
+                                        span { class: "hint", " · archived" }
+                                    }
+                                    if product.has_options() {
+                                        span { class: "hint", " · {product.options.len()} option groups" }
+                                    }
+                                }
+                                td { "{product.price}" }
                                 td {
                                     span {
                                         class: "category-badge",
-                                        style: "background-color: {product.category.color()};",
-                                        "{product.category.as_str()}"
+                                        style: "background-color: {category_color(&category_list, &product.category)};",
+                                        "{category_name(&category_list, &product.category)}"
                                     }
                                 }
-                                td { "{product.stock}" }
                                 td {
+                                    {taxes.class_for(product, &category_list).map_or("—", |c| c.name.as_str())}
+                                }
+                                td {
+                                    class: if product.stock <= 0 { "stock-out" } else { "" },
+                                    "{product.stock}"
+                                }
+                                td {
+                                    button {
+                                        class: "btn-secondary-small",
+                                        onclick: {
+                                            let id = product.id.clone();
+                                            move |_| editing.set(Some(id.clone()))
+                                        },
+                                        "Edit"
+                                    }
                                     button {
-                                        class: "btn-danger-small",
+                                        class: "btn-secondary-small",
                                         onclick: {
                                             let id = product.id.clone();
-                                            move |_| on_delete.call(id.clone())
+                                            move |_| adjusting.set(Some(id.clone()))
                                         },
-                                        "Delete"
+                                        "Stock"
+                                    }
+                                    button {
+                                        class: "btn-secondary-small",
+                                        onclick: {
+                                            let id = product.id.clone();
+                                            move |_| configuring.set(Some(id.clone()))
+                                        },
+                                        "Options"
+                                    }
+                                    if product.archived {
+                                        button {
+                                            class: "btn-secondary-small",
+                                            onclick: {
+                                                let id = product.id.clone();
+                                                move |_| on_restore.call(id.clone())
+                                            },
+                                            "Restore"
+                                        }
+                                    } else {
+                                        button {
+                                            class: "btn-danger-small",
+                                            onclick: {
+                                                let id = product.id.clone();
+                                                move |_| on_archive.call(id.clone())
+                                            },
+                                            "Archive"
+                                        }
                                     }
                                 }
                             }
@@ -146,3 +333,630 @@ pub fn ProductManager(
         }
     }
 }
+
+// Variant and modifier groups of one product. Every change is saved at once.
+#[component]
+fn OptionsPanel(product: Product, on_save: EventHandler<Product>, on_close: EventHandler<()>) -> Element {
+    let mut group_name = use_signal(String::new);
+    let mut group_kind = use_signal(|| GroupKind::Variant);
+    let mut group_min = use_signal(|| "0".to_string());
+    let mut group_max = use_signal(|| "1".to_string());
+    let mut option_group = use_signal(|| product.options.first().map(|g| g.id.clone()).unwrap_or_default());
+    let mut option_name = use_signal(String::new);
+    let mut option_delta = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let add_group_product = product.clone();
+    let add_option_product = product.clone();
+
+    rsx! {
+        div {
+            class: "stock-adjust-panel",
+
+            div {
+                class: "manager-header",
+                h3 { "Options: {product.name} ({product.price})" }
+                button {
+                    class: "btn-secondary",
+                    onclick: move |_| on_close.call(()),
+                    "Close"
+                }
+            }
+
+            if product.options.is_empty() {
+                p { class: "hint", "No variants or modifiers yet" }
+            }
+            for group in product.options.iter() {
+                div {
+                    key: "{group.id}",
+                    class: "option-group",
+                    div {
+                        class: "option-group-header",
+                        h4 { "{group.name}" }
+                        span { class: "hint", "{group.kind.as_str()} · {group.rule_label()}" }
+                        button {
+                            class: "btn-danger-small",
+                            onclick: {
+                                let product = product.clone();
+                                let id = group.id.clone();
+                                move |_| {
+                                    let mut updated = product.clone();
+                                    updated.options.retain(|g| g.id != id);
+                                    on_save.call(updated);
+                                }
+                            },
+                            "Remove group"
+                        }
+                    }
+                    div {
+                        class: "option-choices",
+                        for option in group.options.iter() {
+                            span {
+                                key: "{option.id}",
+                                class: "coupon-chip",
+                                "{option.name} "
+                                {if option.price_delta.minor() >= 0 { format!("+{}", option.price_delta) } else { option.price_delta.to_string() }}
+                                button {
+                                    class: "discount-remove-btn",
+                                    title: "Remove option",
+                                    onclick: {
+                                        let product = product.clone();
+                                        let group_id = group.id.clone();
+                                        let id = option.id.clone();
+                                        move |_| {
+                                            let mut updated = product.clone();
+                                            if let Some(g) = updated.options.iter_mut().find(|g| g.id == group_id) {
+                                                g.options.retain(|o| o.id != id);
+                                            }
+                                            on_save.call(updated);
+                                        }
+                                    },
+                                    "✕"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "stock-adjust-form",
+                div {
+                    class: "form-group",
+                    label { "New group" }
+                    input {
+                        r#type: "text",
+                        class: "form-input",
+                        value: "{group_name}",
+                        oninput: move |e| group_name.set(e.value()),
+                        placeholder: "e.g. Size, Milk",
+                    }
+                }
+                div {
+                    class: "form-group",
+                    label { "Kind" }
+                    select {
+                        class: "form-select",
+                        onchange: move |e| {
+                            let kind = if e.value() == "Modifier" { GroupKind::Modifier } else { GroupKind::Variant };
+                            group_kind.set(kind);
+                        },
+                        option { value: "Variant", "Variant (pick one)" }
+                        option { value: "Modifier", "Modifier" }
+                    }
+                }
+                if group_kind() == GroupKind::Modifier {
+                    div {
+                        class: "form-group",
+                        label { "Min" }
+                        input {
+                            r#type: "number",
+                            class: "form-input",
+                            value: "{group_min}",
+                            oninput: move |e| group_min.set(e.value()),
+                            step: "1",
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Max" }
+                        input {
+                            r#type: "number",
+                            class: "form-input",
+                            value: "{group_max}",
+                            oninput: move |e| group_max.set(e.value()),
+                            step: "1",
+                        }
+                    }
+                }
+                button {
+                    class: "btn-primary",
+                    onclick: move |_| {
+                        let name = group_name().trim().to_string();
+                        let group = match group_kind() {
+                            GroupKind::Variant => OptionGroup::variant(name),
+                            GroupKind::Modifier => {
+                                let (Ok(min), Ok(max)) = (group_min().trim().parse(), group_max().trim().parse()) else {
+                                    error.set(Some("Min and max must be whole numbers".to_string()));
+                                    return;
+                                };
+                                OptionGroup::modifier(name, min, max)
+                            }
+                        };
+                        if let Err(e) = group.validate() {
+                            error.set(Some(e.to_string()));
+                            return;
+                        }
+                        let mut updated = add_group_product.clone();
+                        option_group.set(group.id.clone());
+                        updated.options.push(group);
+                        on_save.call(updated);
+                        group_name.set(String::new());
+                        error.set(None);
+                    },
+                    "Add Group"
+                }
+            }
+
+            if !product.options.is_empty() {
+                div {
+                    class: "stock-adjust-form",
+                    div {
+                        class: "form-group",
+                        label { "Group" }
+                        select {
+                            class: "form-select",
+                            onchange: move |e| option_group.set(e.value()),
+                            for group in product.options.iter() {
+                                option { value: "{group.id}", selected: option_group() == group.id, "{group.name}" }
+                            }
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Option" }
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            value: "{option_name}",
+                            oninput: move |e| option_name.set(e.value()),
+                            placeholder: "e.g. Large, Oat milk",
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Price change" }
+                        input {
+                            r#type: "number",
+                            class: "form-input",
+                            value: "{option_delta}",
+                            oninput: move |e| option_delta.set(e.value()),
+                            placeholder: "0.00",
+                            step: "0.01",
+                        }
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| {
+                            let name = option_name().trim().to_string();
+                            let delta = if option_delta().trim().is_empty() {
+                                Some(Money::zero(Currency::default()))
+                            } else {
+                                Money::parse(&option_delta(), Currency::default())
+                            };
+                            let (false, Some(delta)) = (name.is_empty(), delta) else {
+                                error.set(Some("Enter an option name and a price change".to_string()));
+                                return;
+                            };
+                            let mut updated = add_option_product.clone();
+                            let Some(group) = updated.options.iter_mut().find(|g| g.id == option_group()) else {
+                                error.set(Some("Choose a group".to_string()));
+                                return;
+                            };
+                            group.options.push(ProductOption::new(name, delta));
+                            on_save.call(updated);
+                            option_name.set(String::new());
+                            option_delta.set(String::new());
+                            error.set(None);
+                        },
+                        "Add Option"
+                    }
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "form-error", "{message}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn StockAdjustPanel(
+    product: Product,
+    movements: Vec<StockMovement>,
+    on_apply: EventHandler<StockAdjustment>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut kind = use_signal(|| MovementKind::Receiving);
+    let mut quantity = use_signal(String::new);
+    let mut note = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    let product_id = product.id.clone();
+
+    rsx! {
+        div {
+            class: "stock-adjust-panel",
+
+            div {
+                class: "manager-header",
+                h3 { "Stock: {product.name} ({product.stock} on hand)" }
+                button {
+                    class: "btn-secondary",
+                    onclick: move |_| on_close.call(()),
+                    "Close"
+                }
+            }
+
+            div {
+                class: "stock-adjust-form",
+
+                div {
+                    class: "form-group",
+                    label { "Movement" }
+                    select {
+                        class: "form-select",
+                        onchange: move |e| {
+                            let k = match e.value().as_str() {
+                                "Adjustment" => MovementKind::Adjustment,
+                                "Waste" => MovementKind::Waste,
+                                _ => MovementKind::Receiving,
+                            };
+                            kind.set(k);
+                        },
+
+                        option { value: "Receiving", "Receiving (+)" }
+                        option { value: "Waste", "Waste (−)" }
+                        option { value: "Adjustment", "Count correction (±)" }
+                    }
+                }
+
+                div {
+                    class: "form-group",
+                    label { "Quantity" }
+                    input {
+                        r#type: "number",
+                        class: "form-input",
+                        value: "{quantity}",
+                        oninput: move |e| quantity.set(e.value()),
+                        placeholder: if kind() == MovementKind::Adjustment { "e.g. -2" } else { "0" },
+                        step: "1",
+                    }
+                }
+
+                div {
+                    class: "form-group",
+                    label { "Note" }
+                    input {
+                        r#type: "text",
+                        class: "form-input",
+                        value: "{note}",
+                        oninput: move |e| note.set(e.value()),
+                        placeholder: "Optional",
+                    }
+                }
+
+                button {
+                    class: "btn-primary",
+                    onclick: move |_| {
+                        let Ok(amount) = quantity().trim().parse::<i64>() else {
+                            error.set(Some("Enter a whole number".to_string()));
+                            return;
+                        };
+                        let signed = match kind() {
+                            MovementKind::Adjustment => amount,
+                            MovementKind::Waste => -amount.abs(),
+                            _ => amount.abs(),
+                        };
+                        if signed == 0 {
+                            error.set(Some("Quantity cannot be zero".to_string()));
+                            return;
+                        }
+
+                        let text = note().trim().to_string();
+                        on_apply.call(StockAdjustment {
+                            product_id: product_id.clone(),
+                            kind: kind(),
+                            quantity: signed,
+                            note: (!text.is_empty()).then_some(text),
+                        });
+                        quantity.set(String::new());
+                        note.set(String::new());
+                        error.set(None);
+                    },
+                    "Apply"
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "form-error", "{message}" }
+            }
+
+            if movements.is_empty() {
+                p { class: "hint", "No stock movements yet" }
+            } else {
+                table {
+                    class: "ledger-table",
+                    thead {
+                        tr {
+                            th { "When" }
+                            th { "Movement" }
+                            th { "Change" }
+                            th { "On hand" }
+                            th { "Reference" }
+                        }
+                    }
+                    tbody {
+                        for movement in movements.iter() {
+                            tr {
+                                key: "{movement.id}",
+                                td { {movement.created_at.format("%b %d %I:%M %p").to_string()} }
+                                td { "{movement.kind.as_str()}" }
+                                td { "{movement.quantity:+}" }
+                                td { "{movement.on_hand}" }
+                                td {
+                                    {movement
+                                        .order_id
+                                        .as_ref()
+                                        .map(|id| format!("Order #{}", &id[..8]))
+                                        .or_else(|| movement.note.clone())
+                                        .unwrap_or_default()}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Every field of one product. The stock field sets the on-hand count; the
+// difference is posted as a count correction.
+#[component]
+fn ProductEditPanel(
+    product: Product,
+    catalog: Vec<Product>,
+    // (id, indented name) in tree order.
+    categories: Vec<(String, String)>,
+    // (id, name)
+    tax_classes: Vec<(String, String)>,
+    on_save: EventHandler<Product>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let initial = ProductForm::editing(&product);
+    let mut form = use_signal(move || initial);
+    let mut error = use_signal(|| None::<String>);
+    let save_product = product.clone();
+
+    rsx! {
+        div {
+            class: "stock-adjust-panel",
+
+            div {
+                class: "manager-header",
+                h3 { "Edit: {product.name}" }
+                button {
+                    class: "btn-secondary",
+                    onclick: move |_| on_close.call(()),
+                    "Close"
+                }
+            }
+
+            div {
+                class: "stock-adjust-form",
+                div {
+                    class: "form-group",
+                    label { "Name" }
+                    input {
+                        r#type: "text",
+                        class: "form-input",
+                        value: "{form.read().name}",
+                        oninput: move |e| form.write().name = e.value(),
+                    }
+                }
+                div {
+                    class: "form-group",
+                    label { "Price" }
+                    input {
+                        r#type: "number",
+                        class: "form-input",
+                        value: "{form.read().price}",
+                        oninput: move |e| form.write().price = e.value(),
+                        step: "0.01",
+                        min: "0",
+                    }
+                }
+                div {
+                    class: "form-group",
+                    label { "Category" }
+                    select {
+                        class: "form-select",
+                        onchange: move |e| form.write().category = e.value(),
+                        for (id, label) in categories.iter() {
+                            option { value: "{id}", selected: form.read().category == *id, "{label}" }
+                        }
+                    }
+                }
+                div {
+                    class: "form-group",
+                    label { "Tax Class" }
+                    select {
+                        class: "form-select",
+                        onchange: move |e| form.write().tax_class = e.value(),
+                        option { value: "", "Category default" }
+                        for (id, name) in tax_classes.iter() {
+                            option { value: "{id}", selected: form.read().tax_class == *id, "{name}" }
+                        }
+                    }
+                }
+                div {
+                    class: "form-group",
+                    label { "Stock on hand" }
+                    input {
+                        r#type: "number",
+                        class: "form-input",
+                        value: "{form.read().stock}",
+                        oninput: move |e| form.write().stock = e.value(),
+                        step: "1",
+                    }
+                }
+            }
+
+            div {
+                class: "stock-adjust-form",
+                div {
+                    class: "form-group",
+                    label { "SKU" }
+                    input {
+                        r#type: "text",
+                        class: "form-input",
+                        value: "{form.read().sku}",
+                        oninput: move |e| form.write().sku = e.value(),
+                        placeholder: "Optional",
+                    }
+                }
+                div {
+                    class: "form-group",
+                    label { "Barcodes" }
+                    input {
+                        r#type: "text",
+                        class: "form-input",
+                        value: "{form.read().barcodes}",
+                        oninput: move |e| form.write().barcodes = e.value(),
+                        placeholder: "Comma separated",
+                    }
+                }
+                div {
+                    class: "form-group",
+                    label { "Image URL" }
+                    input {
+                        r#type: "text",
+                        class: "form-input",
+                        value: "{form.read().image_url}",
+                        oninput: move |e| form.write().image_url = e.value(),
+                        placeholder: "Optional",
+                    }
+                }
+                button {
+                    class: "btn-primary",
+                    onclick: move |_| {
+                        let edited = match form.read().build(&save_product) {
+                            Ok(edited) => edited,
+                            Err(message) => {
+                                error.set(Some(message));
+                                return;
+                            }
+                        };
+                        if let Err(e) = edited.validate(&catalog) {
+                            error.set(Some(e.to_string()));
+                            return;
+                        }
+                        error.set(None);
+                        on_save.call(edited);
+                    },
+                    "Save Changes"
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "form-error", "{message}" }
+            }
+
+            if product.price_history.is_empty() {
+                p { class: "hint", "Price never changed" }
+            } else {
+                table {
+                    class: "ledger-table",
+                    thead {
+                        tr {
+                            th { "When" }
+                            th { "From" }
+                            th { "To" }
+                            th { "By" }
+                        }
+                    }
+                    tbody {
+                        for change in product.price_history.iter().rev() {
+                            tr {
+                                td { {change.at.format("%b %d %I:%M %p").to_string()} }
+                                td { "{change.from}" }
+                                td { "{change.to}" }
+                                td { {change.by.as_deref().unwrap_or("—")} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Text fields of the edit form.
+#[derive(Debug, Clone, PartialEq)]
+struct ProductForm {
+    name: String,
+    price: String,
+    category: String,
+    // Empty for the category's class.
+    tax_class: String,
+    stock: String,
+    sku: String,
+    barcodes: String,
+    image_url: String,
+}
+
+impl ProductForm {
+    fn editing(product: &Product) -> Self {
+        Self {
+            name: product.name.clone(),
+            price: product.price.plain_string(),
+            category: product.category.clone(),
+            tax_class: product.tax_class.clone().unwrap_or_default(),
+            stock: product.stock.to_string(),
+            sku: product.sku.clone().unwrap_or_default(),
+            barcodes: product.barcodes.join(", "),
+            image_url: product.image_url.clone().unwrap_or_default(),
+        }
+    }
+
+    // `product` with the form's values; validation against the catalog is
+    // left to `Product::validate`.
+    fn build(&self, product: &Product) -> Result<Product, String> {
+        let price = Money::parse(&self.price, product.price.currency())
+            .ok_or_else(|| "Enter a price".to_string())?;
+        let stock = self
+            .stock
+            .trim()
+            .parse()
+            .map_err(|_| "Stock must be a whole number".to_string())?;
+        let optional = |text: &str| Some(text.trim().to_string()).filter(|t| !t.is_empty());
+        Ok(Product {
+            name: self.name.trim().to_string(),
+            price,
+            category: self.category.clone(),
+            tax_class: optional(&self.tax_class),
+            stock,
+            sku: optional(&self.sku),
+            barcodes: self
+                .barcodes
+                .split(',')
+                .map(str::trim)
+                .filter(|code| !code.is_empty())
+                .map(str::to_string)
+                .collect(),
+            image_url: optional(&self.image_url),
+            ..product.clone()
+        })
+    }
+}
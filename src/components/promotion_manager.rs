@@ -0,0 +1,501 @@
+use chrono::{NaiveTime, Weekday};
+use dioxus::prelude::*;
+use crate::models::{
+    category_tree, normalize_code, Category, Currency, DiscountKind, Money, Product, PromoTarget, Promotion, PromotionRule, QuantityTier,
+    Rate, Schedule,
+};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[component]
+pub fn PromotionManager(
+    promotions: Signal<Vec<Promotion>>,
+    products: Signal<Vec<Product>>,
+    categories: Signal<Vec<Category>>,
+    on_save: EventHandler<Promotion>,
+    on_delete: EventHandler<String>,
+) -> Element {
+    let mut show_add_form = use_signal(|| false);
+    let mut form = use_signal(PromotionForm::default);
+    let mut error = use_signal(|| None::<String>);
+
+    let list = promotions.read().clone();
+    let catalog: Vec<Product> = products.read().iter().filter(|p| !p.archived).cloned().collect();
+    let category_list = categories.read().clone();
+    // (id, indented name) in display order.
+    let category_choices: Vec<(String, String)> = category_tree(&category_list)
+        .into_iter()
+        .map(|(depth, c)| (c.id.clone(), format!("{}{}", "— ".repeat(depth), c.name)))
+        .collect();
+    let kind = form.read().kind;
+
+    rsx! {
+        div {
+            class: "product-manager-container",
+
+            div {
+                class: "manager-header",
+                h2 { "Promotions" }
+                button {
+                    class: "btn-primary",
+                    onclick: move |_| show_add_form.set(!show_add_form()),
+                    if show_add_form() { "Cancel" } else { "+ Add Promotion" }
+                }
+            }
+
+            if let Some(message) = error() {
+                p { class: "lock-error", "{message}" }
+            }
+
+            if show_add_form() {
+                div {
+                    class: "add-product-form promotion-form",
+                    div {
+                        class: "form-group",
+                        label { "Name" }
+                        input {
+                            r#type: "text",
+                            class: "form-input",
+                            value: "{form.read().name}",
+                            oninput: move |e| form.write().name = e.value(),
+                        }
+                    }
+                    div {
+                        class: "form-group",
+                        label { "Type" }
+                        select {
+                            class: "form-select",
+                            onchange: move |e| {
+                                if let Some(kind) = RuleKind::from_label(&e.value()) {
+                                    form.write().kind = kind;
+                                }
+                            },
+                            for k in RuleKind::ALL {
+                                option { value: "{k.as_str()}", selected: k == kind, "{k.as_str()}" }
+                            }
+                        }
+                    }
+
+                    if matches!(kind, RuleKind::PercentOff | RuleKind::Bogo | RuleKind::BuyXGetY | RuleKind::QuantityBreak) {
+                        div {
+                            class: "form-group",
+                            label { "Applies to" }
+                            select {
+                                class: "form-select",
+                                onchange: move |e| form.write().target = e.value(),
+                                option { value: "", "All items" }
+                                for (id, name) in category_choices.iter() {
+                                    option {
+                                        value: "category:{id}",
+                                        selected: form.read().target == format!("category:{id}"),
+                                        "Category: {name}"
+                                    }
+                                }
+                                for product in catalog.iter() {
+                                    option {
+                                        value: "product:{product.id}",
+                                        selected: form.read().target == format!("product:{}", product.id),
+                                        "{product.name}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    if kind == RuleKind::MixAndMatch {
+                        div {
+                            class: "form-group",
+                            label { "Category" }
+                            select {
+                                class: "form-select",
+                                onchange: move |e| form.write().category = e.value(),
+                                option { value: "", "Choose…" }
+                                for (id, name) in category_choices.iter() {
+                                    option { value: "{id}", selected: form.read().category == *id, "{name}" }
+                                }
+                            }
+                        }
+                    }
+
+                    match kind {
+                        RuleKind::PercentOff => rsx! {
+                            PromotionField { label: "Percent off", value: form.read().percent.clone(), on_input: move |v| form.write().percent = v }
+                        },
+                        RuleKind::Bogo => rsx! {},
+                        RuleKind::BuyXGetY => rsx! {
+                            PromotionField { label: "Buy", value: form.read().buy.clone(), on_input: move |v| form.write().buy = v }
+                            PromotionField { label: "Get", value: form.read().get.clone(), on_input: move |v| form.write().get = v }
+                            PromotionField { label: "Percent off those", value: form.read().percent.clone(), on_input: move |v| form.write().percent = v }
+                        },
+                        RuleKind::MixAndMatch => rsx! {
+                            PromotionField { label: "Quantity", value: form.read().buy.clone(), on_input: move |v| form.write().buy = v }
+                            PromotionField { label: "For price", value: form.read().amount.clone(), on_input: move |v| form.write().amount = v }
+                        },
+                        RuleKind::QuantityBreak => rsx! {
+                            PromotionField {
+                                label: "Tiers (quantity:percent, comma separated)",
+                                value: form.read().tiers.clone(),
+                                on_input: move |v| form.write().tiers = v,
+                            }
+                        },
+                        RuleKind::OrderPercent => rsx! {
+                            PromotionField { label: "Percent off", value: form.read().percent.clone(), on_input: move |v| form.write().percent = v }
+                            PromotionField { label: "Minimum subtotal", value: form.read().min_subtotal.clone(), on_input: move |v| form.write().min_subtotal = v }
+                        },
+                        RuleKind::OrderAmount => rsx! {
+                            PromotionField { label: "Amount off", value: form.read().amount.clone(), on_input: move |v| form.write().amount = v }
+                            PromotionField { label: "Minimum subtotal", value: form.read().min_subtotal.clone(), on_input: move |v| form.write().min_subtotal = v }
+                        },
+                    }
+
+                    PromotionField { label: "Priority (lower first)", value: form.read().priority.clone(), on_input: move |v| form.write().priority = v }
+                    PromotionField { label: "Coupon code (optional)", value: form.read().coupon.clone(), on_input: move |v| form.write().coupon = v }
+                    div {
+                        class: "form-group",
+                        label {
+                            input {
+                                r#type: "checkbox",
+                                checked: form.read().stackable,
+                                onchange: move |e| form.write().stackable = e.checked(),
+                            }
+                            " Stacks with other discounts"
+                        }
+                    }
+
+                    div {
+                        class: "form-group",
+                        label { "Happy hour (optional)" }
+                        div {
+                            class: "promotion-days",
+                            for day in WEEKDAYS {
+                                label {
+                                    key: "{day}",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: form.read().days.contains(&day),
+                                        onchange: move |e| {
+                                            let mut f = form.write();
+                                            f.days.retain(|d| *d != day);
+                                            if e.checked() {
+                                                f.days.push(day);
+                                            }
+                                        },
+                                    }
+                                    " {day}"
+                                }
+                            }
+                        }
+                        div {
+                            class: "promotion-times",
+                            input {
+                                r#type: "time",
+                                class: "form-input",
+                                value: "{form.read().start}",
+                                oninput: move |e| form.write().start = e.value(),
+                            }
+                            span { "to" }
+                            input {
+                                r#type: "time",
+                                class: "form-input",
+                                value: "{form.read().end}",
+                                oninput: move |e| form.write().end = e.value(),
+                            }
+                        }
+                    }
+
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| {
+                            let built = form.read().build();
+                            match built {
+                                Ok(promotion) => {
+                                    on_save.call(promotion);
+                                    form.set(PromotionForm::default());
+                                    error.set(None);
+                                    show_add_form.set(false);
+                                }
+                                Err(e) => error.set(Some(e)),
+                            }
+                        },
+                        "Add Promotion"
+                    }
+                }
+            }
+
+            div {
+                class: "products-table",
+                table {
+                    thead {
+                        tr {
+                            th { "Name" }
+                            th { "Rule" }
+                            th { "Priority" }
+                            th { "Coupon" }
+                            th { "When" }
+                            th { "Status" }
+                            th { "Actions" }
+                        }
+                    }
+                    tbody {
+                        if list.is_empty() {
+                            tr {
+                                td { colspan: "7", class: "empty-row", "No promotions yet" }
+                            }
+                        }
+                        for promotion in list.iter() {
+                            tr {
+                                key: "{promotion.id}",
+                                td { "{promotion.name}" }
+                                td {
+                                    {promotion.rule.summary(&category_list)}
+                                    if promotion.stackable { " (stacks)" }
+                                }
+                                td { "{promotion.priority}" }
+                                td { {promotion.coupon_code.clone().unwrap_or_else(|| "—".to_string())} }
+                                td { {promotion.schedule.as_ref().map_or_else(|| "Always".to_string(), schedule_label)} }
+                                td { if promotion.active { "Active" } else { "Paused" } }
+                                td {
+                                    button {
+                                        class: "btn-secondary-small",
+                                        onclick: {
+                                            let promotion = promotion.clone();
+                                            move |_| on_save.call(Promotion { active: !promotion.active, ..promotion.clone() })
+                                        },
+                                        if promotion.active { "Pause" } else { "Resume" }
+                                    }
+                                    button {
+                                        class: "btn-danger-small",
+                                        onclick: {
+                                            let id = promotion.id.clone();
+                                            move |_| on_delete.call(id.clone())
+                                        },
+                                        "Delete"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PromotionField(label: String, value: String, on_input: EventHandler<String>) -> Element {
+    rsx! {
+        div {
+            class: "form-group",
+            label { "{label}" }
+            input {
+                r#type: "text",
+                class: "form-input",
+                value: "{value}",
+                oninput: move |e| on_input.call(e.value()),
+            }
+        }
+    }
+}
+
+fn schedule_label(schedule: &Schedule) -> String {
+    let days = if schedule.days.is_empty() {
+        "Daily".to_string()
+    } else {
+        schedule.days.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")
+    };
+    format!("{days} {}–{}", schedule.start.format("%H:%M"), schedule.end.format("%H:%M"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RuleKind {
+    PercentOff,
+    Bogo,
+    BuyXGetY,
+    MixAndMatch,
+    QuantityBreak,
+    OrderPercent,
+    OrderAmount,
+}
+
+impl RuleKind {
+    const ALL: [RuleKind; 7] = [
+        RuleKind::PercentOff,
+        RuleKind::Bogo,
+        RuleKind::BuyXGetY,
+        RuleKind::MixAndMatch,
+        RuleKind::QuantityBreak,
+        RuleKind::OrderPercent,
+        RuleKind::OrderAmount,
+    ];
+
+    fn as_str(&self) -> &str {
+        match self {
+            RuleKind::PercentOff => "Percent off items",
+            RuleKind::Bogo => "Buy one get one free",
+            RuleKind::BuyXGetY => "Buy X get Y",
+            RuleKind::MixAndMatch => "Mix and match",
+            RuleKind::QuantityBreak => "Quantity break",
+            RuleKind::OrderPercent => "Percent off order",
+            RuleKind::OrderAmount => "Amount off order",
+        }
+    }
+
+    fn from_label(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == value)
+    }
+}
+
+// What has been typed into the add form so far.
+#[derive(Debug, Clone, PartialEq)]
+struct PromotionForm {
+    name: String,
+    kind: RuleKind,
+    // "", "category:<id>" or "product:<id>".
+    target: String,
+    category: String,
+    percent: String,
+    buy: String,
+    get: String,
+    amount: String,
+    tiers: String,
+    min_subtotal: String,
+    priority: String,
+    stackable: bool,
+    coupon: String,
+    days: Vec<Weekday>,
+    start: String,
+    end: String,
+}
+
+impl Default for PromotionForm {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            kind: RuleKind::PercentOff,
+            target: String::new(),
+            category: String::new(),
+            percent: String::new(),
+            buy: String::new(),
+            get: String::new(),
+            amount: String::new(),
+            tiers: String::new(),
+            min_subtotal: String::new(),
+            priority: "10".to_string(),
+            stackable: false,
+            coupon: String::new(),
+            days: Vec::new(),
+            start: String::new(),
+            end: String::new(),
+        }
+    }
+}
+
+impl PromotionForm {
+    fn build(&self) -> Result<Promotion, String> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err("Name is required".to_string());
+        }
+        let rule = self.rule()?;
+        let mut promotion = Promotion::new(name, rule);
+        promotion.priority = self.priority.trim().parse().map_err(|_| "Priority must be a whole number".to_string())?;
+        promotion.stackable = self.stackable;
+        let coupon = normalize_code(&self.coupon);
+        promotion.coupon_code = (!coupon.is_empty()).then_some(coupon);
+        promotion.schedule = self.schedule()?;
+        Ok(promotion)
+    }
+
+    fn rule(&self) -> Result<PromotionRule, String> {
+        let percent = || {
+            Rate::parse_percent(&self.percent)
+                .filter(|r| r.0 > 0 && r.0 <= 1_000_000)
+                .ok_or_else(|| "Percent must be more than 0 and at most 100".to_string())
+        };
+        let count = |value: &str, what: &str| {
+            value
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| format!("{what} must be a whole number above zero"))
+        };
+        let amount = |value: &str| {
+            Money::parse(value, Currency::default())
+                .filter(|m| m.minor() > 0)
+                .ok_or_else(|| "Enter an amount above zero".to_string())
+        };
+        let target = match self.target.split_once(':') {
+            Some(("category", id)) => PromoTarget::Category(id.to_string()),
+            Some(("product", id)) => PromoTarget::Product(id.to_string()),
+            _ => PromoTarget::All,
+        };
+        let min_subtotal = if self.min_subtotal.trim().is_empty() {
+            None
+        } else {
+            Some(amount(&self.min_subtotal)?)
+        };
+
+        Ok(match self.kind {
+            RuleKind::PercentOff => PromotionRule::PercentOff { target, off: percent()? },
+            RuleKind::Bogo => PromotionRule::BuyXGetY { target, buy: 1, get: 1, off: Rate(1_000_000) },
+            RuleKind::BuyXGetY => PromotionRule::BuyXGetY {
+                target,
+                buy: count(&self.buy, "Buy")?,
+                get: count(&self.get, "Get")?,
+                off: percent()?,
+            },
+            RuleKind::MixAndMatch => {
+                if self.category.is_empty() {
+                    return Err("Choose a category".to_string());
+                }
+                PromotionRule::MixAndMatch {
+                    category: self.category.clone(),
+                    quantity: count(&self.buy, "Quantity")?,
+                    price: amount(&self.amount)?,
+                }
+            }
+            RuleKind::QuantityBreak => {
+                let tiers = self
+                    .tiers
+                    .split(',')
+                    .map(|tier| {
+                        let (min, off) = tier.split_once(':')?;
+                        Some(QuantityTier {
+                            min_quantity: min.trim().parse().ok().filter(|n| *n > 0)?,
+                            off: Rate::parse_percent(off).filter(|r| r.0 > 0 && r.0 <= 1_000_000)?,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| "Tiers look like 5:10, 10:15".to_string())?;
+                PromotionRule::QuantityBreak { target, tiers }
+            }
+            RuleKind::OrderPercent => PromotionRule::OrderOff { kind: DiscountKind::Percent(percent()?), min_subtotal },
+            RuleKind::OrderAmount => PromotionRule::OrderOff {
+                kind: DiscountKind::Amount(amount(&self.amount)?),
+                min_subtotal,
+            },
+        })
+    }
+
+    fn schedule(&self) -> Result<Option<Schedule>, String> {
+        if self.start.is_empty() && self.end.is_empty() {
+            return Ok(None);
+        }
+        let time = |value: &str| NaiveTime::parse_from_str(value, "%H:%M").ok();
+        match (time(&self.start), time(&self.end)) {
+            (Some(start), Some(end)) if start != end => {
+                let days = WEEKDAYS.into_iter().filter(|d| self.days.contains(d)).collect();
+                Ok(Some(Schedule { days, start, end }))
+            }
+            _ => Err("Happy hour needs a start and a different end time".to_string()),
+        }
+    }
+}
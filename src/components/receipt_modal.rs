@@ -0,0 +1,64 @@
+use dioxus::prelude::*;
+use crate::receipts::{to_html, Receipt};
+
+#[component]
+pub fn ReceiptModal(
+    receipt: Receipt,
+    status: Option<String>,
+    on_print: EventHandler<()>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let html = to_html(&receipt);
+
+    rsx! {
+        div {
+            class: "modal-overlay",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "modal-content receipt-modal",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "Receipt" }
+                    button {
+                        class: "close-btn",
+                        onclick: move |_| on_close.call(()),
+                        "✕"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+                    div { class: "receipt-paper", dangerous_inner_html: "{html}" }
+                    if let Some(message) = status {
+                        p { class: "hint", "{message}" }
+                    }
+                }
+
+                div {
+                    class: "modal-footer",
+                    button {
+                        class: "btn-secondary",
+                        // Opens the system print dialog; styles.css limits the page to the receipt.
+                        onclick: move |_| {
+                            document::eval("window.print()");
+                        },
+                        "Print View"
+                    }
+                    button {
+                        class: "btn-secondary",
+                        onclick: move |_| on_print.call(()),
+                        "Reprint"
+                    }
+                    button {
+                        class: "btn-primary",
+                        onclick: move |_| on_close.call(()),
+                        "Done"
+                    }
+                }
+            }
+        }
+    }
+}
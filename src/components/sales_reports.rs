@@ -0,0 +1,127 @@
+use chrono::Utc;
+use dioxus::prelude::*;
+use crate::models::{Employee, Order, ReportKind, SalesReport};
+use crate::reports::{title, to_text};
+
+#[component]
+pub fn SalesReports(
+    orders: Signal<Vec<Order>>,
+    employees: Signal<Vec<Employee>>,
+    z_reports: Signal<Vec<SalesReport>>,
+    width: usize,
+    status: Option<String>,
+    on_close_day: EventHandler<()>,
+    on_export: EventHandler<SalesReport>,
+) -> Element {
+    // `None` shows the live X report; otherwise the index of a Z report.
+    let mut selected = use_signal(|| None::<usize>);
+    let mut confirming = use_signal(|| false);
+
+    let closed = z_reports.read();
+    let x_report = SalesReport::build(
+        ReportKind::X,
+        None,
+        &orders.read(),
+        &employees.read(),
+        closed.last().map(|z| z.period_end),
+        Utc::now(),
+    );
+    let report = selected()
+        .and_then(|i| closed.get(i).cloned())
+        .unwrap_or_else(|| x_report.clone());
+    let text = to_text(&report, width);
+
+    rsx! {
+        div {
+            class: "order-history-container reports-container",
+
+            div {
+                class: "history-header",
+                h2 { "Reports" }
+                div {
+                    class: "history-stats",
+                    div {
+                        class: "stat",
+                        span { class: "stat-label", "Since last Z:" }
+                        span { class: "stat-value", "{x_report.sales_count} sales" }
+                    }
+                    div {
+                        class: "stat",
+                        span { class: "stat-label", "Net Sales:" }
+                        span { class: "stat-value", "{x_report.net_sales}" }
+                    }
+                    div {
+                        class: "stat",
+                        span { class: "stat-label", "Tips:" }
+                        span { class: "stat-value", "{x_report.tip_total}" }
+                    }
+                }
+            }
+
+            div {
+                class: "reports-layout",
+
+                div {
+                    class: "reports-list",
+                    button {
+                        class: if selected().is_none() { "report-entry active" } else { "report-entry" },
+                        onclick: move |_| selected.set(None),
+                        span { "X Report" }
+                        span { class: "report-entry-meta", "Current period" }
+                    }
+                    for (i, z) in closed.iter().enumerate().rev() {
+                        button {
+                            key: "{i}",
+                            class: if selected() == Some(i) { "report-entry active" } else { "report-entry" },
+                            onclick: move |_| selected.set(Some(i)),
+                            span { {title(z)} }
+                            span { class: "report-entry-meta", {z.period_end.format("%b %d, %Y %I:%M %p").to_string()} }
+                        }
+                    }
+                }
+
+                div {
+                    class: "report-detail",
+                    pre { class: "receipt-paper report-paper", "{text}" }
+
+                    if let Some(message) = status {
+                        p { class: "hint", "{message}" }
+                    }
+
+                    div {
+                        class: "report-actions",
+                        button {
+                            class: "btn-secondary",
+                            onclick: move |_| on_export.call(report.clone()),
+                            "Export Text & CSV"
+                        }
+                        if selected().is_none() {
+                            if confirming() {
+                                span { class: "report-confirm", "Close the day? This can't be undone." }
+                                button {
+                                    class: "btn-secondary",
+                                    onclick: move |_| confirming.set(false),
+                                    "Keep Open"
+                                }
+                                button {
+                                    class: "btn-danger-small",
+                                    onclick: move |_| {
+                                        confirming.set(false);
+                                        on_close_day.call(());
+                                    },
+                                    "Close Day"
+                                }
+                            } else {
+                                button {
+                                    class: "btn-primary",
+                                    onclick: move |_| confirming.set(true),
+                                    "Close Day (Z)"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
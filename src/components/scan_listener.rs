@@ -0,0 +1,49 @@
+use dioxus::prelude::*;
+
+// Listens for a barcode scanner in keyboard-wedge mode: it types the code far
+// faster than a person and ends with Enter. Keys typed into a form field are
+// left alone. The handler lives on `window` so a remount replaces it.
+const LISTEN: &str = r#"
+if (window.__trezzaScan) document.removeEventListener('keydown', window.__trezzaScan, true);
+let buffer = '';
+let last = 0;
+window.__trezzaScan = (e) => {
+    const t = e.target;
+    if (t && (t.tagName === 'INPUT' || t.tagName === 'TEXTAREA' || t.tagName === 'SELECT' || t.isContentEditable)) return;
+    const now = performance.now();
+    // More than 50 ms between keys is a person typing.
+    if (now - last > 50) buffer = '';
+    last = now;
+    if (e.key === 'Enter') {
+        if (buffer.length >= 4) {
+            e.preventDefault();
+            dioxus.send(buffer);
+        }
+        buffer = '';
+    } else if (e.key.length === 1) {
+        buffer += e.key;
+    }
+};
+document.addEventListener('keydown', window.__trezzaScan, true);
+"#;
+
+const STOP: &str = r#"
+if (window.__trezzaScan) document.removeEventListener('keydown', window.__trezzaScan, true);
+window.__trezzaScan = null;
+"#;
+
+// Renders nothing; calls `on_scan` with each code while mounted.
+#[component]
+pub fn ScanListener(on_scan: EventHandler<String>) -> Element {
+    use_future(move || async move {
+        let mut listener = document::eval(LISTEN);
+        while let Ok(code) = listener.recv::<String>().await {
+            on_scan.call(code);
+        }
+    });
+    use_drop(|| {
+        document::eval(STOP);
+    });
+
+    rsx! {}
+}
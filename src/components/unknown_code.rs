@@ -0,0 +1,144 @@
+use dioxus::prelude::*;
+use crate::models::{category_tree, Category, Currency, EmbeddedKind, Money, Product, Scan};
+
+// Shown when a scanned code matches nothing. Someone allowed to manage
+// products can create one with the code right away.
+#[component]
+pub fn UnknownCodeModal(
+    scan: Scan,
+    products: Signal<Vec<Product>>,
+    categories: Signal<Vec<Category>>,
+    can_create: bool,
+    on_create: EventHandler<Product>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut name = use_signal(String::new);
+    let mut price = use_signal(String::new);
+    let mut category = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    // A label is found by its item code, so that is what the product gets.
+    let (code, per_kg) = match &scan {
+        Scan::Code(code) => (code.clone(), false),
+        Scan::Label(label) => (label.item_code.clone(), label.kind == EmbeddedKind::Weight),
+    };
+    let category_list = categories.read();
+    let category_options: Vec<(String, String)> = category_tree(&category_list)
+        .into_iter()
+        .map(|(depth, c)| (c.id.clone(), format!("{}{}", "— ".repeat(depth), c.name)))
+        .collect();
+    let chosen_category = Some(category())
+        .filter(|id| category_options.iter().any(|(c, _)| c == id))
+        .or_else(|| category_options.first().map(|(id, _)| id.clone()));
+    let create_code = code.clone();
+
+    rsx! {
+        div {
+            class: "modal-overlay",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "modal-content unknown-code",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "Not Found" }
+                    button {
+                        class: "close-btn",
+                        onclick: move |_| on_close.call(()),
+                        "✕"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+                    p { "No product has the code " strong { "{code}" } "." }
+
+                    if can_create {
+                        div {
+                            class: "form-group",
+                            label { "Product Name" }
+                            input {
+                                r#type: "text",
+                                class: "form-input",
+                                value: "{name}",
+                                oninput: move |e| name.set(e.value()),
+                                autofocus: true,
+                            }
+                        }
+                        div {
+                            class: "form-group",
+                            label { if per_kg { "Price per kg" } else { "Price" } }
+                            input {
+                                r#type: "number",
+                                class: "form-input",
+                                value: "{price}",
+                                oninput: move |e| price.set(e.value()),
+                                placeholder: "0.00",
+                                step: "0.01",
+                            }
+                        }
+                        div {
+                            class: "form-group",
+                            label { "Category" }
+                            select {
+                                class: "form-select",
+                                onchange: move |e| category.set(e.value()),
+                                for (id, label) in category_options.iter() {
+                                    option {
+                                        value: "{id}",
+                                        selected: chosen_category.as_deref() == Some(id.as_str()),
+                                        "{label}"
+                                    }
+                                }
+                            }
+                        }
+                        if let Some(message) = error() {
+                            p { class: "form-error", "{message}" }
+                        }
+                    } else {
+                        p { class: "hint", "Ask a supervisor to add it on the Products screen." }
+                    }
+                }
+
+                div {
+                    class: "modal-footer",
+                    button {
+                        class: "btn-secondary",
+                        onclick: move |_| on_close.call(()),
+                        if can_create { "Cancel" } else { "OK" }
+                    }
+                    if can_create {
+                        button {
+                            class: "btn-primary",
+                            onclick: move |_| {
+                                let Some(category) = chosen_category.clone() else {
+                                    error.set(Some("Add a category first".to_string()));
+                                    return;
+                                };
+                                let Some(price) = Money::parse(&price(), Currency::default()) else {
+                                    error.set(Some("Enter a price".to_string()));
+                                    return;
+                                };
+                                let mut product = Product::new(name().trim().to_string(), price, category);
+                                // Numeric codes are barcodes; anything else is the store's SKU.
+                                if create_code.bytes().all(|b| b.is_ascii_digit()) {
+                                    product.barcodes.push(create_code.clone());
+                                } else {
+                                    product.sku = Some(create_code.clone());
+                                }
+                                if let Err(e) = product.validate(&products.read()) {
+                                    error.set(Some(e.to_string()));
+                                    return;
+                                }
+                                on_create.call(product);
+                            },
+                            "Create & Add"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
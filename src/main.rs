@@ -1,37 +1,801 @@
 mod models;
 mod components;
+mod storage;
+mod payments;
+mod receipts;
+mod reports;
+mod catalog;
 
 use dioxus::prelude::*;
-use models::{Product, ProductCategory, Order, Payment};
-use components::{ProductGrid, Cart, PaymentModal, OrderHistory, ProductManager};
+use models::{AuditAction, AuditEntry, AuditEvent, Category, ChosenOption, CardEntry, Currency, Customer, DenominationCount, Discount, DiscountTarget, DrawerSession, Employee, HeldOrder, Money, MovementKind, Permission, Product, Promotion, OptionGroup, Scan, Order, OrderStatus, Payment, PointsEntry, ReasonCode, ReportKind, SalesReport, TaxConfig, Settings, StockCheck, StockMovement};
+use components::{ProductGrid, Cart, PaymentModal, OrderHistory, ProductManager, ReceiptModal, RefundRequest, SalesReports, TipAdjustment, DrawerManager, DrawerEntryRequest, LockScreen, OverrideModal, EmployeeManager, AuditLog, PromotionManager, OptionPicker, CategoryManager, CatalogImport, CustomerManager, GiftCardLoad, GiftCards, HeldOrders, ScanListener, UnknownCodeModal, PriceOverride, StockAdjustment, VoidRequest};
+use catalog::{FileFormat, ImportPlan, RowOutcome};
+use storage::{JsonStore, Repository};
+use payments::PaymentProcessor;
+use std::sync::Arc;
+
+// Ticks once at start-up and every minute after, for clock-driven upkeep.
+const MINUTE_CLOCK: &str = "dioxus.send(true); setInterval(() => dioxus.send(true), 60000);";
 
 fn main() {
+    // `--mock-terminal [ADDR]` runs a simulated card terminal instead of the UI.
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("--mock-terminal") {
+        let addr = args.get(2).map_or(payments::DEFAULT_TERMINAL_ADDR, String::as_str);
+        if let Err(e) = payments::serve_mock_terminal(addr) {
+            eprintln!("mock terminal failed on {addr}: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    // `--verify-audit` checks the audit log's hash chain and exits.
+    if args.get(1).map(String::as_str) == Some("--verify-audit") {
+        std::process::exit(verify_audit());
+    }
     dioxus::launch(App);
 }
 
+fn verify_audit() -> i32 {
+    let dir = JsonStore::default_dir();
+    let entries = match JsonStore::open(&dir).and_then(|store| store.load_audit_log()) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("audit log unreadable in {}: {e}", dir.display());
+            return 2;
+        }
+    };
+    match models::verify_chain(&entries) {
+        Ok(()) => {
+            let head = entries.last().map_or(models::GENESIS_HASH, |e| e.hash.as_str());
+            println!("audit log intact: {} entries, last hash {head}", entries.len());
+            0
+        }
+        Err(e) => {
+            eprintln!("audit log tampered: {e}");
+            1
+        }
+    }
+}
+
 #[component]
 fn App() -> Element {
-    let mut products = use_signal(|| vec![
-        Product::new("Espresso".to_string(), 3.50, ProductCategory::Beverage),
-        Product::new("Cappuccino".to_string(), 4.50, ProductCategory::Beverage),
-        Product::new("Latte".to_string(), 5.00, ProductCategory::Beverage),
-        Product::new("Croissant".to_string(), 3.75, ProductCategory::Food),
-        Product::new("Bagel".to_string(), 2.50, ProductCategory::Food),
-        Product::new("Sandwich".to_string(), 8.50, ProductCategory::Food),
-        Product::new("Salad".to_string(), 9.75, ProductCategory::Food),
-        Product::new("Muffin".to_string(), 3.25, ProductCategory::Food),
-        Product::new("T-Shirt".to_string(), 24.99, ProductCategory::Retail),
-        Product::new("Mug".to_string(), 12.99, ProductCategory::Retail),
-        Product::new("Consultation".to_string(), 50.00, ProductCategory::Service),
-    ]);
-
-    let mut current_order = use_signal(Order::new);
-    let mut order_history = use_signal(|| Vec::<Order>::new());
-    let selected_category = use_signal(|| None);
+    let store = use_signal(|| {
+        let dir = JsonStore::default_dir();
+        JsonStore::open(&dir)
+            .unwrap_or_else(|e| panic!("cannot open data directory {}: {e}", dir.display()))
+    });
+
+    let mut products = use_signal(|| {
+        load_or(store.read().load_products(), seed_products)
+    });
+    let tax_config = use_signal(|| {
+        load_or(store.read().load_tax_config(), TaxConfig::default)
+    });
+    let mut categories = use_signal(|| {
+        // First run since categories became data: start from the four that
+        // used to be built in, with any tax classes they were mapped to.
+        load_or(store.read().load_categories(), || {
+            models::default_categories(&tax_config.peek().category_classes)
+        })
+    });
+    let mut settings = use_signal(|| {
+        load_or(store.read().load_settings(), Settings::default)
+    });
+    let mut promotions = use_signal(|| {
+        load_or(store.read().load_promotions(), Vec::new)
+    });
+    // Every order is priced with the promotions running when it started.
+    let new_order = move || {
+        let mut order = Order::new(tax_config.read().mode);
+        order.set_promotions(&promotions.read());
+        order
+    };
+    let mut current_order = use_signal(|| {
+        // A crash between recording a paid order and resetting the cart can
+        // leave the finished order behind; never reopen it.
+        let mut order = load_or(store.read().load_current_order(), new_order);
+        if order.status != OrderStatus::Pending {
+            return new_order();
+        }
+        order.set_promotions(&promotions.read());
+        order
+    });
+    let mut held_orders = use_signal(|| {
+        load_or(store.read().load_held_orders(), Vec::new)
+    });
+    let mut customers = use_signal(|| {
+        load_or(store.read().load_customers(), Vec::new)
+    });
+    let mut order_history = use_signal(|| {
+        load_or(store.read().load_order_history().map(Some), Vec::new)
+    });
+    let mut stock_ledger = use_signal(|| {
+        load_or(store.read().load_stock_movements().map(Some), Vec::new)
+    });
+    let mut points_ledger = use_signal(|| {
+        load_or(store.read().load_points_ledger().map(Some), Vec::new)
+    });
+    let mut gift_cards = use_signal(|| {
+        load_or(store.read().load_gift_cards(), Vec::new)
+    });
+    let mut card_ledger = use_signal(|| {
+        load_or(store.read().load_card_ledger().map(Some), Vec::new)
+    });
+    let mut z_reports = use_signal(|| {
+        load_or(store.read().load_reports().map(Some), Vec::new)
+    });
+    let mut drawer_sessions = use_signal(|| {
+        load_or(store.read().load_drawer_sessions().map(Some), Vec::new)
+    });
+    let mut employees = use_signal(|| {
+        load_or(store.read().load_employees(), Vec::new)
+    });
+    let mut audit_log = use_signal(|| {
+        load_or(store.read().load_audit_log().map(Some), Vec::new)
+    });
+    let processor = use_signal(|| -> Arc<dyn PaymentProcessor> {
+        payments::connect(settings.read().card_terminal.as_deref())
+    });
+
+    // Write-through: every change to the catalog or the open cart is
+    // persisted as soon as the signal settles.
+    use_effect(move || {
+        if let Err(e) = store.read().save_products(&products.read()) {
+            eprintln!("failed to save products: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_tax_config(&tax_config.read()) {
+            eprintln!("failed to save tax config: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_settings(&settings.read()) {
+            eprintln!("failed to save settings: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_employees(&employees.read()) {
+            eprintln!("failed to save employees: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_categories(&categories.read()) {
+            eprintln!("failed to save categories: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_promotions(&promotions.read()) {
+            eprintln!("failed to save promotions: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_current_order(&current_order.read()) {
+            eprintln!("failed to save current order: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_held_orders(&held_orders.read()) {
+            eprintln!("failed to save held orders: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_customers(&customers.read()) {
+            eprintln!("failed to save customers: {e}");
+        }
+    });
+    use_effect(move || {
+        if let Err(e) = store.read().save_gift_cards(&gift_cards.read()) {
+            eprintln!("failed to save gift cards: {e}");
+        }
+    });
+
+    let mut selected_category = use_signal(|| None::<String>);
     let mut show_payment_modal = use_signal(|| false);
-    let mut current_view = use_signal(|| View::POS);
+    let mut current_view = use_signal(|| View::Pos);
     let mut dark_mode = use_signal(|| true);
     let mut sidebar_open = use_signal(|| false);
+    let mut notice = use_signal(|| None::<String>);
+    // Product whose variants and modifiers are being chosen.
+    let mut choosing = use_signal(|| None::<Product>);
+    // Scan that matched no product.
+    let mut unknown_scan = use_signal(|| None::<Scan>);
+    // Order whose receipt is on screen, and the outcome of the last print.
+    let mut receipt_order = use_signal(|| None::<Order>);
+    let mut print_status = use_signal(|| None::<String>);
+    let mut report_status = use_signal(|| None::<String>);
+    let mut catalog_status = use_signal(|| None::<String>);
+    let mut drawer_status = use_signal(|| None::<String>);
+    let mut history_status = use_signal(|| None::<String>);
+    let mut audit_status = use_signal(|| None::<String>);
+    // Signed-in employee; `None` shows the lock screen.
+    let mut operator = use_signal(|| None::<Employee>);
+    // Action waiting for a supervisor's PIN.
+    let mut pending_override = use_signal(|| None::<Restricted>);
+
+    let allowed = move |permission: Permission| {
+        operator.read().as_ref().is_some_and(|e| e.can(permission))
+    };
+
+    let record_order = move |order: &Order| {
+        if let Err(e) = store.read().record_order(order) {
+            eprintln!("failed to record order {}: {e}", order.id);
+        }
+    };
+    // Chains an entry onto the audit log as the signed-in operator.
+    let mut audit = move |event: AuditEvent| {
+        let entry = AuditEntry::seal(event, operator.read().as_ref(), audit_log.read().last());
+        match store.read().record_audit(&entry) {
+            Ok(()) => audit_log.write().push(entry),
+            Err(e) => eprintln!("failed to record audit entry: {e}"),
+        }
+    };
+    let mut record_movements = move |movements: Vec<StockMovement>| {
+        for movement in &movements {
+            if let Err(e) = store.read().record_stock_movement(movement) {
+                eprintln!("failed to record stock movement: {e}");
+            }
+        }
+        stock_ledger.write().extend(movements);
+    };
+    let mut record_points = move |entries: Vec<PointsEntry>| {
+        for entry in &entries {
+            if let Err(e) = store.read().record_points_entry(entry) {
+                eprintln!("failed to record points entry: {e}");
+            }
+        }
+        points_ledger.write().extend(entries);
+    };
+    let mut record_card_entries = move |entries: Vec<CardEntry>| {
+        for entry in &entries {
+            if let Err(e) = store.read().record_card_entry(entry) {
+                eprintln!("failed to record gift card entry: {e}");
+            }
+        }
+        card_ledger.write().extend(entries);
+    };
+
+    // Applies `change` to the open drawer session, saves it and audits it
+    // as `event`.
+    let mut update_drawer = move |event: AuditEvent, change: &dyn Fn(&mut DrawerSession) -> Result<(), String>| {
+        let mut sessions = drawer_sessions.write();
+        let Some(session) = sessions.iter_mut().find(|s| s.is_open()) else {
+            drawer_status.set(Some("No drawer is open".to_string()));
+            return;
+        };
+        let mut updated = session.clone();
+        if let Err(e) = change(&mut updated) {
+            drawer_status.set(Some(e));
+            return;
+        }
+        match store.read().record_drawer_session(&updated) {
+            Ok(()) => {
+                audit(event.subject(&updated.id).before(session).after(&updated));
+                *session = updated;
+            }
+            Err(e) => drawer_status.set(Some(format!("Drawer not saved: {e}"))),
+        }
+    };
+
+    let mut print_receipt = move |order: &Order, reprint: bool| {
+        let receipt_settings = settings.read().receipt.clone();
+        let receipt = receipts::build(order, &receipt_settings, reprint);
+        let output = receipts::output_for(&receipt_settings, &JsonStore::default_dir());
+        match output.print(&receipt) {
+            Ok(()) => print_status.set(None),
+            Err(e) => print_status.set(Some(format!("Receipt not printed: {e}"))),
+        }
+    };
+
+    // Applies the oversell policy to one more of `product`; false when blocked.
+    let mut stock_allows = move |product: &Product| {
+        let in_cart = current_order.read().quantity_of(&product.id);
+        match settings.read().oversell.check(product, in_cart + 1) {
+            StockCheck::Block(message) => {
+                notice.set(Some(message));
+                return false;
+            }
+            StockCheck::Warn(message) => notice.set(Some(message)),
+            StockCheck::Ok => notice.set(None),
+        }
+        true
+    };
+    let mut add_to_cart = move |product: Product, options: Vec<ChosenOption>| {
+        if !stock_allows(&product) {
+            return;
+        }
+        let taxes = tax_config.read().rates_for(&product, &categories.read());
+        current_order.write().add_item(&product, options, &categories.read(), taxes);
+    };
+
+    // Points already paid with belong to the attached customer, so the
+    // customer stays until that payment is removed.
+    let mut can_change_customer = move || {
+        // Points are the customer's, and an exemption would take the total
+        // below what was paid.
+        let taken = !current_order.read().payments.is_empty();
+        if taken {
+            notice.set(Some("Remove the payments before changing the customer".to_string()));
+        }
+        !taken
+    };
+
+    // Parks the cart and starts a fresh order; false if it can't be held.
+    let mut hold_order = move |label: String| {
+        let by = operator.read().as_ref().map(|e| e.name.clone());
+        let order = current_order.read().clone();
+        let held = match HeldOrder::new(order, &label, by, &held_orders.read()) {
+            Ok(held) => held,
+            Err(e) => {
+                notice.set(Some(format!("Order not held: {e}")));
+                return false;
+            }
+        };
+        audit(
+            AuditEvent::new(
+                AuditAction::OrderHeld,
+                format!("Held {} ({} lines, {})", held.label, held.order.items.len(), held.order.total),
+            )
+            .subject(&held.order.id)
+            .after(&held),
+        );
+        held_orders.write().push(held);
+        current_order.set(new_order());
+        notice.set(None);
+        true
+    };
+    // Brings a held order back into the cart. A cart in progress is held
+    // in its place.
+    let mut recall_order = move |order_id: String| {
+        if !current_order.read().items.is_empty() && !hold_order(String::new()) {
+            return;
+        }
+        let position = held_orders.read().iter().position(|h| h.order.id == order_id);
+        let Some(held) = position.map(|i| held_orders.write().remove(i)) else {
+            return;
+        };
+        audit(
+            AuditEvent::new(
+                AuditAction::OrderRecalled,
+                format!("Recalled {} ({} lines, {})", held.label, held.order.items.len(), held.order.total),
+            )
+            .subject(&held.order.id)
+            .before(&held),
+        );
+        current_order.set(held.order);
+    };
+    // Holds nobody came back for are dropped once they pass the expiry set
+    // in settings.json.
+    use_future(move || async move {
+        let mut clock = document::eval(MINUTE_CLOCK);
+        while clock.recv::<bool>().await.is_ok() {
+            let hours = settings.read().hold_expiry_hours;
+            let now = chrono::Utc::now();
+            if !held_orders.read().iter().any(|h| h.is_expired(hours, now)) {
+                continue;
+            }
+            let expired = models::expire_holds(&mut held_orders.write(), hours, now);
+            for held in expired {
+                audit(
+                    AuditEvent::new(
+                        AuditAction::HoldExpired,
+                        format!("{} expired after {}, {} not sold", held.label, held.age(now), held.order.total),
+                    )
+                    .subject(&held.order.id)
+                    .before(&held),
+                );
+            }
+        }
+    });
+
+    // A code from the barcode scanner: a SKU, a product barcode or an
+    // in-store price or weight label.
+    let mut scan = move |input: String| {
+        // A window is open; the scan would land behind it.
+        if show_payment_modal() || choosing.read().is_some() || unknown_scan.read().is_some() {
+            return;
+        }
+        let scanned = match models::parse_scan(&input, &settings.read().embedded_barcodes) {
+            Ok(scanned) => scanned,
+            Err(e) => {
+                notice.set(Some(format!("Scan not added: {e}")));
+                return;
+            }
+        };
+        match &scanned {
+            Scan::Code(code) => {
+                let product = models::find_by_code(&products.read(), code).cloned();
+                match product {
+                    Some(product) if product.has_options() => choosing.set(Some(product)),
+                    Some(product) => add_to_cart(product, Vec::new()),
+                    None => unknown_scan.set(Some(scanned)),
+                }
+            }
+            Scan::Label(label) => {
+                let product = models::find_by_label(&products.read(), label).cloned();
+                match product {
+                    Some(product) => {
+                        if !stock_allows(&product) {
+                            return;
+                        }
+                        let taxes = tax_config.read().rates_for(&product, &categories.read());
+                        current_order.write().add_labelled_item(&product, label, &categories.read(), taxes);
+                    }
+                    None => unknown_scan.set(Some(scanned)),
+                }
+            }
+        }
+    };
+
+    let void_cart = move |reason: ReasonCode| {
+        let taken = current_order.read().payments.clone();
+        spawn(async move {
+            if let Err(e) = payments::void_card_payments(processor(), taken).await {
+                notice.set(Some(format!("Order not voided: {e}. Void again to retry")));
+                return;
+            }
+            let mut order = current_order.write();
+            let before = order.clone();
+            if order.void(reason, None).is_ok() {
+                audit(
+                    AuditEvent::new(AuditAction::OrderVoided, format!("Voided open order ({}): {}", reason.as_str(), order.total))
+                        .subject(&order.id)
+                        .before(&before)
+                        .after(&*order),
+                );
+                record_order(&order);
+                order_history.write().push(order.clone());
+                *order = new_order();
+                notice.set(None);
+            }
+        });
+    };
+
+    let mut refund_order = move |request: RefundRequest| {
+        let mut history = order_history.write();
+        let Some(order) = history.iter_mut().find(|o| o.id == request.order_id) else {
+            return;
+        };
+        let before = order.clone();
+        // Booked before any card is refunded, so money that reaches a card
+        // is never left off the order; taken back if no card accepts it.
+        let refund = match order.refund(&request.quantities, request.reason, request.note, request.restock) {
+            Ok(refund) => refund,
+            Err(e) => {
+                history_status.set(Some(format!("Refund not made: {e}")));
+                return;
+            }
+        };
+        let card_refunds: Vec<(String, Money)> = refund
+            .tenders
+            .iter()
+            .filter(|_| !request.store_credit)
+            .filter(|t| order.payments.iter().any(|p| p.card.is_some() && Some(&p.id) == t.payment_id.as_ref()))
+            .filter_map(|t| Some((t.payment_id.clone()?, t.amount)))
+            .collect();
+        drop(history);
+
+        spawn(async move {
+            for (done, (payment_id, amount)) in card_refunds.iter().enumerate() {
+                let Err(e) = processor().refund(payment_id, *amount).await else {
+                    continue;
+                };
+                if done == 0 {
+                    if let Some(order) = order_history.write().iter_mut().find(|o| o.id == request.order_id) {
+                        order.undo_refund(&refund.id);
+                    }
+                    history_status.set(Some(format!("Refund not made at the card processor: {e}")));
+                    return;
+                }
+                // Part of it is already back on a card, so the refund stands.
+                history_status.set(Some(format!("Refund made, but {amount} could not be returned to the card: {e}")));
+                break;
+            }
+            let mut history = order_history.write();
+            let Some(order) = history.iter_mut().find(|o| o.id == request.order_id) else {
+                return;
+            };
+            let mut refund = refund;
+            let mut detail = format!("Refunded {} on order #{} ({})", refund.total, &order.id[..8], refund.reason.as_str());
+            if request.store_credit {
+                let (number, entries) = models::issue_store_credit(
+                    &mut gift_cards.write(),
+                    &card_ledger.read(),
+                    &settings.read().gift_cards,
+                    order,
+                    refund.total,
+                    chrono::Utc::now(),
+                );
+                order.credit_refund(&refund.id, &number);
+                refund = order.refunds.iter().find(|r| r.id == refund.id).cloned().unwrap_or(refund);
+                record_card_entries(entries);
+                detail.push_str(&format!(" as store credit {}", models::mask_number(&number)));
+            } else {
+                record_card_entries(models::post_refund_cards(&gift_cards.read(), &card_ledger.read(), order, &refund));
+            }
+            audit(
+                AuditEvent::new(AuditAction::OrderRefunded, detail)
+                .subject(&order.id)
+                .before(&before)
+                .after(&*order),
+            );
+            record_order(order);
+            if refund.restocked {
+                record_movements(models::post_refund(&mut products.write(), &refund));
+            }
+            record_points(models::post_refund_points(&points_ledger.read(), order, &refund));
+        });
+    };
+
+    let mut void_order = move |request: VoidRequest| {
+        let Some(order) = order_history.read().iter().find(|o| o.id == request.order_id).cloned() else {
+            return;
+        };
+        // What a sold gift card paid for can't be taken back.
+        let spent: Vec<String> = models::spent_cards(&gift_cards.read(), &card_ledger.read(), &order)
+            .iter()
+            .map(|c| c.masked())
+            .collect();
+        if !spent.is_empty() {
+            history_status.set(Some(format!("Order not voided: gift card {} already spent", spent.join(", "))));
+            return;
+        }
+        // Dry run, so no card is reversed for a void that would be refused.
+        if let Err(e) = order.clone().void(request.reason, request.note.clone()) {
+            history_status.set(Some(format!("Order not voided: {e}")));
+            return;
+        }
+        let taken = order.payments;
+
+        spawn(async move {
+            if let Err(e) = payments::void_card_payments(processor(), taken).await {
+                history_status.set(Some(format!("Order not voided at the card processor: {e}. Void again to retry")));
+                return;
+            }
+            let mut history = order_history.write();
+            let Some(order) = history.iter_mut().find(|o| o.id == request.order_id) else {
+                return;
+            };
+            let was_paid = order.status == OrderStatus::Paid;
+            let before = order.clone();
+            match order.void(request.reason, request.note) {
+                Ok(()) => {
+                    audit(
+                        AuditEvent::new(
+                            AuditAction::OrderVoided,
+                            format!("Voided order #{} ({}): {}", &order.id[..8], request.reason.as_str(), order.total),
+                        )
+                        .subject(&order.id)
+                        .before(&before)
+                        .after(&*order),
+                    );
+                    record_order(order);
+                    if was_paid {
+                        record_movements(models::post_cancellation(&mut products.write(), order));
+                        record_points(models::post_cancellation_points(&points_ledger.read(), order));
+                        record_card_entries(models::post_cancellation_cards(&card_ledger.read(), order));
+                    }
+                }
+                Err(e) => history_status.set(Some(format!("Order not voided: {e}"))),
+            }
+        });
+    };
+
+    // A tip added to a card payment after it was charged, e.g. from the
+    // signed slip. The processor is adjusted first so the order never shows
+    // a tip that wasn't charged.
+    let adjust_tip = move |request: TipAdjustment| {
+        let Some(order) = order_history.read().iter().find(|o| o.id == request.order_id).cloned() else {
+            return;
+        };
+        let Some(payment) = order.payments.iter().find(|p| p.id == request.payment_id).cloned() else {
+            return;
+        };
+        if let Err(e) = settings.read().tips.check(request.tip, payment.amount) {
+            history_status.set(Some(format!("Tip not changed: {e}")));
+            return;
+        }
+        // A closed Z report must not change under a later adjustment.
+        let closed = z_reports.read().last().map(|z| z.period_end);
+        if order.paid_at().zip(closed).is_some_and(|(paid, closed)| paid <= closed) {
+            history_status.set(Some("Tip not changed: the day of this sale is closed".to_string()));
+            return;
+        }
+        if let Err(e) = order.clone().adjust_tip(&payment.id, request.tip) {
+            history_status.set(Some(format!("Tip not changed: {e}")));
+            return;
+        }
+
+        spawn(async move {
+            if let Err(e) = processor().adjust(&payment.id, payment.amount + request.tip).await {
+                history_status.set(Some(format!("Tip not changed at the card processor: {e}")));
+                return;
+            }
+            let mut history = order_history.write();
+            let Some(order) = history.iter_mut().find(|o| o.id == request.order_id) else {
+                return;
+            };
+            let before = order.clone();
+            match order.adjust_tip(&payment.id, request.tip) {
+                Ok(previous) => {
+                    audit(
+                        AuditEvent::new(
+                            AuditAction::TipAdjusted,
+                            format!("Tip on order #{} changed from {previous} to {}", &order.id[..8], request.tip),
+                        )
+                        .subject(&order.id)
+                        .before(&before)
+                        .after(&*order),
+                    );
+                    record_order(order);
+                    history_status.set(Some(format!("Tip set to {}", request.tip)));
+                }
+                Err(e) => history_status.set(Some(format!("Tip not changed: {e}"))),
+            }
+        });
+    };
+
+    // Archived products leave the till but stay on file for past orders.
+    let mut set_archived = move |id: String, archived: bool| {
+        let Some(before) = products.read().iter().find(|p| p.id == id).cloned() else {
+            return;
+        };
+        let after = Product { archived, ..before.clone() };
+        let (action, verb) = if archived {
+            (AuditAction::ProductArchived, "Archived")
+        } else {
+            (AuditAction::ProductRestored, "Restored")
+        };
+        audit(
+            AuditEvent::new(action, format!("{verb} {}", before.name))
+                .subject(&id)
+                .before(&before)
+                .after(&after),
+        );
+        if let Some(product) = products.write().iter_mut().find(|p| p.id == id) {
+            *product = after;
+        }
+    };
+    // Adds a new product. Its starting stock is received through the ledger,
+    // with `note`, so the ledger adds up to what is on hand.
+    let mut add_product = move |mut product: Product, note: &str| {
+        let opening = std::mem::take(&mut product.stock);
+        let id = product.id.clone();
+        products.write().push(product);
+        if opening != 0 {
+            let movement = models::post_movement(
+                &mut products.write(),
+                &id,
+                MovementKind::Receiving,
+                opening,
+                None,
+                Some(note.to_string()),
+            );
+            record_movements(movement.into_iter().collect());
+        }
+    };
+    // Saves an edited product, logging its price change and posting a stock
+    // difference to the ledger with `note`. Returns the product before and
+    // after and what changed; `None` if nothing did.
+    let mut edit_product = move |edited: Product, note: &str| {
+        let before = products.read().iter().find(|p| p.id == edited.id).cloned()?;
+        let changes = before.changes(&edited);
+        if changes.is_empty() {
+            return None;
+        }
+        let by = operator.read().as_ref().map(|e| e.name.clone());
+        let mut after = before.clone();
+        let stock_change = after.apply_edit(edited, by);
+        if let Some(product) = products.write().iter_mut().find(|p| p.id == after.id) {
+            *product = after.clone();
+        }
+        // Stock set in an edit goes through the ledger like any count correction.
+        if stock_change != 0 {
+            let movement = models::post_movement(
+                &mut products.write(),
+                &after.id,
+                MovementKind::Adjustment,
+                stock_change,
+                None,
+                Some(note.to_string()),
+            );
+            after.stock += stock_change;
+            record_movements(movement.into_iter().collect());
+        }
+        Some((before, after, changes))
+    };
+
+    let mut close_day = move || {
+        let last = z_reports.read().last().cloned();
+        let report = SalesReport::build(
+            ReportKind::Z,
+            Some(last.as_ref().and_then(|z| z.number).unwrap_or(0) + 1),
+            &order_history.read(),
+            &employees.read(),
+            last.map(|z| z.period_end),
+            chrono::Utc::now(),
+        );
+        match store.read().record_report(&report) {
+            Ok(()) => {
+                report_status.set(Some(format!("Day closed: Z report #{:04}", report.number.unwrap_or(0))));
+                audit(
+                    AuditEvent::new(
+                        AuditAction::DayClosed,
+                        format!("Z report #{:04}: net sales {}", report.number.unwrap_or(0), report.net_sales),
+                    )
+                    .after(&report),
+                );
+                z_reports.write().push(report);
+            }
+            Err(e) => report_status.set(Some(format!("Day not closed: {e}"))),
+        }
+    };
+
+    let mut run_restricted = move |action: Restricted| match action {
+        Restricted::ArchiveProduct(id) => set_archived(id, true),
+        Restricted::PriceOverride(request) => {
+            let mut order = current_order.write();
+            let Some(before) = order.item(&request.line_id).cloned() else {
+                return;
+            };
+            if let Err(e) = order.override_price(&request.line_id, request.price) {
+                notice.set(Some(format!("Price not changed: {e}")));
+                return;
+            }
+            let after = order.item(&request.line_id);
+            audit(
+                AuditEvent::new(
+                    AuditAction::PriceOverridden,
+                    format!("{}: {} → {}", before.label(), before.price, request.price),
+                )
+                .subject(&order.id)
+                .before(&before)
+                .after(&after),
+            );
+        }
+        Restricted::Discount(discount) => {
+            let mut order = current_order.write();
+            let before = order.discounts.clone();
+            let on = match &discount.target {
+                DiscountTarget::Order => "order".to_string(),
+                DiscountTarget::Line(id) => order.item(id).map_or_else(|| id.clone(), |i| i.label()),
+            };
+            let detail = format!("{} on {on}", discount.kind.label());
+            if let Err(e) = order.add_discount(discount) {
+                notice.set(Some(format!("Discount not applied: {e}")));
+                return;
+            }
+            audit(
+                AuditEvent::new(AuditAction::DiscountApplied, detail)
+                    .subject(&order.id)
+                    .before(&before)
+                    .after(&order.discounts),
+            );
+        }
+        Restricted::Refund(request) => refund_order(request),
+        Restricted::Void(request) => void_order(request),
+        Restricted::VoidCart(reason) => void_cart(reason),
+        Restricted::CloseDay => close_day(),
+    };
+
+    // Runs the action if the operator may, otherwise asks for an override.
+    let mut attempt = move |action: Restricted| {
+        if allowed(action.permission()) {
+            run_restricted(action);
+        } else {
+            pending_override.set(Some(action));
+        }
+    };
+
+    // A view the operator may not open falls back to the till.
+    let view = match current_view() {
+        View::Products | View::Categories if !allowed(Permission::ManageProducts) => View::Pos,
+        View::Reports if !allowed(Permission::ViewReports) => View::Pos,
+        View::Employees if !allowed(Permission::ManageEmployees) => View::Pos,
+        View::Promotions if !allowed(Permission::ManagePromotions) => View::Pos,
+        View::Audit if !allowed(Permission::ViewAuditLog) => View::Pos,
+        view => view,
+    };
 
     rsx! {
         style { {include_str!("../styles.css")} }
@@ -47,10 +811,27 @@ fn App() -> Element {
                     h1 { "TREZZA TERMINAL" }
                 }
 
-                button {
-                    class: "menu-btn",
-                    onclick: move |_| sidebar_open.set(!sidebar_open()),
-                    "MENU"
+                div {
+                    class: "header-actions",
+                    if let Some(employee) = operator() {
+                        span { class: "header-operator", "{employee.name} · {employee.role.as_str()}" }
+                    }
+                    button {
+                        class: "menu-btn",
+                        onclick: move |_| {
+                            if let Some(employee) = operator() {
+                                audit(AuditEvent::new(AuditAction::Logout, format!("{} signed out", employee.name)).subject(&employee.id));
+                            }
+                            operator.set(None);
+                            sidebar_open.set(false);
+                        },
+                        "LOCK"
+                    }
+                    button {
+                        class: "menu-btn",
+                        onclick: move |_| sidebar_open.set(!sidebar_open()),
+                        "MENU"
+                    }
                 }
             }
 
@@ -71,9 +852,9 @@ fn App() -> Element {
                 nav {
                     class: "sidebar-nav",
                     button {
-                        class: if current_view() == View::POS { "sidebar-btn active" } else { "sidebar-btn" },
+                        class: if current_view() == View::Pos { "sidebar-btn active" } else { "sidebar-btn" },
                         onclick: move |_| {
-                            current_view.set(View::POS);
+                            current_view.set(View::Pos);
                             sidebar_open.set(false);
                         },
                         "POS"
@@ -87,12 +868,86 @@ fn App() -> Element {
                         "History"
                     }
                     button {
-                        class: if current_view() == View::Products { "sidebar-btn active" } else { "sidebar-btn" },
+                        class: if current_view() == View::Customers { "sidebar-btn active" } else { "sidebar-btn" },
                         onclick: move |_| {
-                            current_view.set(View::Products);
+                            current_view.set(View::Customers);
                             sidebar_open.set(false);
                         },
-                        "Products"
+                        "Customers"
+                    }
+                    button {
+                        class: if current_view() == View::GiftCards { "sidebar-btn active" } else { "sidebar-btn" },
+                        onclick: move |_| {
+                            current_view.set(View::GiftCards);
+                            sidebar_open.set(false);
+                        },
+                        "Gift Cards"
+                    }
+                    if allowed(Permission::ManageProducts) {
+                        button {
+                            class: if current_view() == View::Products { "sidebar-btn active" } else { "sidebar-btn" },
+                            onclick: move |_| {
+                                current_view.set(View::Products);
+                                sidebar_open.set(false);
+                            },
+                            "Products"
+                        }
+                        button {
+                            class: if current_view() == View::Categories { "sidebar-btn active" } else { "sidebar-btn" },
+                            onclick: move |_| {
+                                current_view.set(View::Categories);
+                                sidebar_open.set(false);
+                            },
+                            "Categories"
+                        }
+                    }
+                    button {
+                        class: if current_view() == View::Drawer { "sidebar-btn active" } else { "sidebar-btn" },
+                        onclick: move |_| {
+                            current_view.set(View::Drawer);
+                            sidebar_open.set(false);
+                        },
+                        "Drawer"
+                    }
+                    if allowed(Permission::ViewReports) {
+                        button {
+                            class: if current_view() == View::Reports { "sidebar-btn active" } else { "sidebar-btn" },
+                            onclick: move |_| {
+                                current_view.set(View::Reports);
+                                sidebar_open.set(false);
+                            },
+                            "Reports"
+                        }
+                    }
+                    if allowed(Permission::ManageEmployees) {
+                        button {
+                            class: if current_view() == View::Employees { "sidebar-btn active" } else { "sidebar-btn" },
+                            onclick: move |_| {
+                                current_view.set(View::Employees);
+                                sidebar_open.set(false);
+                            },
+                            "Employees"
+                        }
+                    }
+                    if allowed(Permission::ManagePromotions) {
+                        button {
+                            class: if current_view() == View::Promotions { "sidebar-btn active" } else { "sidebar-btn" },
+                            onclick: move |_| {
+                                current_view.set(View::Promotions);
+                                sidebar_open.set(false);
+                            },
+                            "Promotions"
+                        }
+                    }
+                    if allowed(Permission::ViewAuditLog) {
+                        button {
+                            class: if current_view() == View::Audit { "sidebar-btn active" } else { "sidebar-btn" },
+                            onclick: move |_| {
+                                current_view.set(View::Audit);
+                                sidebar_open.set(false);
+                            },
+                            "Audit Log"
+                        }
                     }
 
                     div { class: "sidebar-divider" }
@@ -102,6 +957,16 @@ fn App() -> Element {
                         onclick: move |_| dark_mode.set(!dark_mode()),
                         if dark_mode() { "Light Mode" } else { "Dark Mode" }
                     }
+                    if allowed(Permission::ChangeSettings) {
+                        button {
+                            class: "sidebar-btn",
+                            onclick: move |_| {
+                                let next = settings.read().oversell.next();
+                                settings.write().oversell = next;
+                            },
+                            "Oversell: {settings.read().oversell.as_str()}"
+                        }
+                    }
                 }
             }
 
@@ -117,36 +982,152 @@ fn App() -> Element {
             main {
                 class: "main-content",
 
-                match current_view() {
-                    View::POS => rsx! {
+                match view {
+                    View::Pos => rsx! {
                         div {
                             class: "pos-view",
+                            ScanListener { on_scan: move |code: String| scan(code) },
 
                             div {
                                 class: "products-section",
                                 ProductGrid {
                                     products,
+                                    categories,
                                     selected_category,
+                                    orders: order_history,
                                     on_product_click: move |product: Product| {
-                                        current_order.write().add_item(
-                                            product.id.clone(),
-                                            product.name.clone(),
-                                            product.price,
-                                        );
+                                        if product.has_options() {
+                                            choosing.set(Some(product));
+                                        } else {
+                                            add_to_cart(product, Vec::new());
+                                        }
                                     },
                                 }
                             }
 
                             div {
                                 class: "cart-section",
+                                if let Some(message) = notice() {
+                                    div {
+                                        class: "stock-notice",
+                                        span { "{message}" }
+                                        button {
+                                            class: "notice-dismiss",
+                                            onclick: move |_| notice.set(None),
+                                            "✕"
+                                        }
+                                    }
+                                }
+                                HeldOrders {
+                                    held: held_orders,
+                                    on_recall: move |order_id: String| recall_order(order_id),
+                                }
                                 Cart {
                                     order: current_order,
+                                    on_increment: move |id: String| {
+                                        let Some(item) = current_order.read().item(&id).cloned() else {
+                                            return;
+                                        };
+                                        let product = products.read().iter().find(|p| p.id == item.product_id && !p.archived).cloned();
+                                        match product {
+                                            Some(product) => add_to_cart(product, item.options),
+                                            None => notice.set(Some("This item is no longer sold".to_string())),
+                                        }
+                                    },
+                                    on_decrement: move |id: String| {
+                                        let mut order = current_order.write();
+                                        let Some(before) = order.item(&id).cloned() else {
+                                            return;
+                                        };
+                                        if let Err(e) = order.remove_item(&id) {
+                                            notice.set(Some(format!("Item not removed: {e}")));
+                                            return;
+                                        }
+                                        let after = order.item(&id);
+                                        audit(
+                                            AuditEvent::new(AuditAction::CartLineRemoved, format!("Removed 1 × {}", before.label()))
+                                                .subject(&order.id)
+                                                .before(&before)
+                                                .after(&after),
+                                        );
+                                    },
                                     on_checkout: move |_| {
+                                        // Every sale belongs to a drawer session.
+                                        if !drawer_sessions.read().iter().any(|s| s.is_open()) {
+                                            drawer_status.set(Some("Open the cash drawer before taking payment".to_string()));
+                                            current_view.set(View::Drawer);
+                                            return;
+                                        }
                                         show_payment_modal.set(true);
                                     },
                                     on_clear: move |_| {
-                                        current_order.write().clear();
+                                        let mut order = current_order.write();
+                                        let before = order.clone();
+                                        if let Err(e) = order.clear() {
+                                            notice.set(Some(format!("Cart not cleared: {e}")));
+                                            return;
+                                        }
+                                        if !before.items.is_empty() {
+                                            audit(
+                                                AuditEvent::new(
+                                                    AuditAction::CartCleared,
+                                                    format!("Cleared {} lines totalling {}", before.items.len(), before.total),
+                                                )
+                                                .subject(&before.id)
+                                                .before(&before.items),
+                                            );
+                                        }
+                                        notice.set(None);
+                                    },
+                                    on_hold: move |label: String| {
+                                        hold_order(label);
+                                    },
+                                    customers,
+                                    on_attach_customer: move |customer: Customer| {
+                                        if !can_change_customer() {
+                                            return;
+                                        }
+                                        let attached = customer.for_order();
+                                        let mut order = current_order.write();
+                                        if let Some(certificate) = &attached.exemption_certificate {
+                                            audit(
+                                                AuditEvent::new(
+                                                    AuditAction::TaxExemptionApplied,
+                                                    format!("{} exempt under certificate {certificate}", attached.name),
+                                                )
+                                                .subject(&order.id)
+                                                .after(&attached),
+                                            );
+                                        }
+                                        order.set_customer(Some(attached));
+                                    },
+                                    on_detach_customer: move |_| {
+                                        if can_change_customer() {
+                                            current_order.write().set_customer(None);
+                                        }
+                                    },
+                                    on_void: move |reason: ReasonCode| attempt(Restricted::VoidCart(reason)),
+                                    on_price_override: move |request: PriceOverride| attempt(Restricted::PriceOverride(request)),
+                                    on_discount: move |discount: Discount| attempt(Restricted::Discount(discount)),
+                                    on_remove_discount: move |id: String| {
+                                        let mut order = current_order.write();
+                                        let Some(removed) = order.discounts.iter().find(|d| d.id == id).cloned() else {
+                                            return;
+                                        };
+                                        order.remove_discount(&id);
+                                        audit(
+                                            AuditEvent::new(AuditAction::DiscountRemoved, format!("Removed {}", removed.kind.label()))
+                                                .subject(&order.id)
+                                                .before(&removed),
+                                        );
+                                    },
+                                    on_coupon: move |code: String| {
+                                        match current_order.write().apply_coupon(&code) {
+                                            Ok(()) => notice.set(None),
+                                            Err(e) => notice.set(Some(format!("Coupon not applied: {e}"))),
+                                        }
                                     },
+                                    on_remove_coupon: move |code: String| current_order.write().remove_coupon(&code),
                                 }
                             }
                         }
@@ -154,16 +1135,354 @@ fn App() -> Element {
                     View::History => rsx! {
                         OrderHistory {
                             orders: order_history,
+                            open_session: drawer_sessions.read().iter().find(|s| s.is_open()).map(|s| s.id.clone()),
+                            status: history_status(),
+                            on_refund: move |request: RefundRequest| attempt(Restricted::Refund(request)),
+                            on_reprint: move |order_id: String| {
+                                let order = order_history.read().iter().find(|o| o.id == order_id).cloned();
+                                if let Some(order) = order {
+                                    print_receipt(&order, true);
+                                    receipt_order.set(Some(order));
+                                }
+                            },
+                            on_void: move |request: VoidRequest| attempt(Restricted::Void(request)),
+                            on_adjust_tip: adjust_tip,
+                        }
+                    },
+                    View::Customers => rsx! {
+                        CustomerManager {
+                            customers,
+                            orders: order_history,
+                            ledger: points_ledger,
+                            loyalty: settings.read().loyalty.clone(),
+                            can_exempt: allowed(Permission::TaxExemptions),
+                            on_save: move |customer: Customer| {
+                                let before = customers.read().iter().find(|c| c.id == customer.id).cloned();
+                                let detail = match &before {
+                                    Some(_) => format!("Edited {}", customer.name),
+                                    None => format!("Added {}", customer.name),
+                                };
+                                let mut event = AuditEvent::new(AuditAction::CustomerSaved, detail)
+                                    .subject(&customer.id)
+                                    .after(&customer);
+                                if let Some(old) = &before {
+                                    event = event.before(old);
+                                }
+                                audit(event);
+                                let mut list = customers.write();
+                                match list.iter_mut().find(|c| c.id == customer.id) {
+                                    Some(existing) => *existing = customer,
+                                    None => list.push(customer),
+                                }
+                            },
+                        }
+                    },
+                    View::GiftCards => rsx! {
+                        GiftCards {
+                            cards: gift_cards,
+                            ledger: card_ledger,
+                            customers,
+                            config: settings.read().gift_cards.clone(),
+                            on_load: move |load: GiftCardLoad| {
+                                current_order.write().add_gift_card(&load.number, load.amount);
+                                notice.set(None);
+                                current_view.set(View::Pos);
+                            },
                         }
                     },
                     View::Products => rsx! {
                         ProductManager {
                             products,
-                            on_add: move |product| {
-                                products.write().push(product);
+                            categories,
+                            tax_config,
+                            ledger: stock_ledger,
+                            on_add: move |product: Product| {
+                                audit(
+                                    AuditEvent::new(AuditAction::ProductCreated, format!("Added {} at {}", product.name, product.price))
+                                        .subject(&product.id)
+                                        .after(&product),
+                                );
+                                add_product(product, "Opening stock");
+                            },
+                            on_adjust: move |adjustment: StockAdjustment| {
+                                let movement = models::post_movement(
+                                    &mut products.write(),
+                                    &adjustment.product_id,
+                                    adjustment.kind,
+                                    adjustment.quantity,
+                                    None,
+                                    adjustment.note,
+                                );
+                                if let Some(movement) = &movement {
+                                    audit(
+                                        AuditEvent::new(
+                                            AuditAction::StockAdjusted,
+                                            format!(
+                                                "{} {:+} {} (on hand {})",
+                                                movement.kind.as_str(),
+                                                movement.quantity,
+                                                movement.product_name,
+                                                movement.on_hand
+                                            ),
+                                        )
+                                        .subject(&movement.product_id)
+                                        .after(movement),
+                                    );
+                                }
+                                record_movements(movement.into_iter().collect());
+                            },
+                            on_update: move |edited: Product| {
+                                let Some((before, after, changes)) = edit_product(edited, "Product edit") else {
+                                    return;
+                                };
+                                audit(
+                                    AuditEvent::new(AuditAction::ProductEdited, format!("Edited {}: {}", before.name, changes.join(", ")))
+                                        .subject(&after.id)
+                                        .before(&before)
+                                        .after(&after),
+                                );
+                            },
+                            on_archive: move |id: String| attempt(Restricted::ArchiveProduct(id)),
+                            on_restore: move |id: String| set_archived(id, false),
+                        }
+                        CatalogImport {
+                            products,
+                            categories,
+                            tax_config,
+                            status: catalog_status(),
+                            on_import: move |plan: ImportPlan| {
+                                let counts = plan.counts();
+                                categories.write().extend(plan.new_categories);
+                                for row in plan.rows {
+                                    match row.outcome {
+                                        RowOutcome::Create(product) => add_product(product, "Import"),
+                                        // Stock changes are posted to the ledger as adjustments.
+                                        RowOutcome::Update { product, .. } => {
+                                            let (id, archived) = (product.id.clone(), product.archived);
+                                            edit_product(product, "Import");
+                                            if let Some(p) = products.write().iter_mut().find(|p| p.id == id) {
+                                                p.archived = archived;
+                                            }
+                                        }
+                                        RowOutcome::Unchanged(_) | RowOutcome::Invalid(_) => {}
+                                    }
+                                }
+                                let summary = counts.summary();
+                                audit(AuditEvent::new(AuditAction::CatalogImported, format!("Imported catalog: {summary}")));
+                                catalog_status.set(Some(format!("Imported: {summary}")));
+                            },
+                            on_export: move |format: FileFormat| {
+                                let dir = JsonStore::default_dir().join("exports");
+                                match catalog::export_catalog(&products.read(), &categories.read(), format, &dir) {
+                                    Ok(path) => catalog_status.set(Some(format!("Saved {}", path.display()))),
+                                    Err(e) => catalog_status.set(Some(format!("Export failed: {e}"))),
+                                }
+                            },
+                        }
+                    },
+                    View::Drawer => rsx! {
+                        DrawerManager {
+                            sessions: drawer_sessions,
+                            orders: order_history,
+                            employees,
+                            denominations: settings.read().denominations.clone(),
+                            status: drawer_status(),
+                            on_open: move |float: Money| {
+                                if drawer_sessions.read().iter().any(|s| s.is_open()) {
+                                    return;
+                                }
+                                let session = DrawerSession::open(float);
+                                match store.read().record_drawer_session(&session) {
+                                    Ok(()) => {
+                                        audit(
+                                            AuditEvent::new(AuditAction::DrawerOpened, format!("Opened with float {float}"))
+                                                .subject(&session.id)
+                                                .after(&session),
+                                        );
+                                        drawer_sessions.write().push(session);
+                                        drawer_status.set(None);
+                                    }
+                                    Err(e) => drawer_status.set(Some(format!("Drawer not opened: {e}"))),
+                                }
+                            },
+                            on_entry: move |request: DrawerEntryRequest| {
+                                drawer_status.set(None);
+                                let event = AuditEvent::new(
+                                    AuditAction::DrawerEntry,
+                                    format!("{} {}", request.kind.as_str(), request.amount),
+                                );
+                                update_drawer(event, &|session| {
+                                    session
+                                        .add_entry(request.kind, request.amount, request.note.clone())
+                                        .map_err(|e| e.to_string())
+                                });
+                            },
+                            on_close: move |counts: Vec<DenominationCount>| {
+                                drawer_status.set(None);
+                                let orders = order_history.read().clone();
+                                let counted = counts.iter().map(|c| c.value * c.count).sum::<Money>();
+                                let event = AuditEvent::new(AuditAction::DrawerClosed, format!("Closed with {counted} counted"));
+                                update_drawer(event, &|session| {
+                                    session.close(counts.clone(), &orders).map(|_| ()).map_err(|e| e.to_string())
+                                });
+                            },
+                        }
+                    },
+                    View::Reports => rsx! {
+                        SalesReports {
+                            orders: order_history,
+                            employees,
+                            z_reports,
+                            width: settings.read().receipt.width,
+                            status: report_status(),
+                            on_close_day: move |_| attempt(Restricted::CloseDay),
+                            on_export: move |report: SalesReport| {
+                                let dir = JsonStore::default_dir().join("reports");
+                                match reports::export(&report, &dir, settings.read().receipt.width) {
+                                    Ok((text, csv)) => report_status.set(Some(format!(
+                                        "Saved {} and {}",
+                                        text.display(),
+                                        csv.display()
+                                    ))),
+                                    Err(e) => report_status.set(Some(format!("Export failed: {e}"))),
+                                }
+                            },
+                        }
+                    },
+                    View::Employees => rsx! {
+                        EmployeeManager {
+                            employees,
+                            operator_id: operator.read().as_ref().map(|e| e.id.clone()).unwrap_or_default(),
+                            on_save: move |employee: Employee| {
+                                let before = employees.read().iter().find(|e| e.id == employee.id).cloned();
+                                let detail = match &before {
+                                    None => format!("Added {} as {}", employee.name, employee.role.as_str()),
+                                    Some(old) if old.pin_hash != employee.pin_hash => format!("Reset PIN for {}", employee.name),
+                                    Some(old) if old.role != employee.role => format!(
+                                        "{}: {} → {}",
+                                        employee.name,
+                                        old.role.as_str(),
+                                        employee.role.as_str()
+                                    ),
+                                    Some(_) if employee.active => format!("Reactivated {}", employee.name),
+                                    Some(_) => format!("Deactivated {}", employee.name),
+                                };
+                                let mut event = AuditEvent::new(AuditAction::EmployeeSaved, detail)
+                                    .subject(&employee.id)
+                                    .after(&staff_record(&employee));
+                                if let Some(old) = &before {
+                                    event = event.before(&staff_record(old));
+                                }
+                                audit(event);
+                                if operator.read().as_ref().is_some_and(|e| e.id == employee.id) {
+                                    operator.set(Some(employee.clone()));
+                                }
+                                let mut staff = employees.write();
+                                match staff.iter_mut().find(|e| e.id == employee.id) {
+                                    Some(existing) => *existing = employee,
+                                    None => staff.push(employee),
+                                }
+                            },
+                        }
+                    },
+                    View::Categories => rsx! {
+                        CategoryManager {
+                            categories,
+                            products,
+                            tax_config,
+                            on_save: move |category: Category| {
+                                let before = categories.read().iter().find(|c| c.id == category.id).cloned();
+                                let detail = match &before {
+                                    Some(old) if old.name != category.name => format!("Renamed {} to {}", old.name, category.name),
+                                    Some(_) => format!("Edited {}", category.name),
+                                    None => format!("Added {}", category.name),
+                                };
+                                let mut event = AuditEvent::new(AuditAction::CategorySaved, detail)
+                                    .subject(&category.id)
+                                    .after(&category);
+                                if let Some(old) = &before {
+                                    event = event.before(old);
+                                }
+                                audit(event);
+                                let mut list = categories.write();
+                                match list.iter_mut().find(|c| c.id == category.id) {
+                                    Some(existing) => *existing = category,
+                                    None => list.push(category),
+                                }
                             },
-                            on_delete: move |id| {
-                                products.write().retain(|p| p.id != id);
+                            on_delete: move |id: String| {
+                                let Some(removed) = categories.read().iter().find(|c| c.id == id).cloned() else {
+                                    return;
+                                };
+                                audit(
+                                    AuditEvent::new(AuditAction::CategoryDeleted, format!("Deleted {}", removed.name))
+                                        .subject(&id)
+                                        .before(&removed),
+                                );
+                                categories.write().retain(|c| c.id != id);
+                                if selected_category() == Some(id) {
+                                    selected_category.set(None);
+                                }
+                            },
+                        }
+                    },
+                    View::Promotions => rsx! {
+                        PromotionManager {
+                            promotions,
+                            products,
+                            categories,
+                            on_save: move |promotion: Promotion| {
+                                let before = promotions.read().iter().find(|p| p.id == promotion.id).cloned();
+                                let detail = match &before {
+                                    None => format!("Added {}: {}", promotion.name, promotion.rule.summary(&categories.read())),
+                                    Some(_) if promotion.active => format!("Resumed {}", promotion.name),
+                                    Some(_) => format!("Paused {}", promotion.name),
+                                };
+                                let mut event = AuditEvent::new(AuditAction::PromotionSaved, detail)
+                                    .subject(&promotion.id)
+                                    .after(&promotion);
+                                if let Some(old) = &before {
+                                    event = event.before(old);
+                                }
+                                audit(event);
+                                {
+                                    let mut list = promotions.write();
+                                    match list.iter_mut().find(|p| p.id == promotion.id) {
+                                        Some(existing) => *existing = promotion,
+                                        None => list.push(promotion),
+                                    }
+                                }
+                                current_order.write().set_promotions(&promotions.read());
+                            },
+                            on_delete: move |id: String| {
+                                let Some(removed) = promotions.read().iter().find(|p| p.id == id).cloned() else {
+                                    return;
+                                };
+                                audit(
+                                    AuditEvent::new(AuditAction::PromotionDeleted, format!("Deleted {}", removed.name))
+                                        .subject(&id)
+                                        .before(&removed),
+                                );
+                                promotions.write().retain(|p| p.id != id);
+                                current_order.write().set_promotions(&promotions.read());
+                            },
+                        }
+                    },
+                    View::Audit => rsx! {
+                        AuditLog {
+                            entries: audit_log,
+                            status: audit_status(),
+                            on_verify: move |_| {
+                                // Check what is on disk, not the copy in memory.
+                                let result = store.read().load_audit_log();
+                                let message = match result {
+                                    Ok(entries) => match models::verify_chain(&entries) {
+                                        Ok(()) => format!("Chain intact: {} entries verified", entries.len()),
+                                        Err(e) => format!("Tampering detected: {e}"),
+                                    },
+                                    Err(e) => format!("Tampering detected: {e}"),
+                                };
+                                audit_status.set(Some(message));
                             },
                         }
                     },
@@ -173,25 +1492,272 @@ fn App() -> Element {
             // Payment Modal
             PaymentModal {
                 order: current_order,
+                settings,
+                processor,
                 show: show_payment_modal,
-                on_complete: move |payment: Payment| {
+                points_balance: current_order
+                    .read()
+                    .customer
+                    .as_ref()
+                    .filter(|_| settings.read().loyalty.enabled)
+                    .map(|c| models::points_balance(&points_ledger.read(), &c.id)),
+                gift_cards,
+                card_ledger,
+                on_payment: move |mut payment: Payment| {
+                    let session_id = drawer_sessions.read().iter().find(|s| s.is_open()).map(|s| s.id.clone());
+                    let operator_id = operator.read().as_ref().map(|e| e.id.clone());
+                    payment.operator_id = operator_id.clone();
                     let mut order = current_order.write();
-                    order.complete_payment(payment);
+                    order.session_id = session_id;
+                    order.operator_id = operator_id;
+                    if let Err(e) = order.add_payment(payment.clone()) {
+                        notice.set(Some(format!("Payment not taken: {e}")));
+                        // Don't keep money the order didn't take.
+                        spawn(async move {
+                            if let Err(e) = payments::void_card_payments(processor(), vec![payment]).await {
+                                notice.set(Some(format!("Payment not taken, but the card could not be reversed: {e}")));
+                            }
+                        });
+                        return;
+                    }
+                    if order.status != OrderStatus::Paid {
+                        return;
+                    }
+
+                    record_order(&order);
                     order_history.write().push(order.clone());
-                    *order = Order::new();
+                    record_movements(models::post_sale(&mut products.write(), &order));
+                    record_points(models::post_sale_points(&settings.read().loyalty, &points_ledger.read(), &order));
+                    record_card_entries(models::post_sale_cards(
+                        &mut gift_cards.write(),
+                        &card_ledger.read(),
+                        &settings.read().gift_cards,
+                        &order,
+                        chrono::Utc::now(),
+                    ));
+                    print_receipt(&order, false);
+                    receipt_order.set(Some(order.clone()));
+
+                    *order = new_order();
+                    notice.set(None);
                     show_payment_modal.set(false);
                 },
+                on_remove_payment: move |payment_id: String| {
+                    let Some(payment) = current_order.read().payments.iter().find(|p| p.id == payment_id).cloned() else {
+                        return;
+                    };
+                    spawn(async move {
+                        if let Err(e) = payments::void_card_payments(processor(), vec![payment]).await {
+                            notice.set(Some(format!("Payment kept, card could not be reversed: {e}")));
+                            return;
+                        }
+                        let mut order = current_order.write();
+                        if let Err(e) = order.remove_payment(&payment_id) {
+                            notice.set(Some(format!("Card reversed, but the payment is still on the order: {e}")));
+                        }
+                    });
+                },
                 on_cancel: move |_| {
                     show_payment_modal.set(false);
                 },
             }
+
+            if let Some(scanned) = unknown_scan() {
+                UnknownCodeModal {
+                    scan: scanned.clone(),
+                    products,
+                    categories,
+                    can_create: allowed(Permission::ManageProducts),
+                    on_create: move |product: Product| {
+                        audit(
+                            AuditEvent::new(AuditAction::ProductCreated, format!("Added {} at {} from a scan", product.name, product.price))
+                                .subject(&product.id)
+                                .after(&product),
+                        );
+                        add_product(product, "Opening stock");
+                        unknown_scan.set(None);
+                        // Rung up as if it had been found the first time.
+                        match &scanned {
+                            Scan::Code(code) | Scan::Label(models::ScaleLabel { code, .. }) => scan(code.clone()),
+                        }
+                    },
+                    on_close: move |_| unknown_scan.set(None),
+                }
+            }
+
+            if let Some(product) = choosing() {
+                OptionPicker {
+                    key: "{product.id}",
+                    product: product.clone(),
+                    on_confirm: move |options: Vec<ChosenOption>| {
+                        add_to_cart(product.clone(), options);
+                        choosing.set(None);
+                    },
+                    on_cancel: move |_| choosing.set(None),
+                }
+            }
+
+            if let Some(order) = receipt_order() {
+                ReceiptModal {
+                    receipt: receipts::build(&order, &settings.read().receipt, false),
+                    status: print_status(),
+                    on_print: move |_| print_receipt(&order, true),
+                    on_close: move |_| {
+                        receipt_order.set(None);
+                        print_status.set(None);
+                    },
+                }
+            }
+
+            if let Some(action) = pending_override() {
+                OverrideModal {
+                    employees,
+                    permission: action.permission(),
+                    on_approve: move |approver: Employee| {
+                        audit(
+                            AuditEvent::new(
+                                AuditAction::OverrideApproved,
+                                format!("{} approved: {}", approver.name, action.permission().as_str()),
+                            )
+                            .subject(&approver.id),
+                        );
+                        pending_override.set(None);
+                        run_restricted(action.clone());
+                    },
+                    on_failed: move |approver: Employee| {
+                        audit(
+                            AuditEvent::new(AuditAction::OverrideFailed, format!("Wrong PIN for {}", approver.name))
+                                .subject(&approver.id),
+                        );
+                    },
+                    on_cancel: move |_| pending_override.set(None),
+                }
+            }
+
+            if operator.read().is_none() {
+                LockScreen {
+                    employees,
+                    on_unlock: move |employee: Employee| {
+                        current_view.set(View::Pos);
+                        operator.set(Some(employee.clone()));
+                        audit(AuditEvent::new(AuditAction::Login, format!("{} signed in", employee.name)).subject(&employee.id));
+                    },
+                    on_failed: move |employee: Employee| {
+                        audit(
+                            AuditEvent::new(AuditAction::LoginFailed, format!("Wrong PIN for {}", employee.name))
+                                .subject(&employee.id),
+                        );
+                    },
+                    on_setup: move |admin: Employee| {
+                        employees.write().push(admin.clone());
+                        operator.set(Some(admin.clone()));
+                        audit(
+                            AuditEvent::new(AuditAction::EmployeeSaved, format!("Created administrator {}", admin.name))
+                                .subject(&admin.id)
+                                .after(&staff_record(&admin)),
+                        );
+                    },
+                }
+            }
         }
     }
 }
 
+fn seed_products() -> Vec<Product> {
+    vec![
+        Product::new("Espresso".to_string(), usd(350), "beverage".to_string()),
+        coffee("Cappuccino", usd(450)),
+        coffee("Latte", usd(500)),
+        Product::new("Croissant".to_string(), usd(375), "food".to_string()),
+        Product::new("Bagel".to_string(), usd(250), "food".to_string()),
+        Product::new("Sandwich".to_string(), usd(850), "food".to_string()),
+        Product::new("Salad".to_string(), usd(975), "food".to_string()),
+        Product::new("Muffin".to_string(), usd(325), "food".to_string()),
+        Product::new("T-Shirt".to_string(), usd(2499), "retail".to_string()),
+        Product::new("Mug".to_string(), usd(1299), "retail".to_string()),
+        Product::new("Consultation".to_string(), usd(5000), "service".to_string()),
+    ]
+}
+
+// Milk drinks come in three sizes with a choice of milk and extra shots.
+fn coffee(name: &str, price: Money) -> Product {
+    let mut product = Product::new(name.to_string(), price, "beverage".to_string());
+    product.options = vec![
+        OptionGroup::variant("Size".to_string())
+            .with_option("Small", usd(-50))
+            .with_option("Medium", usd(0))
+            .with_option("Large", usd(75)),
+        OptionGroup::modifier("Milk".to_string(), 0, 1)
+            .with_option("Whole Milk", usd(0))
+            .with_option("Oat Milk", usd(60))
+            .with_option("Almond Milk", usd(60)),
+        OptionGroup::modifier("Extras".to_string(), 0, 2)
+            .with_option("Extra Shot", usd(80))
+            .with_option("Vanilla Syrup", usd(50)),
+    ];
+    product
+}
+
+fn usd(cents: i64) -> Money {
+    Money::new(cents, Currency::USD)
+}
+
+// What the audit log keeps of an employee: never the PIN hash.
+fn staff_record(employee: &Employee) -> serde_json::Value {
+    serde_json::json!({
+        "id": employee.id,
+        "name": employee.name,
+        "role": employee.role,
+        "active": employee.active,
+    })
+}
+
+// Falls back to `default` when nothing is stored yet. A load error is fatal:
+// starting from scratch would overwrite the data we failed to read.
+fn load_or<T>(loaded: Result<Option<T>, storage::StorageError>, default: impl FnOnce() -> T) -> T {
+    match loaded {
+        Ok(Some(value)) => value,
+        Ok(None) => default(),
+        Err(e) => panic!("cannot load saved data: {e}"),
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 enum View {
-    POS,
+    Pos,
     History,
+    Customers,
+    GiftCards,
     Products,
+    Categories,
+    Drawer,
+    Reports,
+    Employees,
+    Promotions,
+    Audit,
+}
+
+// An action that needs a permission the signed-in employee may lack.
+#[derive(Clone, PartialEq)]
+enum Restricted {
+    ArchiveProduct(String),
+    PriceOverride(PriceOverride),
+    Discount(Discount),
+    Refund(RefundRequest),
+    Void(VoidRequest),
+    VoidCart(ReasonCode),
+    CloseDay,
+}
+
+impl Restricted {
+    fn permission(&self) -> Permission {
+        match self {
+            Restricted::ArchiveProduct(_) => Permission::ArchiveProducts,
+            Restricted::PriceOverride(_) => Permission::PriceOverride,
+            Restricted::Discount(_) => Permission::Discount,
+            Restricted::Refund(_) => Permission::Refund,
+            Restricted::Void(_) | Restricted::VoidCart(_) => Permission::Void,
+            Restricted::CloseDay => Permission::CloseDay,
+        }
+    }
 }
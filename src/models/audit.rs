@@ -0,0 +1,265 @@
+use std::fmt;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use super::employee::Employee;
+
+// What the first entry's `prev_hash` points at.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+// One sensitive action. Entries are chained: each carries the hash of the
+// one before it and a SHA-256 over its own contents, so editing, removing
+// or reordering any recorded entry breaks the chain from that point on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    // 1, 2, 3, ... with no gaps.
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    // Who was signed in. The name is copied so renames don't rewrite history.
+    pub actor_id: Option<String>,
+    pub actor_name: Option<String>,
+    pub action: AuditAction,
+    // Id of the product, order, drawer session or employee acted on.
+    pub subject: Option<String>,
+    pub detail: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    Login,
+    LoginFailed,
+    Logout,
+    OverrideApproved,
+    OverrideFailed,
+    ProductCreated,
+    // Recorded before products were archived instead of deleted.
+    ProductDeleted,
+    ProductEdited,
+    ProductArchived,
+    ProductRestored,
+    CatalogImported,
+    StockAdjusted,
+    CartLineRemoved,
+    CartCleared,
+    OrderHeld,
+    OrderRecalled,
+    HoldExpired,
+    PriceOverridden,
+    DiscountApplied,
+    DiscountRemoved,
+    OrderVoided,
+    OrderRefunded,
+    TipAdjusted,
+    DrawerOpened,
+    DrawerEntry,
+    DrawerClosed,
+    DayClosed,
+    EmployeeSaved,
+    PromotionSaved,
+    PromotionDeleted,
+    CategorySaved,
+    CategoryDeleted,
+    CustomerSaved,
+    TaxExemptionApplied,
+}
+
+// An action waiting to be chained onto the log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    pub action: AuditAction,
+    pub subject: Option<String>,
+    pub detail: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditError {
+    // An entry is missing, duplicated or out of order.
+    OutOfSequence { seq: u64, expected: u64 },
+    // The entry's contents no longer match its hash.
+    Altered { seq: u64 },
+    // The entry doesn't point at the hash of the one before it.
+    BrokenLink { seq: u64 },
+}
+
+impl AuditEvent {
+    pub fn new(action: AuditAction, detail: impl Into<String>) -> Self {
+        Self {
+            action,
+            subject: None,
+            detail: detail.into(),
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn subject(mut self, id: &str) -> Self {
+        self.subject = Some(id.to_string());
+        self
+    }
+
+    pub fn before<T: Serialize>(mut self, value: &T) -> Self {
+        self.before = serde_json::to_value(value).ok();
+        self
+    }
+
+    pub fn after<T: Serialize>(mut self, value: &T) -> Self {
+        self.after = serde_json::to_value(value).ok();
+        self
+    }
+}
+
+impl AuditEntry {
+    // Chains `event` onto `prev` (the last entry, `None` for an empty log).
+    pub fn seal(event: AuditEvent, actor: Option<&Employee>, prev: Option<&AuditEntry>) -> Self {
+        let mut entry = Self {
+            seq: prev.map_or(1, |p| p.seq + 1),
+            at: Utc::now(),
+            actor_id: actor.map(|e| e.id.clone()),
+            actor_name: actor.map(|e| e.name.clone()),
+            action: event.action,
+            subject: event.subject,
+            detail: event.detail,
+            before: event.before,
+            after: event.after,
+            prev_hash: prev.map_or_else(|| GENESIS_HASH.to_string(), |p| p.hash.clone()),
+            hash: String::new(),
+        };
+        entry.hash = entry.digest();
+        entry
+    }
+
+    // SHA-256 of the entry serialized with an empty `hash`, as lowercase hex.
+    pub fn digest(&self) -> String {
+        let unsealed = Self {
+            hash: String::new(),
+            ..self.clone()
+        };
+        let bytes = serde_json::to_vec(&unsealed).unwrap_or_default();
+        Sha256::digest(&bytes).iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
+
+// Checks the whole log from the first entry. Entries cut off the end leave
+// a valid shorter chain, so compare the last hash with one noted earlier
+// to detect that.
+pub fn verify_chain(entries: &[AuditEntry]) -> Result<(), AuditError> {
+    let mut prev_hash = GENESIS_HASH;
+    for (expected, entry) in (1..).zip(entries) {
+        if entry.seq != expected {
+            return Err(AuditError::OutOfSequence { seq: entry.seq, expected });
+        }
+        if entry.digest() != entry.hash {
+            return Err(AuditError::Altered { seq: entry.seq });
+        }
+        if entry.prev_hash != prev_hash {
+            return Err(AuditError::BrokenLink { seq: entry.seq });
+        }
+        prev_hash = &entry.hash;
+    }
+    Ok(())
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 34] = [
+        AuditAction::Login,
+        AuditAction::LoginFailed,
+        AuditAction::Logout,
+        AuditAction::OverrideApproved,
+        AuditAction::OverrideFailed,
+        AuditAction::ProductCreated,
+        AuditAction::ProductDeleted,
+        AuditAction::ProductEdited,
+        AuditAction::ProductArchived,
+        AuditAction::ProductRestored,
+        AuditAction::CatalogImported,
+        AuditAction::StockAdjusted,
+        AuditAction::CartLineRemoved,
+        AuditAction::CartCleared,
+        AuditAction::OrderHeld,
+        AuditAction::OrderRecalled,
+        AuditAction::HoldExpired,
+        AuditAction::PriceOverridden,
+        AuditAction::DiscountApplied,
+        AuditAction::DiscountRemoved,
+        AuditAction::OrderVoided,
+        AuditAction::OrderRefunded,
+        AuditAction::TipAdjusted,
+        AuditAction::DrawerOpened,
+        AuditAction::DrawerEntry,
+        AuditAction::DrawerClosed,
+        AuditAction::DayClosed,
+        AuditAction::EmployeeSaved,
+        AuditAction::PromotionSaved,
+        AuditAction::PromotionDeleted,
+        AuditAction::CategorySaved,
+        AuditAction::CategoryDeleted,
+        AuditAction::CustomerSaved,
+        AuditAction::TaxExemptionApplied,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            AuditAction::Login => "Sign In",
+            AuditAction::LoginFailed => "Failed Sign In",
+            AuditAction::Logout => "Sign Out",
+            AuditAction::OverrideApproved => "Override Approved",
+            AuditAction::OverrideFailed => "Failed Override",
+            AuditAction::ProductCreated => "Product Added",
+            AuditAction::ProductDeleted => "Product Deleted",
+            AuditAction::ProductEdited => "Product Edited",
+            AuditAction::ProductArchived => "Product Archived",
+            AuditAction::ProductRestored => "Product Restored",
+            AuditAction::CatalogImported => "Catalog Import",
+            AuditAction::StockAdjusted => "Stock Adjusted",
+            AuditAction::CartLineRemoved => "Cart Line Removed",
+            AuditAction::CartCleared => "Cart Cleared",
+            AuditAction::OrderHeld => "Order Held",
+            AuditAction::OrderRecalled => "Order Recalled",
+            AuditAction::HoldExpired => "Hold Expired",
+            AuditAction::PriceOverridden => "Price Override",
+            AuditAction::DiscountApplied => "Discount",
+            AuditAction::DiscountRemoved => "Discount Removed",
+            AuditAction::OrderVoided => "Void",
+            AuditAction::OrderRefunded => "Refund",
+            AuditAction::TipAdjusted => "Tip Adjusted",
+            AuditAction::DrawerOpened => "Drawer Opened",
+            AuditAction::DrawerEntry => "Drawer Entry",
+            AuditAction::DrawerClosed => "Drawer Closed",
+            AuditAction::DayClosed => "Day Closed",
+            AuditAction::EmployeeSaved => "Employee Changed",
+            AuditAction::PromotionSaved => "Promotion Changed",
+            AuditAction::PromotionDeleted => "Promotion Deleted",
+            AuditAction::CategorySaved => "Category Changed",
+            AuditAction::CategoryDeleted => "Category Deleted",
+            AuditAction::CustomerSaved => "Customer Saved",
+            AuditAction::TaxExemptionApplied => "Tax Exemption Applied",
+        }
+    }
+
+    pub fn from_label(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.as_str() == value)
+    }
+}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditError::OutOfSequence { seq, expected } => {
+                write!(f, "entry #{seq} found where #{expected} should be")
+            }
+            AuditError::Altered { seq } => write!(f, "entry #{seq} has been altered"),
+            AuditError::BrokenLink { seq } => {
+                write!(f, "entry #{seq} does not follow the entry before it")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
@@ -0,0 +1,174 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use super::modifier::ChosenOption;
+use super::money::{Money, Rate, Rounding};
+use super::product::Product;
+
+// A barcode prefix the store's scale or label printer uses for in-store
+// codes (GS1 prefix 2). Such an EAN-13 reads as
+// `PP IIIII VVVVV C`: prefix, item code, value and check digit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddedPrefix {
+    // Two digits, 20 to 29.
+    pub prefix: String,
+    pub kind: EmbeddedKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmbeddedKind {
+    // The value is the item's price in minor units.
+    Price,
+    // The value is the weight in grams, charged at the product's price per kg.
+    Weight,
+}
+
+// What a scan resolved to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scan {
+    // A SKU or a product barcode, looked up as is.
+    Code(String),
+    // A price or weight label printed in store.
+    Label(ScaleLabel),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleLabel {
+    // The whole barcode; identical labels share a cart line.
+    pub code: String,
+    // Prefix and item code, the first seven digits; products carry it as a barcode.
+    pub item_code: String,
+    pub kind: EmbeddedKind,
+    pub value: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanError {
+    Empty,
+    // All digits and of a barcode's length, but the last digit doesn't check out.
+    CheckDigit(String),
+}
+
+// Prefixes used when settings.json doesn't list any.
+pub fn default_embedded_prefixes() -> Vec<EmbeddedPrefix> {
+    [("20", EmbeddedKind::Price), ("21", EmbeddedKind::Weight), ("22", EmbeddedKind::Weight), ("23", EmbeddedKind::Price)]
+        .into_iter()
+        .map(|(prefix, kind)| EmbeddedPrefix { prefix: prefix.to_string(), kind })
+        .collect()
+}
+
+// EAN-8, UPC-A, EAN-13 or GTIN-14: these carry a GS1 check digit.
+fn is_gtin(code: &str) -> bool {
+    matches!(code.len(), 8 | 12 | 13 | 14) && code.bytes().all(|b| b.is_ascii_digit())
+}
+
+// GS1 check digit for the digits before it: weights 3 and 1 alternating
+// from the right.
+fn check_digit(body: &str) -> u32 {
+    let sum: u32 = body
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| (b - b'0') as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+// True unless `code` looks like a GTIN and fails its check digit. Shorter
+// codes and codes with letters aren't checked.
+pub fn barcode_is_valid(code: &str) -> bool {
+    if !is_gtin(code) {
+        return true;
+    }
+    let (body, last) = code.split_at(code.len() - 1);
+    check_digit(body) == (last.as_bytes()[0] - b'0') as u32
+}
+
+// Whether two barcodes are the same item: a UPC-A is the EAN-13 with a
+// leading zero.
+pub fn same_barcode(a: &str, b: &str) -> bool {
+    if is_gtin(a) && is_gtin(b) {
+        a.trim_start_matches('0') == b.trim_start_matches('0')
+    } else {
+        a == b
+    }
+}
+
+// Reads what the scanner typed.
+pub fn parse_scan(input: &str, prefixes: &[EmbeddedPrefix]) -> Result<Scan, ScanError> {
+    let code = input.trim();
+    if code.is_empty() {
+        return Err(ScanError::Empty);
+    }
+    if !barcode_is_valid(code) {
+        return Err(ScanError::CheckDigit(code.to_string()));
+    }
+    if code.len() == 13 && is_gtin(code) {
+        if let Some(rule) = prefixes.iter().find(|p| code.starts_with(&p.prefix)) {
+            return Ok(Scan::Label(ScaleLabel {
+                code: code.to_string(),
+                item_code: code[..7].to_string(),
+                kind: rule.kind,
+                value: code[7..12].parse().unwrap_or(0),
+            }));
+        }
+    }
+    Ok(Scan::Code(code.to_string()))
+}
+
+// The product on sale with this SKU (any case) or barcode.
+pub fn find_by_code<'a>(products: &'a [Product], code: &str) -> Option<&'a Product> {
+    products.iter().filter(|p| !p.archived).find(|p| {
+        p.sku.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(code)) || p.barcodes.iter().any(|b| same_barcode(b, code))
+    })
+}
+
+// The product on sale a label is for: one with the label's item code as a
+// barcode, or the full code with a zero value.
+pub fn find_by_label<'a>(products: &'a [Product], label: &ScaleLabel) -> Option<&'a Product> {
+    let blank = format!("{}00000", label.item_code);
+    products
+        .iter()
+        .filter(|p| !p.archived)
+        .find(|p| p.barcodes.iter().any(|b| *b == label.item_code || b.starts_with(&blank)))
+}
+
+impl ScaleLabel {
+    // What the item costs: the printed price, or the weight at the product's
+    // price per kg.
+    pub fn price_for(&self, product: &Product) -> Money {
+        match self.kind {
+            EmbeddedKind::Price => Money::new(self.value, product.price.currency()),
+            EmbeddedKind::Weight => product.price.apply_rate(Rate(self.value as u32 * 1000), Rounding::HalfUp),
+        }
+    }
+
+    // "0.512 kg" or "Label price".
+    pub fn describe(&self) -> String {
+        match self.kind {
+            EmbeddedKind::Price => "Label price".to_string(),
+            EmbeddedKind::Weight => format!("{}.{:03} kg", self.value / 1000, self.value % 1000),
+        }
+    }
+
+    // The label as the line's only option, so it shows on the cart and
+    // receipt and rescanning the same label adds to its line.
+    pub fn as_option(&self, product: &Product) -> ChosenOption {
+        ChosenOption {
+            option_id: self.code.clone(),
+            group: "Label".to_string(),
+            name: self.describe(),
+            price_delta: Money::zero(product.price.currency()),
+        }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::Empty => write!(f, "nothing was scanned"),
+            ScanError::CheckDigit(code) => write!(f, "barcode {code} failed its check digit; scan it again"),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
@@ -0,0 +1,182 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::product::Product;
+use super::tax::CategoryTaxClass;
+
+// A product category as the store defines it. Products point at it by id, so
+// renaming or recoloring a category never touches the products.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    // CSS color for badges and the product card edge.
+    pub color: String,
+    // Lower numbers are listed first; ties go by name.
+    pub sort_order: u32,
+    // Makes this a sub-category, e.g. Hot Drinks under Beverage.
+    pub parent_id: Option<String>,
+    // Tax class for products without their own. A sub-category without one
+    // uses its parent's.
+    pub tax_class: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CategoryError {
+    EmptyName,
+    DuplicateName(String),
+    // The parent is the category itself or one of its sub-categories.
+    ParentCycle,
+    // Still used by this many products or sub-categories.
+    InUse(usize),
+}
+
+// Ids of the categories that replaced the old fixed Food, Beverage, Retail
+// and Service; existing data is migrated onto them.
+const BUILT_IN: [(&str, &str, &str); 4] = [
+    ("food", "Food", "#FF6B6B"),
+    ("beverage", "Beverage", "#4ECDC4"),
+    ("retail", "Retail", "#95E1D3"),
+    ("service", "Service", "#FFA07A"),
+];
+
+// Shown for a product whose category has been removed.
+pub const UNCATEGORIZED: &str = "Uncategorized";
+const UNCATEGORIZED_COLOR: &str = "#A0AEC0";
+
+impl Category {
+    pub fn new(name: String, color: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            color,
+            sort_order: 0,
+            parent_id: None,
+            tax_class: None,
+        }
+    }
+}
+
+// The starting list, taking tax classes from the old per-category mapping.
+pub fn default_categories(classes: &[CategoryTaxClass]) -> Vec<Category> {
+    BUILT_IN
+        .iter()
+        .enumerate()
+        .map(|(i, (id, name, color))| Category {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: color.to_string(),
+            sort_order: i as u32,
+            parent_id: None,
+            tax_class: classes.iter().find(|c| c.category == *id).map(|c| c.class_id.clone()),
+        })
+        .collect()
+}
+
+pub fn find_category<'a>(categories: &'a [Category], id: &str) -> Option<&'a Category> {
+    categories.iter().find(|c| c.id == id)
+}
+
+// The category and its parents, nearest first. A broken parent chain ends the
+// walk instead of looping.
+pub fn category_lineage<'a>(categories: &'a [Category], id: &str) -> Vec<&'a Category> {
+    let mut chain: Vec<&Category> = Vec::new();
+    let mut next = find_category(categories, id);
+    while let Some(category) = next {
+        if chain.iter().any(|c| c.id == category.id) {
+            break;
+        }
+        chain.push(category);
+        next = category.parent_id.as_deref().and_then(|p| find_category(categories, p));
+    }
+    chain
+}
+
+// Whether category `id` is `ancestor` or one of its sub-categories.
+pub fn in_category(categories: &[Category], id: &str, ancestor: &str) -> bool {
+    id == ancestor || category_lineage(categories, id).iter().any(|c| c.id == ancestor)
+}
+
+pub fn category_name<'a>(categories: &'a [Category], id: &str) -> &'a str {
+    find_category(categories, id).map_or(UNCATEGORIZED, |c| c.name.as_str())
+}
+
+pub fn category_color<'a>(categories: &'a [Category], id: &str) -> &'a str {
+    find_category(categories, id).map_or(UNCATEGORIZED_COLOR, |c| c.color.as_str())
+}
+
+// Nearest tax class up the parent chain.
+pub fn category_tax_class<'a>(categories: &'a [Category], id: &str) -> Option<&'a str> {
+    category_lineage(categories, id).into_iter().find_map(|c| c.tax_class.as_deref())
+}
+
+// Every category in display order with its depth: each parent is followed
+// by its sub-categories. A category whose parent is gone is listed at the top.
+pub fn category_tree(categories: &[Category]) -> Vec<(usize, &Category)> {
+    fn add<'a>(categories: &'a [Category], parent: Option<&str>, depth: usize, out: &mut Vec<(usize, &'a Category)>) {
+        let mut level: Vec<&Category> = categories
+            .iter()
+            .filter(|c| match parent {
+                Some(id) => c.parent_id.as_deref() == Some(id),
+                None => match c.parent_id.as_deref() {
+                    Some(p) => find_category(categories, p).is_none(),
+                    None => true,
+                },
+            })
+            .filter(|c| !out.iter().any(|(_, seen)| seen.id == c.id))
+            .collect();
+        level.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.name.cmp(&b.name)));
+        for category in level {
+            out.push((depth, category));
+            add(categories, Some(&category.id), depth + 1, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    add(categories, None, 0, &mut out);
+    out
+}
+
+// Checks a new or edited category against the rest of the list.
+pub fn validate_category(categories: &[Category], category: &Category) -> Result<(), CategoryError> {
+    let name = category.name.trim();
+    if name.is_empty() {
+        return Err(CategoryError::EmptyName);
+    }
+    if categories
+        .iter()
+        .any(|c| c.id != category.id && c.name.trim().eq_ignore_ascii_case(name))
+    {
+        return Err(CategoryError::DuplicateName(name.to_string()));
+    }
+    if let Some(parent) = &category.parent_id {
+        if in_category(categories, parent, &category.id) {
+            return Err(CategoryError::ParentCycle);
+        }
+    }
+    Ok(())
+}
+
+// A category can only go once nothing points at it.
+pub fn check_category_removable(categories: &[Category], products: &[Product], id: &str) -> Result<(), CategoryError> {
+    let users = products.iter().filter(|p| p.category == id).count()
+        + categories.iter().filter(|c| c.parent_id.as_deref() == Some(id)).count();
+    if users > 0 {
+        Err(CategoryError::InUse(users))
+    } else {
+        Ok(())
+    }
+}
+
+impl fmt::Display for CategoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CategoryError::EmptyName => write!(f, "category name is required"),
+            CategoryError::DuplicateName(name) => write!(f, "a category named {name} already exists"),
+            CategoryError::ParentCycle => write!(f, "a category can't sit under itself or its own sub-categories"),
+            CategoryError::InUse(count) => write!(f, "still used by {count} products or sub-categories"),
+        }
+    }
+}
+
+impl std::error::Error for CategoryError {}
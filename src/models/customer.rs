@@ -0,0 +1,170 @@
+use std::fmt;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::money::{Currency, Money};
+use super::order::{Order, OrderStatus};
+
+// Lookups by phone need at least this many digits, so a partial number
+// doesn't pick the wrong person.
+const MIN_PHONE_DIGITS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Customer {
+    pub id: String,
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    // No tax is charged on their orders; needs a certificate on file.
+    #[serde(default)]
+    pub tax_exempt: bool,
+    #[serde(default)]
+    pub exemption_certificate: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// The customer as they were when attached to an order, so editing the
+// record later doesn't change past sales.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderCustomer {
+    pub id: String,
+    pub name: String,
+    // Certificate of a tax-exempt customer; `None` when tax is charged.
+    pub exemption_certificate: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomerError {
+    EmptyName,
+    InvalidEmail(String),
+    DuplicatePhone(String),
+    MissingCertificate,
+}
+
+// What a customer has bought, from the order history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CustomerStats {
+    pub orders: usize,
+    // After refunds.
+    pub spent: Money,
+    pub last_visit: Option<DateTime<Utc>>,
+}
+
+impl Customer {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            phone: None,
+            email: None,
+            notes: String::new(),
+            tax_exempt: false,
+            exemption_certificate: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    // Checks a new or edited customer against the others. Phone numbers
+    // compare by their digits.
+    pub fn validate(&self, customers: &[Customer]) -> Result<(), CustomerError> {
+        if self.name.trim().is_empty() {
+            return Err(CustomerError::EmptyName);
+        }
+        if let Some(email) = &self.email {
+            let valid = email
+                .split_once('@')
+                .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.') && !email.contains(' '));
+            if !valid {
+                return Err(CustomerError::InvalidEmail(email.clone()));
+            }
+        }
+        if let Some(phone) = &self.phone {
+            let digits = phone_digits(phone);
+            let taken = customers
+                .iter()
+                .filter(|c| c.id != self.id)
+                .any(|c| c.phone.as_deref().is_some_and(|p| phone_digits(p) == digits));
+            if taken {
+                return Err(CustomerError::DuplicatePhone(phone.clone()));
+            }
+        }
+        let has_certificate = self.exemption_certificate.as_deref().is_some_and(|c| !c.trim().is_empty());
+        if self.tax_exempt && !has_certificate {
+            return Err(CustomerError::MissingCertificate);
+        }
+        Ok(())
+    }
+
+    pub fn for_order(&self) -> OrderCustomer {
+        OrderCustomer {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            exemption_certificate: self.exemption_certificate.clone().filter(|_| self.tax_exempt),
+        }
+    }
+}
+
+impl OrderCustomer {
+    pub fn is_tax_exempt(&self) -> bool {
+        self.exemption_certificate.is_some()
+    }
+}
+
+fn phone_digits(phone: &str) -> String {
+    phone.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+// Customers whose phone ends with the digits typed, or whose name or email
+// contains the text; phone matches first.
+pub fn find_customers<'a>(customers: &'a [Customer], query: &str) -> Vec<&'a Customer> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let digits = phone_digits(query);
+    let text = query.to_lowercase();
+    let by_phone = |c: &Customer| {
+        digits.len() >= MIN_PHONE_DIGITS && c.phone.as_deref().is_some_and(|p| phone_digits(p).ends_with(&digits))
+    };
+    let by_text = |c: &Customer| {
+        c.name.to_lowercase().contains(&text) || c.email.as_deref().is_some_and(|e| e.to_lowercase().contains(&text))
+    };
+    let mut found: Vec<&Customer> = customers.iter().filter(|c| by_phone(c)).collect();
+    found.extend(customers.iter().filter(|c| !by_phone(c) && by_text(c)));
+    found
+}
+
+// The customer's completed and voided orders, newest first.
+pub fn purchase_history<'a>(orders: &'a [Order], customer_id: &str) -> Vec<&'a Order> {
+    orders
+        .iter()
+        .rev()
+        .filter(|o| o.status != OrderStatus::Pending)
+        .filter(|o| o.customer.as_ref().is_some_and(|c| c.id == customer_id))
+        .collect()
+}
+
+pub fn customer_stats(orders: &[Order], customer_id: &str, currency: Currency) -> CustomerStats {
+    let history = purchase_history(orders, customer_id);
+    let sales: Vec<&&Order> = history.iter().filter(|o| o.status != OrderStatus::Cancelled).collect();
+    CustomerStats {
+        orders: sales.len(),
+        spent: sales.iter().fold(Money::zero(currency), |sum, o| sum + o.net_total()),
+        last_visit: sales.iter().map(|o| o.paid_at().unwrap_or(o.created_at)).max(),
+    }
+}
+
+impl fmt::Display for CustomerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomerError::EmptyName => write!(f, "customer name is required"),
+            CustomerError::InvalidEmail(email) => write!(f, "{email} is not an email address"),
+            CustomerError::DuplicatePhone(phone) => write!(f, "{phone} already belongs to another customer"),
+            CustomerError::MissingCertificate => write!(f, "a tax-exempt customer needs an exemption certificate id"),
+        }
+    }
+}
+
+impl std::error::Error for CustomerError {}
@@ -0,0 +1,284 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::money::{Money, Rate, Rounding};
+use super::order::OrderItem;
+use super::promotion::{Promotion, PromotionRule};
+
+// A discount keyed in at the till, on one line or the whole order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Discount {
+    pub id: String,
+    pub kind: DiscountKind,
+    pub target: DiscountTarget,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DiscountKind {
+    Percent(Rate),
+    // Off the line or order as a whole, not per unit.
+    Amount(Money),
+    // Sells each unit of a line at this price.
+    FixedPrice(Money),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DiscountTarget {
+    Order,
+    // Id of the order line.
+    Line(String),
+}
+
+// A discount as it was taken off a line or the order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedDiscount {
+    pub name: String,
+    pub source: DiscountSource,
+    // What came off. A price override above the catalog price is negative.
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DiscountSource {
+    // Id of the `Discount` on the order.
+    Manual(String),
+    // Id of the `Promotion`.
+    Promotion(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscountError {
+    NotPending,
+    InvalidAmount,
+    UnknownItem(String),
+    // A fixed price only makes sense for a single line.
+    FixedPriceOnOrder,
+    UnknownCoupon(String),
+    CouponAlreadyApplied(String),
+    // Gift cards are sold at face value.
+    GiftCardLine,
+    // Would take the total below what was already paid.
+    PaymentsTaken,
+}
+
+const FULL: Rate = Rate(1_000_000);
+
+impl Discount {
+    pub fn new(kind: DiscountKind, target: DiscountTarget) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            kind,
+            target,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), DiscountError> {
+        match (self.kind, &self.target) {
+            (DiscountKind::FixedPrice(_), DiscountTarget::Order) => Err(DiscountError::FixedPriceOnOrder),
+            (DiscountKind::Percent(rate), _) if rate.0 == 0 || rate > FULL => Err(DiscountError::InvalidAmount),
+            (DiscountKind::Amount(amount), _) if amount.minor() <= 0 => Err(DiscountError::InvalidAmount),
+            (DiscountKind::FixedPrice(price), _) if price.minor() < 0 => Err(DiscountError::InvalidAmount),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl DiscountKind {
+    pub fn label(&self) -> String {
+        match self {
+            DiscountKind::Percent(rate) => format!("{rate} off"),
+            DiscountKind::Amount(amount) => format!("{amount} off"),
+            DiscountKind::FixedPrice(price) => format!("Price override {price}"),
+        }
+    }
+}
+
+impl AppliedDiscount {
+    // Id of the manual discount this came from, which the cashier may remove.
+    pub fn manual_id(&self) -> Option<&str> {
+        match &self.source {
+            DiscountSource::Manual(id) => Some(id),
+            DiscountSource::Promotion(_) => None,
+        }
+    }
+}
+
+// Works out every discount on `items` from scratch and returns the
+// order-level ones. In turn:
+//
+// 1. Manual line discounts, in the order they were keyed in. A line with one
+//    takes no promotions.
+// 2. Line promotions by priority. One that doesn't stack skips lines that
+//    already have a discount, and nothing else is added to a line it took.
+// 3. Order discounts, manual first, then promotions by priority, each on what
+//    is left and spread over the lines in proportion to their amounts so tax
+//    is charged on the discounted price. A promotion that doesn't stack
+//    skips an order with another order discount and ends the list.
+//
+// Gift card lines take no discounts at all.
+//
+// `promotions` must be the ones in effect, sorted by priority.
+pub(crate) fn apply_discounts(
+    items: &mut [OrderItem],
+    manual: &[Discount],
+    promotions: &[&Promotion],
+) -> Vec<AppliedDiscount> {
+    for item in items.iter_mut() {
+        item.discounts.clear();
+        item.order_discount = Money::zero(item.price.currency());
+    }
+    let mut locked = vec![false; items.len()];
+
+    for discount in manual {
+        let DiscountTarget::Line(line_id) = &discount.target else {
+            continue;
+        };
+        let Some(pos) = items.iter().position(|i| &i.line_id == line_id) else {
+            continue;
+        };
+        let item = &mut items[pos];
+        let remaining = item.net_amount();
+        let amount = match discount.kind {
+            DiscountKind::Percent(rate) => remaining.apply_rate(rate, Rounding::HalfUp),
+            DiscountKind::Amount(amount) => cap(amount, remaining),
+            DiscountKind::FixedPrice(price) => remaining - price * item.quantity,
+        };
+        item.discounts.push(AppliedDiscount {
+            name: discount.kind.label(),
+            source: DiscountSource::Manual(discount.id.clone()),
+            amount,
+        });
+        locked[pos] = true;
+    }
+
+    for promotion in promotions.iter().filter(|p| !p.rule.is_order_rule()) {
+        let eligible: Vec<usize> = (0..items.len())
+            .filter(|&i| !locked[i] && items[i].gift_card.is_none())
+            .filter(|&i| promotion.stackable || items[i].discounts.is_empty())
+            .filter(|&i| promotion.rule.applies_to(&items[i]))
+            .collect();
+        for (pos, amount) in promotion.rule.line_discounts(items, &eligible) {
+            let item = &mut items[pos];
+            let amount = cap(amount, item.net_amount());
+            if amount.minor() <= 0 {
+                continue;
+            }
+            item.discounts.push(AppliedDiscount {
+                name: promotion.name.clone(),
+                source: DiscountSource::Promotion(promotion.id.clone()),
+                amount,
+            });
+            if !promotion.stackable {
+                locked[pos] = true;
+            }
+        }
+    }
+
+    let mut applied: Vec<AppliedDiscount> = Vec::new();
+    for discount in manual.iter().filter(|d| d.target == DiscountTarget::Order) {
+        let remaining = net_total(items);
+        let amount = match discount.kind {
+            DiscountKind::Percent(rate) => remaining.apply_rate(rate, Rounding::HalfUp),
+            DiscountKind::Amount(amount) | DiscountKind::FixedPrice(amount) => cap(amount, remaining),
+        };
+        spread(items, amount);
+        applied.push(AppliedDiscount {
+            name: discount.kind.label(),
+            source: DiscountSource::Manual(discount.id.clone()),
+            amount,
+        });
+    }
+    for promotion in promotions.iter() {
+        let PromotionRule::OrderOff { kind, min_subtotal } = &promotion.rule else {
+            continue;
+        };
+        if !promotion.stackable && !applied.is_empty() {
+            continue;
+        }
+        let remaining = net_total(items);
+        if items.is_empty() || min_subtotal.is_some_and(|min| remaining < min) {
+            continue;
+        }
+        let amount = match kind {
+            DiscountKind::Percent(rate) => remaining.apply_rate(*rate, Rounding::HalfUp),
+            DiscountKind::Amount(amount) | DiscountKind::FixedPrice(amount) => cap(*amount, remaining),
+        };
+        if amount.minor() <= 0 {
+            continue;
+        }
+        spread(items, amount);
+        applied.push(AppliedDiscount {
+            name: promotion.name.clone(),
+            source: DiscountSource::Promotion(promotion.id.clone()),
+            amount,
+        });
+        if !promotion.stackable {
+            break;
+        }
+    }
+    applied
+}
+
+// Shares a non-negative `amount` in proportion to `weights`, exactly: the
+// remainder goes one minor unit at a time to the largest fractional shares.
+pub(crate) fn allocate(amount: Money, weights: &[Money]) -> Vec<Money> {
+    let currency = amount.currency();
+    let total: i128 = weights.iter().map(|w| w.minor().max(0) as i128).sum();
+    if total == 0 {
+        return weights.iter().map(|_| Money::zero(currency)).collect();
+    }
+    let exact: Vec<(i128, i128)> = weights
+        .iter()
+        .map(|w| {
+            let scaled = amount.minor() as i128 * w.minor().max(0) as i128;
+            (scaled / total, scaled % total)
+        })
+        .collect();
+    let mut shares: Vec<i128> = exact.iter().map(|(q, _)| *q).collect();
+    let leftover = amount.minor() as i128 - shares.iter().sum::<i128>();
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|&a, &b| exact[b].1.cmp(&exact[a].1));
+    for &i in order.iter().take(leftover as usize) {
+        shares[i] += 1;
+    }
+    shares.into_iter().map(|m| Money::new(m as i64, currency)).collect()
+}
+
+fn spread(items: &mut [OrderItem], amount: Money) {
+    let weights: Vec<Money> = items.iter().map(discountable).collect();
+    for (item, share) in items.iter_mut().zip(allocate(amount, &weights)) {
+        item.order_discount += share;
+    }
+}
+
+fn net_total(items: &[OrderItem]) -> Money {
+    items.iter().map(discountable).sum()
+}
+
+fn discountable(item: &OrderItem) -> Money {
+    match item.gift_card {
+        Some(_) => Money::zero(item.price.currency()),
+        None => item.net_amount(),
+    }
+}
+
+fn cap(amount: Money, max: Money) -> Money {
+    if amount > max { max } else { amount }
+}
+
+impl fmt::Display for DiscountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscountError::NotPending => write!(f, "order is no longer open"),
+            DiscountError::InvalidAmount => write!(f, "discount must be more than zero and at most 100%"),
+            DiscountError::UnknownItem(id) => write!(f, "no line {id} on this order"),
+            DiscountError::FixedPriceOnOrder => write!(f, "a price override needs a line"),
+            DiscountError::UnknownCoupon(code) => write!(f, "coupon {code} is not valid"),
+            DiscountError::CouponAlreadyApplied(code) => write!(f, "coupon {code} is already on this order"),
+            DiscountError::GiftCardLine => write!(f, "gift cards can't be discounted"),
+            DiscountError::PaymentsTaken => write!(f, "payments were already taken; remove them or void the order"),
+        }
+    }
+}
+
+impl std::error::Error for DiscountError {}
@@ -0,0 +1,238 @@
+use std::fmt;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::money::{Currency, Money};
+use super::order::Order;
+use super::payment::{Payment, PaymentMethod};
+
+// One cash drawer from the opening float to the count at close. Orders
+// carry the id of the session they were rung in; refunds and voids are
+// paid out of whichever drawer is open when they are made.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrawerSession {
+    pub id: String,
+    pub opening_float: Money,
+    pub entries: Vec<DrawerEntry>,
+    pub opened_at: DateTime<Utc>,
+    pub close: Option<DrawerClose>,
+}
+
+// Cash put in or taken out other than through a sale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrawerEntry {
+    pub id: String,
+    pub kind: DrawerEntryKind,
+    // Always positive; `kind` says which way it went.
+    pub amount: Money,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DrawerEntryKind {
+    // Cash added, e.g. extra change from the safe.
+    PaidIn,
+    // Cash paid out for store expenses, e.g. a delivery tip.
+    PaidOut,
+    // Excess cash moved to the safe mid-shift.
+    Drop,
+}
+
+// The count at close and what the drawer should have held. Figures are
+// fixed when the drawer closes so later changes to history can't move them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrawerClose {
+    pub counts: Vec<DenominationCount>,
+    pub summary: CashSummary,
+    pub counted: Money,
+    // Counted minus expected: positive is over, negative is short.
+    pub variance: Money,
+    pub closed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DenominationCount {
+    pub value: Money,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CashSummary {
+    pub opening_float: Money,
+    pub cash_sales: Money,
+    // Tips left in cash, on top of the sales.
+    #[serde(default)]
+    pub cash_tips: Money,
+    pub cash_refunds: Money,
+    pub paid_in: Money,
+    pub paid_out: Money,
+    pub drops: Money,
+    pub expected: Money,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawerError {
+    AlreadyClosed,
+    InvalidAmount,
+}
+
+impl DrawerSession {
+    pub fn open(opening_float: Money) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            opening_float,
+            entries: Vec::new(),
+            opened_at: Utc::now(),
+            close: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.close.is_none()
+    }
+
+    pub fn add_entry(
+        &mut self,
+        kind: DrawerEntryKind,
+        amount: Money,
+        note: Option<String>,
+    ) -> Result<(), DrawerError> {
+        if !self.is_open() {
+            return Err(DrawerError::AlreadyClosed);
+        }
+        if amount.minor() <= 0 {
+            return Err(DrawerError::InvalidAmount);
+        }
+        self.entries.push(DrawerEntry {
+            id: Uuid::new_v4().to_string(),
+            kind,
+            amount,
+            note,
+            created_at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    // What should be in the drawer now (or at close, once closed).
+    pub fn summary(&self, orders: &[Order]) -> CashSummary {
+        if let Some(close) = &self.close {
+            return close.summary.clone();
+        }
+        let currency = self.opening_float.currency();
+        let end = Utc::now();
+        let in_session = |at: DateTime<Utc>| at >= self.opened_at && at <= end;
+
+        let cash_payments = || {
+            orders
+                .iter()
+                .filter(|o| o.session_id.as_deref() == Some(self.id.as_str()))
+                .flat_map(|o| &o.payments)
+                .filter(|p| p.method == PaymentMethod::Cash)
+        };
+        let cash_tips = cash_payments().fold(Money::zero(currency), |sum, p| sum + p.tip_amount());
+        let cash_sales = cash_payments().fold(Money::zero(currency), |sum, p| sum + cash_in(p)) - cash_tips;
+        // Money handed back: cash refunds, and the cash taken on orders
+        // voided while this drawer was open.
+        let refunded = orders
+            .iter()
+            .flat_map(|o| &o.refunds)
+            .filter(|r| in_session(r.created_at))
+            .fold(Money::zero(currency), |sum, r| sum + r.amount_to(&PaymentMethod::Cash));
+        let voided = orders
+            .iter()
+            .filter(|o| o.void.as_ref().is_some_and(|v| in_session(v.voided_at)))
+            .flat_map(|o| &o.payments)
+            .fold(Money::zero(currency), |sum, p| sum + cash_in(p));
+
+        let entries = |kind: DrawerEntryKind| {
+            self.entries
+                .iter()
+                .filter(|e| e.kind == kind)
+                .fold(Money::zero(currency), |sum, e| sum + e.amount)
+        };
+        let paid_in = entries(DrawerEntryKind::PaidIn);
+        let paid_out = entries(DrawerEntryKind::PaidOut);
+        let drops = entries(DrawerEntryKind::Drop);
+        let cash_refunds = refunded + voided;
+
+        CashSummary {
+            opening_float: self.opening_float,
+            cash_sales,
+            cash_tips,
+            cash_refunds,
+            paid_in,
+            paid_out,
+            drops,
+            expected: self.opening_float + cash_sales + cash_tips - cash_refunds + paid_in - paid_out - drops,
+        }
+    }
+
+    // Blind close: the count is taken first and only then compared with
+    // what the drawer should hold.
+    pub fn close(
+        &mut self,
+        counts: Vec<DenominationCount>,
+        orders: &[Order],
+    ) -> Result<&DrawerClose, DrawerError> {
+        if !self.is_open() {
+            return Err(DrawerError::AlreadyClosed);
+        }
+        let summary = self.summary(orders);
+        let counted = counted_total(&counts, self.opening_float.currency());
+        let variance = counted - summary.expected;
+        Ok(self.close.insert(DrawerClose {
+            counts,
+            summary,
+            counted,
+            variance,
+            closed_at: Utc::now(),
+        }))
+    }
+}
+
+impl DrawerEntryKind {
+    pub const ALL: [DrawerEntryKind; 3] = [
+        DrawerEntryKind::PaidIn,
+        DrawerEntryKind::PaidOut,
+        DrawerEntryKind::Drop,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            DrawerEntryKind::PaidIn => "Paid In",
+            DrawerEntryKind::PaidOut => "Paid Out",
+            DrawerEntryKind::Drop => "Cash Drop",
+        }
+    }
+
+    pub fn from_label(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == value)
+    }
+}
+
+pub fn counted_total(counts: &[DenominationCount], currency: Currency) -> Money {
+    counts
+        .iter()
+        .fold(Money::zero(currency), |sum, c| sum + c.value * c.count)
+}
+
+// Cash that went into the drawer for a payment: the tender less change,
+// which includes any cash rounding and tip.
+fn cash_in(payment: &Payment) -> Money {
+    if payment.method != PaymentMethod::Cash {
+        return Money::zero(payment.amount.currency());
+    }
+    payment.amount + payment.rounding.unwrap_or(Money::zero(payment.amount.currency())) + payment.tip_amount()
+}
+
+impl fmt::Display for DrawerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawerError::AlreadyClosed => write!(f, "drawer session is already closed"),
+            DrawerError::InvalidAmount => write!(f, "amount must be greater than zero"),
+        }
+    }
+}
+
+impl std::error::Error for DrawerError {}
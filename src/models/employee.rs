@@ -0,0 +1,170 @@
+use std::fmt;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// Someone who can unlock the terminal. The PIN is only ever kept as an
+// Argon2 hash in PHC string form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Employee {
+    pub id: String,
+    pub name: String,
+    pub role: Role,
+    pub pin_hash: String,
+    // Deactivated employees stay on file so past orders still name them.
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// Ordered from least to most trusted; each role can do everything the ones
+// below it can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Role {
+    Cashier,
+    Supervisor,
+    Manager,
+    Admin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Permission {
+    ManageProducts,
+    ArchiveProducts,
+    PriceOverride,
+    Discount,
+    Refund,
+    Void,
+    ViewReports,
+    CloseDay,
+    ChangeSettings,
+    ManagePromotions,
+    ViewAuditLog,
+    ManageEmployees,
+    // Marking customers tax exempt.
+    TaxExemptions,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmployeeError {
+    InvalidPin,
+    EmptyName,
+    Hash(String),
+}
+
+// PINs are 4 to 8 digits.
+const PIN_LENGTH: std::ops::RangeInclusive<usize> = 4..=8;
+
+impl Employee {
+    pub fn new(name: String, role: Role, pin: &str) -> Result<Self, EmployeeError> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(EmployeeError::EmptyName);
+        }
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            role,
+            pin_hash: hash_pin(pin)?,
+            active: true,
+            created_at: Utc::now(),
+        })
+    }
+
+    pub fn set_pin(&mut self, pin: &str) -> Result<(), EmployeeError> {
+        self.pin_hash = hash_pin(pin)?;
+        Ok(())
+    }
+
+    pub fn verify_pin(&self, pin: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(&self.pin_hash) else {
+            return false;
+        };
+        self.active && Argon2::default().verify_password(pin.as_bytes(), &hash).is_ok()
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        self.active && self.role >= permission.min_role()
+    }
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Cashier, Role::Supervisor, Role::Manager, Role::Admin];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Role::Cashier => "Cashier",
+            Role::Supervisor => "Supervisor",
+            Role::Manager => "Manager",
+            Role::Admin => "Admin",
+        }
+    }
+
+    pub fn from_label(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == value)
+    }
+}
+
+impl Permission {
+    // The least trusted role allowed to do this without an override.
+    pub fn min_role(&self) -> Role {
+        match self {
+            Permission::PriceOverride
+            | Permission::Discount
+            | Permission::Refund
+            | Permission::Void
+            | Permission::ManageProducts
+            | Permission::ViewReports
+            | Permission::TaxExemptions => Role::Supervisor,
+            Permission::ArchiveProducts
+            | Permission::CloseDay
+            | Permission::ChangeSettings
+            | Permission::ManagePromotions
+            | Permission::ViewAuditLog => Role::Manager,
+            Permission::ManageEmployees => Role::Admin,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Permission::ManageProducts => "manage products",
+            Permission::ArchiveProducts => "archive products",
+            Permission::PriceOverride => "override prices",
+            Permission::Discount => "give discounts",
+            Permission::Refund => "issue refunds",
+            Permission::Void => "void orders",
+            Permission::ViewReports => "view reports",
+            Permission::CloseDay => "close the day",
+            Permission::ChangeSettings => "change settings",
+            Permission::ManagePromotions => "manage promotions",
+            Permission::ViewAuditLog => "view the audit log",
+            Permission::ManageEmployees => "manage employees",
+            Permission::TaxExemptions => "grant tax exemptions",
+        }
+    }
+}
+
+fn hash_pin(pin: &str) -> Result<String, EmployeeError> {
+    if !PIN_LENGTH.contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(EmployeeError::InvalidPin);
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| EmployeeError::Hash(e.to_string()))
+}
+
+impl fmt::Display for EmployeeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmployeeError::InvalidPin => write!(f, "PIN must be 4 to 8 digits"),
+            EmployeeError::EmptyName => write!(f, "name is required"),
+            EmployeeError::Hash(e) => write!(f, "could not hash PIN: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for EmployeeError {}
@@ -0,0 +1,434 @@
+use std::fmt;
+use chrono::{DateTime, Months, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::money::{Currency, Money};
+use super::order::Order;
+use super::refund::Refund;
+
+// Card numbers are this many digits; a number typed or scanned may have
+// spaces or dashes.
+const NUMBER_DIGITS: usize = 16;
+
+// Limits for gift cards and store credit; part of `settings.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GiftCardConfig {
+    // A gift card expires this long after it was last loaded; 0 never.
+    pub expiry_months: u32,
+    // Store credit expires this long after it was last issued; 0 never.
+    pub store_credit_expiry_months: u32,
+    // Most a card may hold.
+    pub max_balance: Money,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CardKind {
+    GiftCard,
+    StoreCredit,
+}
+
+// A stored-value account. Its balance is the sum of its ledger entries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GiftCard {
+    pub id: String,
+    pub number: String,
+    pub kind: CardKind,
+    // Store credit belongs to the customer it was issued to, if any.
+    pub customer_id: Option<String>,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CardEntryKind {
+    // Sold or reloaded at the till.
+    Loaded,
+    // Store credit given for a refund.
+    Credited,
+    Redeemed,
+    // Money paid from the card that a refund or void put back.
+    Returned,
+    // A load taken back by voiding the sale.
+    Reversed,
+}
+
+// One entry in a card's ledger. `amount` is the signed change and `balance`
+// the card's balance right after it was applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardEntry {
+    pub id: String,
+    pub card_id: String,
+    pub kind: CardEntryKind,
+    pub amount: Money,
+    pub balance: Money,
+    pub order_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GiftCardError {
+    InvalidNumber(String),
+    UnknownCard(String),
+    WrongKind(CardKind),
+    Expired(DateTime<Utc>),
+    // The most that can still be loaded.
+    OverLimit(Money),
+    NothingToLoad,
+}
+
+impl Default for GiftCardConfig {
+    fn default() -> Self {
+        Self {
+            expiry_months: 60,
+            store_credit_expiry_months: 12,
+            max_balance: Money::new(50000, Currency::default()),
+        }
+    }
+}
+
+impl GiftCardConfig {
+    fn expiry(&self, kind: CardKind, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let months = match kind {
+            CardKind::GiftCard => self.expiry_months,
+            CardKind::StoreCredit => self.store_credit_expiry_months,
+        };
+        if months == 0 {
+            return None;
+        }
+        from.checked_add_months(Months::new(months))
+    }
+}
+
+impl GiftCard {
+    pub fn new(number: String, kind: CardKind, config: &GiftCardConfig, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            number,
+            kind,
+            customer_id: None,
+            issued_at: now,
+            expires_at: config.expiry(kind, now),
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    // "•••• 1234"
+    pub fn masked(&self) -> String {
+        mask_number(&self.number)
+    }
+}
+
+impl CardKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            CardKind::GiftCard => "Gift Card",
+            CardKind::StoreCredit => "Store Credit",
+        }
+    }
+}
+
+impl CardEntryKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            CardEntryKind::Loaded => "Loaded",
+            CardEntryKind::Credited => "Credited",
+            CardEntryKind::Redeemed => "Redeemed",
+            CardEntryKind::Returned => "Returned",
+            CardEntryKind::Reversed => "Reversed",
+        }
+    }
+}
+
+pub fn mask_number(number: &str) -> String {
+    format!("•••• {}", &number[number.len().saturating_sub(4)..])
+}
+
+// "1234 5678 9012 3456", for the receipt that is the customer's only copy.
+pub fn group_number(number: &str) -> String {
+    let chars: Vec<char> = number.chars().collect();
+    chars.chunks(4).map(|c| c.iter().collect::<String>()).collect::<Vec<_>>().join(" ")
+}
+
+// The digits of a typed or scanned number, if it has the right length.
+pub fn parse_card_number(input: &str) -> Result<String, GiftCardError> {
+    let digits: String = input.chars().filter(|c| !matches!(c, ' ' | '-')).collect();
+    if digits.len() != NUMBER_DIGITS || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(GiftCardError::InvalidNumber(input.trim().to_string()));
+    }
+    Ok(digits)
+}
+
+// A fresh random number for a card issued without one.
+pub fn new_card_number(cards: &[GiftCard]) -> String {
+    loop {
+        let bytes = Uuid::new_v4();
+        let number: String = bytes
+            .as_bytes()
+            .iter()
+            .take(NUMBER_DIGITS)
+            .map(|b| char::from(b'0' + b % 10))
+            .collect();
+        if !number.starts_with('0') && !cards.iter().any(|c| c.number == number) {
+            return number;
+        }
+    }
+}
+
+pub fn find_card<'a>(cards: &'a [GiftCard], input: &str) -> Option<&'a GiftCard> {
+    let number = parse_card_number(input).ok()?;
+    cards.iter().find(|c| c.number == number)
+}
+
+pub fn card_balance(ledger: &[CardEntry], card_id: &str) -> Money {
+    ledger
+        .iter()
+        .filter(|e| e.card_id == card_id)
+        .fold(Money::zero(Currency::default()), |sum, e| sum + e.amount)
+}
+
+// The card's entries, newest first.
+pub fn card_history<'a>(ledger: &'a [CardEntry], card_id: &str) -> Vec<&'a CardEntry> {
+    ledger.iter().rev().filter(|e| e.card_id == card_id).collect()
+}
+
+// Store credit held by a customer, newest first.
+pub fn customer_credit<'a>(cards: &'a [GiftCard], customer_id: &str) -> Vec<&'a GiftCard> {
+    cards
+        .iter()
+        .rev()
+        .filter(|c| c.kind == CardKind::StoreCredit && c.customer_id.as_deref() == Some(customer_id))
+        .collect()
+}
+
+// What the order has already tendered from card `number`.
+pub fn card_tendered(order: &Order, number: &str) -> Money {
+    order
+        .payments
+        .iter()
+        .filter(|p| p.gift_card.as_deref() == Some(number))
+        .fold(Money::zero(order.currency), |sum, p| sum + p.amount)
+}
+
+// The card behind `input` and what it can still pay towards `order`, for a
+// tender of `kind`.
+pub fn redeemable<'a>(
+    cards: &'a [GiftCard],
+    ledger: &[CardEntry],
+    input: &str,
+    kind: CardKind,
+    order: &Order,
+    now: DateTime<Utc>,
+) -> Result<(&'a GiftCard, Money), GiftCardError> {
+    let number = parse_card_number(input)?;
+    let card = cards
+        .iter()
+        .find(|c| c.number == number)
+        .ok_or(GiftCardError::UnknownCard(number.clone()))?;
+    if card.kind != kind {
+        return Err(GiftCardError::WrongKind(card.kind));
+    }
+    if let Some(at) = card.expires_at.filter(|_| card.is_expired(now)) {
+        return Err(GiftCardError::Expired(at));
+    }
+    let available = card_balance(ledger, &card.id) - card_tendered(order, &number);
+    Ok((card, available))
+}
+
+// Checks that `amount` can go on the gift card `input`, a new number or an
+// existing gift card, and returns the card's number.
+pub fn check_load(
+    cards: &[GiftCard],
+    ledger: &[CardEntry],
+    config: &GiftCardConfig,
+    input: &str,
+    amount: Money,
+) -> Result<String, GiftCardError> {
+    if amount.minor() <= 0 {
+        return Err(GiftCardError::NothingToLoad);
+    }
+    let number = parse_card_number(input)?;
+    let balance = match cards.iter().find(|c| c.number == number) {
+        Some(card) if card.kind != CardKind::GiftCard => return Err(GiftCardError::WrongKind(card.kind)),
+        Some(card) => card_balance(ledger, &card.id),
+        None => Money::zero(amount.currency()),
+    };
+    if balance + amount > config.max_balance {
+        let room = config.max_balance - balance;
+        return Err(GiftCardError::OverLimit(if room.minor() > 0 { room } else { Money::zero(amount.currency()) }));
+    }
+    Ok(number)
+}
+
+// Loads the gift cards sold on a paid order, creating new ones, and takes
+// the card tenders off their cards.
+pub fn post_sale_cards(
+    cards: &mut Vec<GiftCard>,
+    ledger: &[CardEntry],
+    config: &GiftCardConfig,
+    order: &Order,
+    now: DateTime<Utc>,
+) -> Vec<CardEntry> {
+    let mut posting = Posting::new(ledger, &order.id);
+    for item in &order.items {
+        let Some(number) = &item.gift_card else {
+            continue;
+        };
+        let pos = match cards.iter().position(|c| &c.number == number) {
+            Some(pos) => pos,
+            None => {
+                cards.push(GiftCard::new(number.clone(), CardKind::GiftCard, config, now));
+                cards.len() - 1
+            }
+        };
+        // Loading again starts the expiry over.
+        cards[pos].expires_at = config.expiry(CardKind::GiftCard, now);
+        posting.post(&cards[pos].id, CardEntryKind::Loaded, item.net_amount());
+    }
+    for payment in &order.payments {
+        let Some(card) = payment.gift_card.as_ref().and_then(|n| cards.iter().find(|c| &c.number == n)) else {
+            continue;
+        };
+        posting.post(&card.id, CardEntryKind::Redeemed, -payment.amount);
+    }
+    posting.entries
+}
+
+// Puts each part of a refund paid from a card back on that card.
+pub fn post_refund_cards(cards: &[GiftCard], ledger: &[CardEntry], order: &Order, refund: &Refund) -> Vec<CardEntry> {
+    let mut posting = Posting::new(ledger, &order.id);
+    for tender in &refund.tenders {
+        let payment = order.payments.iter().find(|p| Some(&p.id) == tender.payment_id.as_ref());
+        let card = payment
+            .and_then(|p| p.gift_card.as_ref())
+            .and_then(|n| cards.iter().find(|c| &c.number == n));
+        if let Some(card) = card {
+            posting.post(&card.id, CardEntryKind::Returned, tender.amount);
+        }
+    }
+    posting.entries
+}
+
+// Adds `amount` of store credit for a refund on `order`: onto the
+// customer's newest unexpired credit, otherwise onto a new one. Returns the
+// card's number with the entry.
+pub fn issue_store_credit(
+    cards: &mut Vec<GiftCard>,
+    ledger: &[CardEntry],
+    config: &GiftCardConfig,
+    order: &Order,
+    amount: Money,
+    now: DateTime<Utc>,
+) -> (String, Vec<CardEntry>) {
+    let customer_id = order.customer.as_ref().map(|c| c.id.clone());
+    let existing = customer_id
+        .as_deref()
+        .and_then(|id| customer_credit(cards, id).into_iter().find(|c| !c.is_expired(now)))
+        .map(|c| c.id.clone());
+    let pos = match existing.and_then(|id| cards.iter().position(|c| c.id == id)) {
+        Some(pos) => pos,
+        None => {
+            let mut card = GiftCard::new(new_card_number(cards), CardKind::StoreCredit, config, now);
+            card.customer_id = customer_id;
+            cards.push(card);
+            cards.len() - 1
+        }
+    };
+    cards[pos].expires_at = config.expiry(CardKind::StoreCredit, now);
+    let mut posting = Posting::new(ledger, &order.id);
+    posting.post(&cards[pos].id, CardEntryKind::Credited, amount);
+    (cards[pos].number.clone(), posting.entries)
+}
+
+// Cards loaded by `order` that no longer hold what was loaded, so voiding
+// the sale can't take it back.
+pub fn spent_cards<'a>(cards: &'a [GiftCard], ledger: &[CardEntry], order: &Order) -> Vec<&'a GiftCard> {
+    cards
+        .iter()
+        .filter(|c| {
+            let loaded = ledger
+                .iter()
+                .filter(|e| e.card_id == c.id && e.kind == CardEntryKind::Loaded && e.order_id.as_deref() == Some(&order.id))
+                .fold(Money::zero(order.currency), |sum, e| sum + e.amount);
+            loaded.minor() > 0 && card_balance(ledger, &c.id) < loaded
+        })
+        .collect()
+}
+
+// Undoes a voided sale: takes back what it loaded and returns what it
+// took from cards, less anything already returned by refunds.
+pub fn post_cancellation_cards(ledger: &[CardEntry], order: &Order) -> Vec<CardEntry> {
+    let mut posting = Posting::new(ledger, &order.id);
+    let mut totals: Vec<(String, Money, Money)> = Vec::new();
+    for entry in ledger.iter().filter(|e| e.order_id.as_deref() == Some(&order.id)) {
+        let pos = match totals.iter().position(|(id, _, _)| *id == entry.card_id) {
+            Some(pos) => pos,
+            None => {
+                let zero = Money::zero(entry.amount.currency());
+                totals.push((entry.card_id.clone(), zero, zero));
+                totals.len() - 1
+            }
+        };
+        match entry.kind {
+            CardEntryKind::Loaded | CardEntryKind::Reversed => totals[pos].1 += entry.amount,
+            CardEntryKind::Redeemed | CardEntryKind::Returned => totals[pos].2 -= entry.amount,
+            CardEntryKind::Credited => {}
+        }
+    }
+    for (card_id, loaded, taken) in totals {
+        if loaded.minor() > 0 {
+            posting.post(&card_id, CardEntryKind::Reversed, -loaded);
+        }
+        if taken.minor() > 0 {
+            posting.post(&card_id, CardEntryKind::Returned, taken);
+        }
+    }
+    posting.entries
+}
+
+// Entries for one order, each carrying its card's running balance.
+struct Posting<'a> {
+    ledger: &'a [CardEntry],
+    order_id: &'a str,
+    entries: Vec<CardEntry>,
+}
+
+impl<'a> Posting<'a> {
+    fn new(ledger: &'a [CardEntry], order_id: &'a str) -> Self {
+        Self { ledger, order_id, entries: Vec::new() }
+    }
+
+    fn post(&mut self, card_id: &str, kind: CardEntryKind, amount: Money) {
+        if amount.minor() == 0 {
+            return;
+        }
+        let balance = card_balance(self.ledger, card_id) + card_balance(&self.entries, card_id) + amount;
+        self.entries.push(CardEntry {
+            id: Uuid::new_v4().to_string(),
+            card_id: card_id.to_string(),
+            kind,
+            amount,
+            balance,
+            order_id: Some(self.order_id.to_string()),
+            created_at: Utc::now(),
+        });
+    }
+}
+
+impl fmt::Display for GiftCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GiftCardError::InvalidNumber(input) => write!(f, "'{input}' is not a {NUMBER_DIGITS}-digit card number"),
+            GiftCardError::UnknownCard(number) => write!(f, "no card {}", mask_number(number)),
+            GiftCardError::WrongKind(kind) => write!(f, "that number is {}", kind.as_str().to_lowercase()),
+            GiftCardError::Expired(at) => write!(f, "the card expired on {}", at.format("%b %d, %Y")),
+            GiftCardError::OverLimit(room) => write!(f, "at most {room} more can go on this card"),
+            GiftCardError::NothingToLoad => write!(f, "enter an amount to load"),
+        }
+    }
+}
+
+impl std::error::Error for GiftCardError {}
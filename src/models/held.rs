@@ -0,0 +1,83 @@
+use std::fmt;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use super::order::Order;
+
+// An order parked so the till can serve someone else; recalled into the
+// cart later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeldOrder {
+    pub order: Order,
+    // What the cashier called it, e.g. "Blue jacket" or "Table 4".
+    pub label: String,
+    pub held_at: DateTime<Utc>,
+    // Name of the employee who parked it.
+    pub held_by: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HoldError {
+    Empty,
+    // Money already taken has to be settled or given back first.
+    PaymentsTaken,
+    DuplicateLabel(String),
+}
+
+impl HeldOrder {
+    // Parks `order` under `label`, or under the next free "Hold n" when the
+    // label is blank. Labels are unique among `held`, ignoring case.
+    pub fn new(order: Order, label: &str, held_by: Option<String>, held: &[HeldOrder]) -> Result<Self, HoldError> {
+        if order.items.is_empty() {
+            return Err(HoldError::Empty);
+        }
+        if !order.payments.is_empty() {
+            return Err(HoldError::PaymentsTaken);
+        }
+        let taken = |l: &str| held.iter().any(|h| h.label.eq_ignore_ascii_case(l));
+        let label = match label.trim() {
+            "" => (1..).map(|n| format!("Hold {n}")).find(|l| !taken(l)).unwrap_or_default(),
+            label if taken(label) => return Err(HoldError::DuplicateLabel(label.to_string())),
+            label => label.to_string(),
+        };
+        Ok(Self {
+            order,
+            label,
+            held_at: Utc::now(),
+            held_by,
+        })
+    }
+
+    // Held for longer than `hours`; 0 hours never expires.
+    pub fn is_expired(&self, hours: u32, now: DateTime<Utc>) -> bool {
+        hours > 0 && now - self.held_at > Duration::hours(hours as i64)
+    }
+
+    // "just now", "12 min" or "3 h 5 min".
+    pub fn age(&self, now: DateTime<Utc>) -> String {
+        let minutes = (now - self.held_at).num_minutes().max(0);
+        match minutes {
+            0 => "just now".to_string(),
+            1..=59 => format!("{minutes} min"),
+            _ => format!("{} h {} min", minutes / 60, minutes % 60),
+        }
+    }
+}
+
+// Takes the holds older than `hours` out of `held` and returns them.
+pub fn expire_holds(held: &mut Vec<HeldOrder>, hours: u32, now: DateTime<Utc>) -> Vec<HeldOrder> {
+    let (expired, kept) = std::mem::take(held).into_iter().partition(|h| h.is_expired(hours, now));
+    *held = kept;
+    expired
+}
+
+impl fmt::Display for HoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoldError::Empty => write!(f, "the cart is empty"),
+            HoldError::PaymentsTaken => write!(f, "payments were already taken; finish or void the order"),
+            HoldError::DuplicateLabel(label) => write!(f, "an order is already held as {label}"),
+        }
+    }
+}
+
+impl std::error::Error for HoldError {}
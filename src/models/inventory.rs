@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use super::order::Order;
+use super::product::Product;
+use super::refund::Refund;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MovementKind {
+    Sale,
+    Refund,
+    Cancellation,
+    Adjustment,
+    Receiving,
+    Waste,
+}
+
+// One entry in the inventory ledger. `quantity` is the signed change and
+// `on_hand` the product's stock right after it was applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockMovement {
+    pub id: String,
+    pub product_id: String,
+    pub product_name: String,
+    pub kind: MovementKind,
+    pub quantity: i64,
+    pub on_hand: i64,
+    pub order_id: Option<String>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum OversellPolicy {
+    // Sell regardless; stock may go negative.
+    Allow,
+    // Sell, but tell the cashier the item is out of stock.
+    #[default]
+    Warn,
+    // Refuse to add more than is on hand.
+    Block,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StockCheck {
+    Ok,
+    Warn(String),
+    Block(String),
+}
+
+impl MovementKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MovementKind::Sale => "Sale",
+            MovementKind::Refund => "Refund",
+            MovementKind::Cancellation => "Cancellation",
+            MovementKind::Adjustment => "Adjustment",
+            MovementKind::Receiving => "Receiving",
+            MovementKind::Waste => "Waste",
+        }
+    }
+}
+
+impl OversellPolicy {
+    pub fn as_str(&self) -> &str {
+        match self {
+            OversellPolicy::Allow => "Allow",
+            OversellPolicy::Warn => "Warn",
+            OversellPolicy::Block => "Block",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            OversellPolicy::Allow => OversellPolicy::Warn,
+            OversellPolicy::Warn => OversellPolicy::Block,
+            OversellPolicy::Block => OversellPolicy::Allow,
+        }
+    }
+
+    // `requested` is the quantity the cart would hold after the change.
+    pub fn check(&self, product: &Product, requested: u32) -> StockCheck {
+        if requested as i64 <= product.stock {
+            return StockCheck::Ok;
+        }
+
+        let message = format!(
+            "{} is out of stock ({} on hand, {} in cart)",
+            product.name,
+            product.stock.max(0),
+            requested
+        );
+        match self {
+            OversellPolicy::Allow => StockCheck::Ok,
+            OversellPolicy::Warn => StockCheck::Warn(message),
+            OversellPolicy::Block => StockCheck::Block(message),
+        }
+    }
+}
+
+// Applies a stock change to the matching product and returns the ledger
+// entry for it, or `None` if the product no longer exists.
+pub fn post_movement(
+    products: &mut [Product],
+    product_id: &str,
+    kind: MovementKind,
+    quantity: i64,
+    order_id: Option<&str>,
+    note: Option<String>,
+) -> Option<StockMovement> {
+    let product = products.iter_mut().find(|p| p.id == product_id)?;
+    product.stock += quantity;
+
+    Some(StockMovement {
+        id: Uuid::new_v4().to_string(),
+        product_id: product.id.clone(),
+        product_name: product.name.clone(),
+        kind,
+        quantity,
+        on_hand: product.stock,
+        order_id: order_id.map(str::to_string),
+        note,
+        created_at: Utc::now(),
+    })
+}
+
+// Takes every line of a paid order out of stock.
+pub fn post_sale(products: &mut [Product], order: &Order) -> Vec<StockMovement> {
+    order
+        .items
+        .iter()
+        .filter_map(|item| {
+            post_movement(
+                products,
+                &item.product_id,
+                MovementKind::Sale,
+                -(item.quantity as i64),
+                Some(&order.id),
+                None,
+            )
+        })
+        .collect()
+}
+
+// Puts refunded units back on the shelf.
+pub fn post_refund(products: &mut [Product], refund: &Refund) -> Vec<StockMovement> {
+    refund
+        .lines
+        .iter()
+        .filter_map(|line| {
+            post_movement(
+                products,
+                &line.product_id,
+                MovementKind::Refund,
+                line.quantity as i64,
+                Some(&refund.order_id),
+                None,
+            )
+        })
+        .collect()
+}
+
+// Returns every line of a voided sale to stock. Only call this for orders
+// whose sale was already posted, i.e. ones that had been paid.
+pub fn post_cancellation(products: &mut [Product], order: &Order) -> Vec<StockMovement> {
+    order
+        .items
+        .iter()
+        .filter_map(|item| {
+            post_movement(
+                products,
+                &item.product_id,
+                MovementKind::Cancellation,
+                item.quantity as i64,
+                Some(&order.id),
+                None,
+            )
+        })
+        .collect()
+}
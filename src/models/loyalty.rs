@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use super::money::Money;
+use super::order::{Order, OrderItem, OrderStatus};
+use super::payment::PaymentMethod;
+use super::refund::Refund;
+
+// How customers earn and spend points; part of `settings.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoyaltyConfig {
+    pub enabled: bool,
+    // Points per whole currency unit spent after discounts.
+    pub points_per_unit: u32,
+    // Categories earning at their own rate. A sub-category without one
+    // earns at its nearest parent's.
+    pub category_rates: Vec<CategoryRate>,
+    // Reached by lifetime points; the highest one reached applies.
+    pub tiers: Vec<LoyaltyTier>,
+    // Points that pay for one currency unit.
+    pub points_per_redeemed_unit: u32,
+    // Fewest points taken as one payment.
+    pub min_redeem: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryRate {
+    pub category_id: String,
+    pub points_per_unit: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoyaltyTier {
+    pub name: String,
+    // Points earned over the customer's lifetime, after reversals.
+    pub min_points: i64,
+    // Extra points on every sale, as a percentage of the points earned.
+    pub bonus_percent: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PointsKind {
+    Earned,
+    Redeemed,
+    // Earned points taken back by a refund or void.
+    Reversed,
+    // Redeemed points given back by a refund or void.
+    Restored,
+}
+
+// One entry in the points ledger. `points` is the signed change and
+// `balance` the customer's points right after it was applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PointsEntry {
+    pub id: String,
+    pub customer_id: String,
+    pub kind: PointsKind,
+    pub points: i64,
+    pub balance: i64,
+    pub order_id: Option<String>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Default for LoyaltyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            points_per_unit: 1,
+            category_rates: Vec::new(),
+            tiers: vec![
+                LoyaltyTier { name: "Member".to_string(), min_points: 0, bonus_percent: 0 },
+                LoyaltyTier { name: "Silver".to_string(), min_points: 500, bonus_percent: 10 },
+                LoyaltyTier { name: "Gold".to_string(), min_points: 2000, bonus_percent: 25 },
+            ],
+            points_per_redeemed_unit: 100,
+            min_redeem: 100,
+        }
+    }
+}
+
+impl LoyaltyConfig {
+    // The highest tier `lifetime` points reach.
+    pub fn tier(&self, lifetime: i64) -> Option<&LoyaltyTier> {
+        self.tiers
+            .iter()
+            .filter(|t| t.min_points <= lifetime)
+            .max_by_key(|t| t.min_points)
+    }
+
+    // Gift cards earn when they are spent, not when they are bought.
+    fn rate_for(&self, item: &OrderItem) -> u32 {
+        if item.gift_card.is_some() {
+            return 0;
+        }
+        item.category_ids
+            .iter()
+            .find_map(|id| self.category_rates.iter().find(|r| &r.category_id == id))
+            .map_or(self.points_per_unit, |r| r.points_per_unit)
+    }
+
+    // Points a paid order earns, with the tier's bonus. Only what was paid
+    // other than with points earns; parts of a unit are dropped per line.
+    pub fn points_earned(&self, order: &Order, tier: Option<&LoyaltyTier>) -> i64 {
+        if !self.enabled || order.total.minor() <= 0 {
+            return 0;
+        }
+        let scale = order.currency.scale() as i128;
+        let base: i128 = order
+            .items
+            .iter()
+            .map(|item| item.net_amount().minor() as i128 * self.rate_for(item) as i128 / scale)
+            .sum();
+        let total = order.total.minor() as i128;
+        let paid = (total - points_tendered(order).minor() as i128).max(0);
+        let base = base * paid / total;
+        let bonus = base * tier.map_or(0, |t| t.bonus_percent) as i128 / 100;
+        (base + bonus) as i64
+    }
+
+    // What `points` pay for, rounded down.
+    pub fn points_value(&self, points: i64, like: Money) -> Money {
+        let scale = like.currency().scale();
+        Money::new(points * scale / self.points_per_redeemed_unit.max(1) as i64, like.currency())
+    }
+
+    // Points needed to pay `amount`, rounded up.
+    pub fn points_for(&self, amount: Money) -> i64 {
+        let scale = amount.currency().scale();
+        let per_unit = self.points_per_redeemed_unit.max(1) as i64;
+        (amount.minor() * per_unit + scale - 1) / scale
+    }
+}
+
+// What the order has taken in points so far.
+pub fn points_tendered(order: &Order) -> Money {
+    order
+        .payments
+        .iter()
+        .filter(|p| p.method == PaymentMethod::Points)
+        .fold(Money::zero(order.currency), |sum, p| sum + p.amount)
+}
+
+pub fn points_balance(ledger: &[PointsEntry], customer_id: &str) -> i64 {
+    ledger.iter().filter(|e| e.customer_id == customer_id).map(|e| e.points).sum()
+}
+
+// Points earned over the customer's lifetime, net of reversals; sets the tier.
+pub fn lifetime_points(ledger: &[PointsEntry], customer_id: &str) -> i64 {
+    ledger
+        .iter()
+        .filter(|e| e.customer_id == customer_id)
+        .filter(|e| matches!(e.kind, PointsKind::Earned | PointsKind::Reversed))
+        .map(|e| e.points)
+        .sum()
+}
+
+// The customer's entries, newest first.
+pub fn points_history<'a>(ledger: &'a [PointsEntry], customer_id: &str) -> Vec<&'a PointsEntry> {
+    ledger.iter().rev().filter(|e| e.customer_id == customer_id).collect()
+}
+
+// Entries whose balance doesn't follow from the customer's entry before
+// them, i.e. a ledger that was edited or lost lines.
+pub fn check_points_ledger(ledger: &[PointsEntry]) -> Vec<&PointsEntry> {
+    let mut balances: Vec<(&str, i64)> = Vec::new();
+    let mut broken = Vec::new();
+    for entry in ledger {
+        let pos = match balances.iter().position(|(id, _)| *id == entry.customer_id) {
+            Some(pos) => pos,
+            None => {
+                balances.push((&entry.customer_id, 0));
+                balances.len() - 1
+            }
+        };
+        let expected = balances[pos].1 + entry.points;
+        if entry.balance != expected {
+            broken.push(entry);
+        }
+        balances[pos].1 = entry.balance;
+    }
+    broken
+}
+
+// Takes the points paid with and adds the points earned for a paid order
+// with a customer attached.
+pub fn post_sale_points(config: &LoyaltyConfig, ledger: &[PointsEntry], order: &Order) -> Vec<PointsEntry> {
+    let Some(customer) = &order.customer else {
+        return Vec::new();
+    };
+    let redeemed = config.points_for(points_tendered(order));
+    let tier = config.tier(lifetime_points(ledger, &customer.id));
+    let earned = config.points_earned(order, tier);
+    let note = tier.map(|t| t.name.clone());
+    let mut posting = Posting::new(ledger, &customer.id, &order.id);
+    posting.post(PointsKind::Redeemed, -redeemed, None);
+    posting.post(PointsKind::Earned, earned, note);
+    posting.entries
+}
+
+// Takes back the points `refund` didn't earn, in proportion to what was
+// refunded, and gives back points when the refund goes to the points
+// tender. `order` is the order after the refund.
+pub fn post_refund_points(ledger: &[PointsEntry], order: &Order, refund: &Refund) -> Vec<PointsEntry> {
+    let Some(customer) = &order.customer else {
+        return Vec::new();
+    };
+    let points = OrderPoints::of(ledger, &order.id);
+    let share = |points: i64, part: Money, whole: Money| {
+        if whole.minor() <= 0 {
+            return points;
+        }
+        (points as i128 * part.minor() as i128 / whole.minor() as i128) as i64
+    };
+    let reversed = match order.status {
+        OrderStatus::Refunded => points.earned_left(),
+        _ => share(points.earned, refund.total, order.total).min(points.earned_left()),
+    };
+    let to_points = refund.amount_to(&PaymentMethod::Points);
+    let restored = match to_points.minor() {
+        0 => 0,
+        _ => share(points.redeemed, to_points, points_tendered(order)).min(points.redeemed_left()),
+    };
+    let note = Some(refund.reason.as_str().to_string());
+    let mut posting = Posting::new(ledger, &customer.id, &order.id);
+    posting.post(PointsKind::Reversed, -reversed, note.clone());
+    posting.post(PointsKind::Restored, restored, note);
+    posting.entries
+}
+
+// Undoes whatever points a voided order still holds.
+pub fn post_cancellation_points(ledger: &[PointsEntry], order: &Order) -> Vec<PointsEntry> {
+    let Some(customer) = &order.customer else {
+        return Vec::new();
+    };
+    let points = OrderPoints::of(ledger, &order.id);
+    let note = order.void.as_ref().map(|v| v.reason.as_str().to_string());
+    let mut posting = Posting::new(ledger, &customer.id, &order.id);
+    posting.post(PointsKind::Reversed, -points.earned_left(), note.clone());
+    posting.post(PointsKind::Restored, points.redeemed_left(), note);
+    posting.entries
+}
+
+// What the ledger holds for one order, all as positive points.
+struct OrderPoints {
+    earned: i64,
+    reversed: i64,
+    redeemed: i64,
+    restored: i64,
+}
+
+impl OrderPoints {
+    fn of(ledger: &[PointsEntry], order_id: &str) -> Self {
+        let mut points = Self { earned: 0, reversed: 0, redeemed: 0, restored: 0 };
+        for entry in ledger.iter().filter(|e| e.order_id.as_deref() == Some(order_id)) {
+            let total = match entry.kind {
+                PointsKind::Earned => &mut points.earned,
+                PointsKind::Reversed => &mut points.reversed,
+                PointsKind::Redeemed => &mut points.redeemed,
+                PointsKind::Restored => &mut points.restored,
+            };
+            *total += entry.points.abs();
+        }
+        points
+    }
+
+    fn earned_left(&self) -> i64 {
+        (self.earned - self.reversed).max(0)
+    }
+
+    fn redeemed_left(&self) -> i64 {
+        (self.redeemed - self.restored).max(0)
+    }
+}
+
+// Entries for one customer and order, each carrying the running balance.
+struct Posting<'a> {
+    customer_id: &'a str,
+    order_id: &'a str,
+    balance: i64,
+    entries: Vec<PointsEntry>,
+}
+
+impl<'a> Posting<'a> {
+    fn new(ledger: &[PointsEntry], customer_id: &'a str, order_id: &'a str) -> Self {
+        Self {
+            customer_id,
+            order_id,
+            balance: points_balance(ledger, customer_id),
+            entries: Vec::new(),
+        }
+    }
+
+    // Nothing is posted for zero points.
+    fn post(&mut self, kind: PointsKind, points: i64, note: Option<String>) {
+        if points == 0 {
+            return;
+        }
+        self.balance += points;
+        self.entries.push(PointsEntry {
+            id: Uuid::new_v4().to_string(),
+            customer_id: self.customer_id.to_string(),
+            kind,
+            points,
+            balance: self.balance,
+            order_id: Some(self.order_id.to_string()),
+            note,
+            created_at: Utc::now(),
+        });
+    }
+}
+
+impl PointsKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            PointsKind::Earned => "Earned",
+            PointsKind::Redeemed => "Redeemed",
+            PointsKind::Reversed => "Reversed",
+            PointsKind::Restored => "Restored",
+        }
+    }
+}
@@ -1,7 +1,47 @@
 pub mod product;
 pub mod order;
 pub mod payment;
+pub mod money;
+pub mod tax;
+pub mod inventory;
+pub mod refund;
+pub mod settings;
+pub mod report;
+pub mod drawer;
+pub mod employee;
+pub mod audit;
+pub mod discount;
+pub mod promotion;
+pub mod modifier;
+pub mod category;
+pub mod barcode;
+pub mod search;
+pub mod held;
+pub mod customer;
+pub mod loyalty;
+pub mod gift_card;
+pub mod tip;
 
 pub use product::*;
 pub use order::*;
 pub use payment::*;
+pub use money::*;
+pub use tax::*;
+pub use inventory::*;
+pub use refund::*;
+pub use settings::*;
+pub use report::*;
+pub use drawer::*;
+pub use employee::*;
+pub use audit::*;
+pub use discount::*;
+pub use promotion::*;
+pub use modifier::*;
+pub use category::*;
+pub use barcode::*;
+pub use search::*;
+pub use held::*;
+pub use customer::*;
+pub use loyalty::*;
+pub use gift_card::*;
+pub use tip::*;
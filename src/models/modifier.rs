@@ -0,0 +1,192 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::money::Money;
+use super::product::Product;
+
+// Choices a product offers at the till. A variant group takes exactly one
+// option (a size); a modifier group takes between `min` and `max` (milk,
+// extra shot) and is required when `min` is above zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionGroup {
+    pub id: String,
+    pub name: String,
+    pub kind: GroupKind,
+    pub min: u32,
+    pub max: u32,
+    pub options: Vec<ProductOption>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroupKind {
+    Variant,
+    Modifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductOption {
+    pub id: String,
+    pub name: String,
+    // Added to the product's price; negative for a cheaper variant.
+    pub price_delta: Money,
+}
+
+// An option as it was chosen for an order line. Names are copied so renaming
+// an option never rewrites a past sale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChosenOption {
+    pub option_id: String,
+    pub group: String,
+    pub name: String,
+    pub price_delta: Money,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionError {
+    UnknownOption(String),
+    TooFew { group: String, min: u32 },
+    TooMany { group: String, max: u32 },
+    // The deltas would take the price below zero.
+    NegativePrice,
+    // A group that can never be satisfied, e.g. `min` above `max`.
+    InvalidGroup(String),
+}
+
+impl OptionGroup {
+    // Exactly one of its options must be chosen.
+    pub fn variant(name: String) -> Self {
+        Self::new(name, GroupKind::Variant, 1, 1)
+    }
+
+    pub fn modifier(name: String, min: u32, max: u32) -> Self {
+        Self::new(name, GroupKind::Modifier, min, max)
+    }
+
+    fn new(name: String, kind: GroupKind, min: u32, max: u32) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            kind,
+            min,
+            max,
+            options: Vec::new(),
+        }
+    }
+
+    pub fn with_option(mut self, name: &str, price_delta: Money) -> Self {
+        self.options.push(ProductOption::new(name.to_string(), price_delta));
+        self
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.min > 0
+    }
+
+    pub fn validate(&self) -> Result<(), SelectionError> {
+        let fits = !self.name.trim().is_empty()
+            && self.max >= 1
+            && self.min <= self.max
+            && (self.options.is_empty() || self.min as usize <= self.options.len());
+        if fits {
+            Ok(())
+        } else {
+            Err(SelectionError::InvalidGroup(self.name.clone()))
+        }
+    }
+
+    // "Pick 1", "Up to 2", "Pick 1 to 3".
+    pub fn rule_label(&self) -> String {
+        match (self.min, self.max) {
+            (0, max) => format!("Up to {max}"),
+            (min, max) if min == max => format!("Pick {min}"),
+            (min, max) => format!("Pick {min} to {max}"),
+        }
+    }
+}
+
+impl GroupKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            GroupKind::Variant => "Variant",
+            GroupKind::Modifier => "Modifier",
+        }
+    }
+}
+
+impl ProductOption {
+    pub fn new(name: String, price_delta: Money) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            price_delta,
+        }
+    }
+}
+
+impl Product {
+    pub fn has_options(&self) -> bool {
+        self.options.iter().any(|g| !g.options.is_empty())
+    }
+
+    // Checks the ids picked at the till against every group and returns the
+    // choices in the product's group and option order, so the same picks
+    // always give the same line.
+    pub fn choose(&self, option_ids: &[String]) -> Result<Vec<ChosenOption>, SelectionError> {
+        if let Some(unknown) = option_ids
+            .iter()
+            .find(|id| !self.options.iter().flat_map(|g| &g.options).any(|o| &o.id == *id))
+        {
+            return Err(SelectionError::UnknownOption(unknown.clone()));
+        }
+
+        let mut chosen = Vec::new();
+        for group in self.options.iter().filter(|g| !g.options.is_empty()) {
+            let picked: Vec<&ProductOption> = group.options.iter().filter(|o| option_ids.contains(&o.id)).collect();
+            let count = picked.len() as u32;
+            if count < group.min {
+                return Err(SelectionError::TooFew { group: group.name.clone(), min: group.min });
+            }
+            if count > group.max {
+                return Err(SelectionError::TooMany { group: group.name.clone(), max: group.max });
+            }
+            chosen.extend(picked.into_iter().map(|o| ChosenOption {
+                option_id: o.id.clone(),
+                group: group.name.clone(),
+                name: o.name.clone(),
+                price_delta: o.price_delta,
+            }));
+        }
+
+        let price = chosen.iter().fold(self.price, |sum, o| sum + o.price_delta);
+        if price.minor() < 0 {
+            return Err(SelectionError::NegativePrice);
+        }
+        Ok(chosen)
+    }
+}
+
+// Identifies an order line. Lines merge only when the product and every
+// chosen option match; a product without options keeps its own id.
+pub fn line_id(product_id: &str, options: &[ChosenOption]) -> String {
+    if options.is_empty() {
+        return product_id.to_string();
+    }
+    let ids: Vec<&str> = options.iter().map(|o| o.option_id.as_str()).collect();
+    format!("{product_id}/{}", ids.join("+"))
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionError::UnknownOption(id) => write!(f, "option {id} is not offered"),
+            SelectionError::TooFew { group, min } => write!(f, "choose at least {min} for {group}"),
+            SelectionError::TooMany { group, max } => write!(f, "choose at most {max} for {group}"),
+            SelectionError::NegativePrice => write!(f, "options would make the price negative"),
+            SelectionError::InvalidGroup(name) => {
+                write!(f, "option group '{name}' needs a name, a maximum of at least 1 and a minimum it can meet")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SelectionError {}
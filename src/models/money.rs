@@ -0,0 +1,362 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const USD: Currency = Currency(*b"USD");
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        let bytes: [u8; 3] = code.as_bytes().try_into().ok()?;
+        bytes
+            .iter()
+            .all(u8::is_ascii_uppercase)
+            .then_some(Currency(bytes))
+    }
+
+    pub fn code(&self) -> &str {
+        // Only ever constructed from ASCII uppercase bytes.
+        std::str::from_utf8(&self.0).unwrap_or("???")
+    }
+
+    // ISO 4217 minor unit exponent.
+    pub fn decimals(&self) -> u32 {
+        match &self.0 {
+            b"JPY" | b"KRW" | b"VND" | b"CLP" | b"ISK" => 0,
+            b"BHD" | b"KWD" | b"OMR" | b"JOD" | b"TND" => 3,
+            _ => 2,
+        }
+    }
+
+    pub fn symbol(&self) -> Option<&str> {
+        match &self.0 {
+            b"USD" | b"CAD" | b"AUD" | b"NZD" => Some("$"),
+            b"EUR" => Some("€"),
+            b"GBP" => Some("£"),
+            b"JPY" => Some("¥"),
+            _ => None,
+        }
+    }
+
+    // Minor units in one major unit (100 for USD).
+    pub fn scale(&self) -> i64 {
+        10_i64.pow(self.decimals())
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::USD
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Currency::from_code(&code)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid currency code {code:?}")))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Rounding {
+    // Ties away from zero: 0.125 -> 0.13, -0.125 -> -0.13.
+    #[default]
+    HalfUp,
+    // Banker's rounding, ties to the even neighbour: 0.125 -> 0.12, 0.135 -> 0.14.
+    HalfEven,
+}
+
+impl Rounding {
+    // Divides `value` by a positive `divisor`, resolving the remainder by this rule.
+    pub fn divide(self, value: i128, divisor: i128) -> i128 {
+        let quotient = value / divisor;
+        let remainder = value % divisor;
+        let twice = remainder.abs() * 2;
+        let step = if value < 0 { -1 } else { 1 };
+
+        if twice > divisor {
+            quotient + step
+        } else if twice < divisor {
+            quotient
+        } else {
+            match self {
+                Rounding::HalfUp => quotient + step,
+                Rounding::HalfEven if quotient % 2 == 0 => quotient,
+                Rounding::HalfEven => quotient + step,
+            }
+        }
+    }
+}
+
+// A fraction expressed in parts per million, so 8.875% is `Rate(88_750)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Default)]
+pub struct Rate(pub u32);
+
+impl Rate {
+    const ONE: i128 = 1_000_000;
+
+    pub fn as_percent(&self) -> f64 {
+        self.0 as f64 / 10_000.0
+    }
+
+    // Exact parse of a percentage such as "12.5" or "15%"; at most four
+    // decimals, `None` if invalid.
+    pub fn parse_percent(input: &str) -> Option<Rate> {
+        let input = input.trim().trim_end_matches('%').trim_end();
+        let (whole, frac) = input.split_once('.').unwrap_or((input, ""));
+        let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if (whole.is_empty() && frac.is_empty()) || frac.len() > 4 || !digits(whole) || !digits(frac) {
+            return None;
+        }
+        let whole: u32 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+        let frac: u32 = format!("{frac:0<4}").parse().ok()?;
+        whole.checked_mul(10_000)?.checked_add(frac).map(Rate)
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = format!("{:.4}", self.as_percent());
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        write!(f, "{text}%")
+    }
+}
+
+// An exact amount in the currency's minor unit (cents for USD).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct Money {
+    minor: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn new(minor: i64, currency: Currency) -> Self {
+        Self { minor, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(0, currency)
+    }
+
+    // Exact parse of user input such as "3.5", "-12.99" or "1,250.00".
+    // Rejects more fractional digits than the currency has.
+    pub fn parse(input: &str, currency: Currency) -> Option<Self> {
+        let text: String = input.trim().chars().filter(|c| *c != ',').collect();
+        let text = currency
+            .symbol()
+            .and_then(|s| text.strip_prefix(s))
+            .unwrap_or(&text);
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (whole, frac) = text.split_once('.').unwrap_or((text, ""));
+        if whole.is_empty() && frac.is_empty() {
+            return None;
+        }
+        if !whole.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let decimals = currency.decimals() as usize;
+        if frac.len() > decimals {
+            return None;
+        }
+        let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+        let frac: i64 = format!("{frac:0<decimals$}").parse().unwrap_or(0);
+        let minor = whole.checked_mul(currency.scale())?.checked_add(frac)?;
+        Some(Self::new(if negative { -minor } else { minor }, currency))
+    }
+
+    pub fn minor(&self) -> i64 {
+        self.minor
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    // Only for reading legacy float amounts; never use for arithmetic.
+    pub fn from_major_f64(amount: f64, currency: Currency) -> Self {
+        Self::new((amount * currency.scale() as f64).round() as i64, currency)
+    }
+
+    pub fn apply_rate(&self, rate: Rate, rounding: Rounding) -> Money {
+        let minor = rounding.divide(self.minor as i128 * rate.0 as i128, Rate::ONE);
+        Self::new(minor as i64, self.currency)
+    }
+
+    // Bare decimal without symbol or code, e.g. "-12.50"; round-trips through `parse`.
+    pub fn plain_string(&self) -> String {
+        let sign = if self.minor < 0 { "-" } else { "" };
+        format!("{sign}{}", self.abs_plain())
+    }
+
+    fn abs_plain(&self) -> String {
+        let scale = self.currency.scale() as u64;
+        let abs = self.minor.unsigned_abs();
+        let decimals = self.currency.decimals() as usize;
+        if decimals == 0 {
+            format!("{abs}")
+        } else {
+            format!("{}.{:0decimals$}", abs / scale, abs % scale)
+        }
+    }
+
+    // Smallest whole major-unit amount that is at least `self`.
+    pub fn ceil_major(&self) -> Money {
+        let scale = self.currency.scale();
+        let whole = self.minor.div_euclid(scale) * scale;
+        let bump = if self.minor.rem_euclid(scale) > 0 { scale } else { 0 };
+        Self::new(whole + bump, self.currency)
+    }
+
+    // Rounds to a multiple of `increment` minor units, e.g. 5 for nickel
+    // rounding of cash totals.
+    pub fn round_to(&self, increment: i64, rounding: Rounding) -> Money {
+        if increment <= 1 {
+            return *self;
+        }
+        let units = rounding.divide(self.minor as i128, increment as i128) as i64;
+        Self::new(units * increment, self.currency)
+    }
+
+    // Divides into `parts` amounts that differ by at most one minor unit and
+    // sum back to `self` exactly; the leftover units go to the first parts.
+    pub fn split(&self, parts: u32) -> Vec<Money> {
+        let parts = parts.max(1) as i64;
+        let base = self.minor.div_euclid(parts);
+        let extra = self.minor.rem_euclid(parts);
+        (0..parts)
+            .map(|i| Self::new(base + i64::from(i < extra), self.currency))
+            .collect()
+    }
+
+    fn check_currency(&self, other: &Money) {
+        assert_eq!(
+            self.currency, other.currency,
+            "cannot combine {} and {} amounts",
+            self.currency, other.currency
+        );
+    }
+}
+
+impl Default for Money {
+    fn default() -> Self {
+        Money::zero(Currency::default())
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.minor < 0 { "-" } else { "" };
+        let number = self.abs_plain();
+
+        match self.currency.symbol() {
+            Some(symbol) => write!(f, "{sign}{symbol}{number}"),
+            None => write!(f, "{sign}{number} {}", self.currency),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Exact { minor: i64, currency: Currency },
+            // Amounts saved before `Money` existed were bare USD floats.
+            Legacy(f64),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Exact { minor, currency } => Money::new(minor, currency),
+            Repr::Legacy(amount) => Money::from_major_f64(amount, Currency::USD),
+        })
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        self.check_currency(&rhs);
+        Money::new(self.minor + rhs.minor, self.currency)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        self.check_currency(&rhs);
+        Money::new(self.minor - rhs.minor, self.currency)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money::new(-self.minor, self.currency)
+    }
+}
+
+impl Mul<u32> for Money {
+    type Output = Money;
+
+    fn mul(self, quantity: u32) -> Money {
+        Money::new(self.minor * quantity as i64, self.currency)
+    }
+}
+
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Money) -> Option<std::cmp::Ordering> {
+        (self.currency == other.currency).then(|| self.minor.cmp(&other.minor))
+    }
+}
+
+// An empty sum is zero in the default currency.
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        let mut iter = iter;
+        match iter.next() {
+            Some(first) => iter.fold(first, |acc, m| acc + m),
+            None => Money::default(),
+        }
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
@@ -1,90 +1,772 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
-use super::payment::Payment;
+use chrono::{DateTime, Local, Utc};
+use super::payment::{Payment, PaymentError, PaymentMethod};
+use super::product::Product;
+use super::money::{Currency, Money, Rounding};
+use super::tax::{line_taxes, TaxLine, TaxMode, TaxRate};
+use super::refund::{ReasonCode, Refund, RefundError, RefundLine, RefundTender, VoidRecord};
+use super::discount::{apply_discounts, AppliedDiscount, Discount, DiscountError, DiscountKind, DiscountTarget};
+use super::promotion::{normalize_code, Promotion};
+use super::modifier::{line_id, ChosenOption};
+use super::category::{category_lineage, Category};
+use super::barcode::ScaleLabel;
+use super::customer::OrderCustomer;
+use super::gift_card::mask_number;
+use super::tip::TipError;
+
+// Product id of gift card lines, which have no catalog product.
+pub const GIFT_CARD_PRODUCT: &str = "gift-card";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Order {
     pub id: String,
     pub items: Vec<OrderItem>,
-    pub subtotal: f64,
-    pub tax: f64,
-    pub total: f64,
-    pub payment: Option<Payment>,
+    #[serde(default)]
+    pub currency: Currency,
+    // Before discounts.
+    pub subtotal: Money,
+    // Every discount on the lines and the order.
+    #[serde(default)]
+    pub discount: Money,
+    pub tax: Money,
+    pub total: Money,
+    #[serde(default)]
+    pub tax_mode: TaxMode,
+    #[serde(default)]
+    pub tax_lines: Vec<TaxLine>,
+    // Discounts keyed in at the till.
+    #[serde(default)]
+    pub discounts: Vec<Discount>,
+    // Coupon codes entered, normalized.
+    #[serde(default)]
+    pub coupons: Vec<String>,
+    // Promotions the order is priced with, copied from the store's list when
+    // the order was started so editing them never reprices a past sale.
+    #[serde(default)]
+    pub promotions: Vec<Promotion>,
+    // Order-level discounts as applied, for display. Line discounts are on
+    // the lines.
+    #[serde(default)]
+    pub order_discounts: Vec<AppliedDiscount>,
+    // Every tender taken against the order, in the order they were taken.
+    #[serde(default)]
+    pub payments: Vec<Payment>,
     pub status: OrderStatus,
+    #[serde(default)]
+    pub refunds: Vec<Refund>,
+    #[serde(default)]
+    pub void: Option<VoidRecord>,
+    // Drawer session the order was rung in.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    // Employee who took the payment that completed the order.
+    #[serde(default)]
+    pub operator_id: Option<String>,
+    // Who the sale is for, if anyone.
+    #[serde(default)]
+    pub customer: Option<OrderCustomer>,
     pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct OrderItem {
+    // Product id plus the chosen options; see `line_id`.
+    pub line_id: String,
     pub product_id: String,
     pub product_name: String,
-    pub price: f64,
+    // Unit price with the options' price deltas.
+    pub price: Money,
     pub quantity: u32,
+    #[serde(default)]
+    pub taxes: Vec<TaxRate>,
+    // Category name at the time of sale, for reporting.
+    #[serde(default)]
+    pub category: Option<String>,
+    // The product's category id and its parents', nearest first, so a
+    // promotion on a category also covers its sub-categories.
+    #[serde(default)]
+    pub category_ids: Vec<String>,
+    // Variants and modifiers, in the product's group order.
+    #[serde(default)]
+    pub options: Vec<ChosenOption>,
+    // Discounts on this line alone.
+    #[serde(default)]
+    pub discounts: Vec<AppliedDiscount>,
+    // This line's share of the order-level discounts.
+    #[serde(default)]
+    pub order_discount: Money,
+    // Number of the gift card this line sells or reloads.
+    #[serde(default)]
+    pub gift_card: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum OrderStatus {
     Pending,
     Paid,
+    PartiallyRefunded,
     Refunded,
     Cancelled,
 }
 
 impl Order {
-    pub fn new() -> Self {
+    pub fn new(tax_mode: TaxMode) -> Self {
+        let currency = Currency::default();
         Self {
             id: Uuid::new_v4().to_string(),
             items: Vec::new(),
-            subtotal: 0.0,
-            tax: 0.0,
-            total: 0.0,
-            payment: None,
+            currency,
+            subtotal: Money::zero(currency),
+            discount: Money::zero(currency),
+            tax: Money::zero(currency),
+            total: Money::zero(currency),
+            tax_mode,
+            tax_lines: Vec::new(),
+            discounts: Vec::new(),
+            coupons: Vec::new(),
+            promotions: Vec::new(),
+            order_discounts: Vec::new(),
+            payments: Vec::new(),
             status: OrderStatus::Pending,
+            refunds: Vec::new(),
+            void: None,
+            session_id: None,
+            operator_id: None,
+            customer: None,
             created_at: Utc::now(),
         }
     }
 
-    pub fn add_item(&mut self, product_id: String, product_name: String, price: f64) {
-        if let Some(item) = self.items.iter_mut().find(|i| i.product_id == product_id) {
+    // Adds one unit with `options` (from `Product::choose`). The same product
+    // with different options goes on a line of its own.
+    pub fn add_item(
+        &mut self,
+        product: &Product,
+        options: Vec<ChosenOption>,
+        categories: &[Category],
+        taxes: Vec<TaxRate>,
+    ) {
+        let price = options.iter().fold(product.price, |sum, o| sum + o.price_delta);
+        self.push_line(product, options, price, categories, taxes);
+    }
+
+    // A pre-packed item whose barcode carries its price or weight. The label
+    // is the line's option, so only the same label adds to the line.
+    pub fn add_labelled_item(
+        &mut self,
+        product: &Product,
+        label: &ScaleLabel,
+        categories: &[Category],
+        taxes: Vec<TaxRate>,
+    ) {
+        let price = label.price_for(product);
+        self.push_line(product, vec![label.as_option(product)], price, categories, taxes);
+    }
+
+    fn push_line(
+        &mut self,
+        product: &Product,
+        options: Vec<ChosenOption>,
+        price: Money,
+        categories: &[Category],
+        taxes: Vec<TaxRate>,
+    ) {
+        let id = line_id(&product.id, &options);
+        let lineage = category_lineage(categories, &product.category);
+        if let Some(item) = self.items.iter_mut().find(|i| i.line_id == id) {
             item.quantity += 1;
         } else {
             self.items.push(OrderItem {
-                product_id,
-                product_name,
+                line_id: id,
+                product_id: product.id.clone(),
+                product_name: product.name.clone(),
                 price,
                 quantity: 1,
+                taxes,
+                category: lineage.first().map(|c| c.name.clone()),
+                category_ids: lineage.iter().map(|c| c.id.clone()).collect(),
+                options,
+                discounts: Vec::new(),
+                order_discount: Money::zero(product.price.currency()),
+                gift_card: None,
             });
         }
         self.calculate_totals();
     }
 
-    pub fn remove_item(&mut self, product_id: &str) {
-        if let Some(pos) = self.items.iter().position(|i| i.product_id == product_id) {
+    // Sells or reloads gift card `number` for `amount`, untaxed. Loading the
+    // same card again in this order replaces the amount.
+    pub fn add_gift_card(&mut self, number: &str, amount: Money) {
+        let id = format!("gift-card:{number}");
+        if let Some(item) = self.items.iter_mut().find(|i| i.line_id == id) {
+            item.price = amount;
+            item.quantity = 1;
+        } else {
+            self.items.push(OrderItem {
+                line_id: id,
+                product_id: GIFT_CARD_PRODUCT.to_string(),
+                product_name: format!("Gift Card {}", mask_number(number)),
+                price: amount,
+                quantity: 1,
+                taxes: Vec::new(),
+                category: Some("Gift Cards".to_string()),
+                category_ids: Vec::new(),
+                options: Vec::new(),
+                discounts: Vec::new(),
+                order_discount: Money::zero(amount.currency()),
+                gift_card: Some(number.to_string()),
+            });
+        }
+        self.calculate_totals();
+    }
+
+    // Units of the product across all its lines.
+    pub fn quantity_of(&self, product_id: &str) -> u32 {
+        self.items
+            .iter()
+            .filter(|i| i.product_id == product_id)
+            .map(|i| i.quantity)
+            .sum()
+    }
+
+    pub fn item(&self, line_id: &str) -> Option<&OrderItem> {
+        self.items.iter().find(|i| i.line_id == line_id)
+    }
+
+    // Lines can't come off once a tender is taken: the total would drop
+    // below what was paid. The payments are removed or voided first.
+    pub fn remove_item(&mut self, line_id: &str) -> Result<(), PaymentError> {
+        if !self.payments.is_empty() {
+            return Err(PaymentError::PaymentsTaken);
+        }
+        if let Some(pos) = self.items.iter().position(|i| i.line_id == line_id) {
             let item = &mut self.items[pos];
             if item.quantity > 1 {
                 item.quantity -= 1;
             } else {
                 self.items.remove(pos);
+                self.discounts.retain(|d| d.target != DiscountTarget::Line(line_id.to_string()));
             }
         }
         self.calculate_totals();
+        Ok(())
+    }
+
+    // Sells each unit of the line at `price` instead of the catalog price,
+    // replacing any earlier override. Recorded as a discount so the saving
+    // shows on the receipt and in reports.
+    pub fn override_price(&mut self, line_id: &str, price: Money) -> Result<(), DiscountError> {
+        let target = DiscountTarget::Line(line_id.to_string());
+        let discount = Discount::new(DiscountKind::FixedPrice(price), target.clone());
+        discount.validate()?;
+        self.check_discountable(&target)?;
+        self.discounts
+            .retain(|d| !(d.target == target && matches!(d.kind, DiscountKind::FixedPrice(_))));
+        self.discounts.push(discount);
+        self.calculate_totals();
+        Ok(())
+    }
+
+    pub fn add_discount(&mut self, discount: Discount) -> Result<(), DiscountError> {
+        discount.validate()?;
+        self.check_discountable(&discount.target)?;
+        self.discounts.push(discount);
+        self.calculate_totals();
+        Ok(())
+    }
+
+    pub fn remove_discount(&mut self, discount_id: &str) {
+        self.discounts.retain(|d| d.id != discount_id);
+        self.calculate_totals();
+    }
+
+    // Enters a coupon code; it must belong to one of the order's promotions.
+    pub fn apply_coupon(&mut self, code: &str) -> Result<(), DiscountError> {
+        let code = normalize_code(code);
+        if self.status != OrderStatus::Pending {
+            return Err(DiscountError::NotPending);
+        }
+        if !self.payments.is_empty() {
+            return Err(DiscountError::PaymentsTaken);
+        }
+        if self.coupons.contains(&code) {
+            return Err(DiscountError::CouponAlreadyApplied(code));
+        }
+        if !self.promotions.iter().any(|p| p.coupon_code.as_ref() == Some(&code)) {
+            return Err(DiscountError::UnknownCoupon(code));
+        }
+        self.coupons.push(code);
+        self.calculate_totals();
+        Ok(())
+    }
+
+    pub fn remove_coupon(&mut self, code: &str) {
+        self.coupons.retain(|c| c != code);
+        self.calculate_totals();
     }
 
-    pub fn clear(&mut self) {
+    // Reprices an open order with the store's current promotions. Coupons
+    // whose promotion is gone are dropped. An order part-paid keeps its
+    // prices.
+    pub fn set_promotions(&mut self, promotions: &[Promotion]) {
+        if self.status != OrderStatus::Pending || !self.payments.is_empty() {
+            return;
+        }
+        self.promotions = promotions.iter().filter(|p| p.active).cloned().collect();
+        let known = |code: &String| self.promotions.iter().any(|p| p.coupon_code.as_ref() == Some(code));
+        let coupons = self.coupons.iter().filter(|c| known(c)).cloned().collect();
+        self.coupons = coupons;
+        self.calculate_totals();
+    }
+
+    fn check_discountable(&self, target: &DiscountTarget) -> Result<(), DiscountError> {
+        if self.status != OrderStatus::Pending {
+            return Err(DiscountError::NotPending);
+        }
+        if !self.payments.is_empty() {
+            return Err(DiscountError::PaymentsTaken);
+        }
+        match target {
+            DiscountTarget::Line(id) => match self.item(id) {
+                None => Err(DiscountError::UnknownItem(id.clone())),
+                Some(item) if item.gift_card.is_some() => Err(DiscountError::GiftCardLine),
+                Some(_) => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    pub fn clear(&mut self) -> Result<(), PaymentError> {
+        if !self.payments.is_empty() {
+            return Err(PaymentError::PaymentsTaken);
+        }
         self.items.clear();
+        self.discounts.clear();
+        self.coupons.clear();
+        self.customer = None;
         self.calculate_totals();
+        Ok(())
+    }
+
+    // Attaches or detaches the customer; a tax-exempt one takes the tax off.
+    pub fn set_customer(&mut self, customer: Option<OrderCustomer>) {
+        self.customer = customer;
+        self.calculate_totals();
+    }
+
+    pub fn is_tax_exempt(&self) -> bool {
+        self.customer.as_ref().is_some_and(|c| c.is_tax_exempt())
+    }
+
+    // Rates charged on a line: none for a tax-exempt customer. Lines keep
+    // their rates so detaching the customer charges tax again.
+    pub fn rates<'a>(&self, item: &'a OrderItem) -> &'a [TaxRate] {
+        if self.is_tax_exempt() {
+            &[]
+        } else {
+            &item.taxes
+        }
     }
 
     pub fn calculate_totals(&mut self) {
-        self.subtotal = self.items.iter().map(|i| i.price * i.quantity as f64).sum();
-        self.tax = self.subtotal * 0.08; // 8% tax
-        self.total = self.subtotal + self.tax;
+        // Discounts are worked out first so tax is charged on what the
+        // customer actually pays. Promotions are timed by the till's clock.
+        let now = Local::now().naive_local();
+        let mut in_effect: Vec<&Promotion> = self
+            .promotions
+            .iter()
+            .filter(|p| p.in_effect(now, &self.coupons))
+            .collect();
+        in_effect.sort_by_key(|p| p.priority);
+        self.order_discounts = apply_discounts(&mut self.items, &self.discounts, &in_effect);
+
+        // Line totals and their sum are exact; each tax is rounded per line.
+        let zero = Money::zero(self.currency);
+        let mut tax_lines: Vec<TaxLine> = Vec::new();
+
+        for item in &self.items {
+            let amount = item.net_amount();
+            let rates = self.rates(item);
+            for (rate, tax) in rates.iter().zip(line_taxes(amount, rates, self.tax_mode)) {
+                match tax_lines.iter_mut().find(|l| l.rate_id == rate.id) {
+                    Some(line) => {
+                        line.taxable += amount;
+                        line.amount += tax;
+                    }
+                    None => tax_lines.push(TaxLine {
+                        rate_id: rate.id.clone(),
+                        name: rate.name.clone(),
+                        rate: rate.rate,
+                        taxable: amount,
+                        amount: tax,
+                    }),
+                }
+            }
+        }
+
+        self.subtotal = self.items.iter().fold(zero, |sum, i| sum + i.line_total());
+        self.discount = self.items.iter().fold(zero, |sum, i| sum + i.discount_total());
+        self.tax = tax_lines.iter().fold(zero, |sum, l| sum + l.amount);
+        self.total = match self.tax_mode {
+            TaxMode::Exclusive => self.subtotal - self.discount + self.tax,
+            TaxMode::Inclusive => self.subtotal - self.discount,
+        };
+        self.tax_lines = tax_lines;
+    }
+
+    pub fn paid_total(&self) -> Money {
+        self.payments.iter().fold(Money::zero(self.currency), |sum, p| sum + p.amount)
+    }
+
+    pub fn balance_due(&self) -> Money {
+        self.total - self.paid_total()
+    }
+
+    // When the last payment covering the total was taken; `None` for an
+    // order that was never fully paid, including one voided part-way.
+    pub fn paid_at(&self) -> Option<DateTime<Utc>> {
+        if self.payments.is_empty() || self.paid_total() < self.total {
+            return None;
+        }
+        self.payments.iter().map(|p| p.processed_at).max()
+    }
+
+    // Takes one tender towards the balance. The order becomes `Paid` only
+    // once the payments cover the total.
+    pub fn add_payment(&mut self, payment: Payment) -> Result<(), PaymentError> {
+        if self.status != OrderStatus::Pending {
+            return Err(PaymentError::NotPending);
+        }
+        let zero = Money::zero(self.currency);
+        if payment.amount <= zero {
+            return Err(PaymentError::NothingToPay);
+        }
+        if payment.amount > self.balance_due() {
+            return Err(PaymentError::ExceedsBalance(self.balance_due()));
+        }
+
+        self.payments.push(payment);
+        if self.balance_due() <= zero {
+            self.status = OrderStatus::Paid;
+        }
+        Ok(())
+    }
+
+    // Backs out a tender taken by mistake while the sale is still open.
+    pub fn remove_payment(&mut self, payment_id: &str) -> Result<Payment, PaymentError> {
+        if self.status != OrderStatus::Pending {
+            return Err(PaymentError::NotPending);
+        }
+        let pos = self
+            .payments
+            .iter()
+            .position(|p| p.id == payment_id)
+            .ok_or(PaymentError::UnknownPayment)?;
+        Ok(self.payments.remove(pos))
+    }
+
+    // The balance shared between `parts` payers; shares differ by at most
+    // one minor unit and the larger ones come first.
+    pub fn split_evenly(&self, parts: u32) -> Vec<Money> {
+        self.balance_due().split(parts)
+    }
+
+    // What the listed lines cost including their tax, for a payer settling
+    // only their own items. Taxes are rounded per line, so shares for every
+    // line add up to the order total exactly.
+    pub fn lines_total(&self, line_ids: &[String]) -> Money {
+        let zero = Money::zero(self.currency);
+        self.items
+            .iter()
+            .filter(|i| line_ids.contains(&i.line_id))
+            .fold(zero, |sum, item| {
+                let amount = item.net_amount();
+                let tax = line_taxes(amount, self.rates(item), self.tax_mode)
+                    .into_iter()
+                    .fold(zero, |sum, t| sum + t);
+                sum + match self.tax_mode {
+                    TaxMode::Exclusive => amount + tax,
+                    TaxMode::Inclusive => amount,
+                }
+            })
+    }
+
+    pub fn refunded_quantity(&self, line_id: &str) -> u32 {
+        self.refunds
+            .iter()
+            .flat_map(|r| &r.lines)
+            .filter(|l| l.line_id == line_id)
+            .map(|l| l.quantity)
+            .sum()
+    }
+
+    // Gift cards sold are never refunded; the sale is voided instead.
+    pub fn refundable_quantity(&self, line_id: &str) -> u32 {
+        self.item(line_id)
+            .filter(|i| i.gift_card.is_none())
+            .map_or(0, |i| i.quantity)
+            .saturating_sub(self.refunded_quantity(line_id))
+    }
+
+    // What refunds have already returned through `payment_id`.
+    pub fn refunded_from(&self, payment_id: &str) -> Money {
+        self.refunds
+            .iter()
+            .flat_map(|r| &r.tenders)
+            .filter(|t| t.payment_id.as_deref() == Some(payment_id))
+            .fold(Money::zero(self.currency), |sum, t| sum + t.amount)
+    }
+
+    // Splits `total` over the payments by what each has left after earlier
+    // refunds: the latest payment that can take it all, otherwise the
+    // latest ones first until it is covered.
+    fn refund_tenders(&self, total: Money) -> Vec<RefundTender> {
+        let zero = Money::zero(self.currency);
+        let left: Vec<(&Payment, Money)> = self
+            .payments
+            .iter()
+            .rev()
+            .map(|p| (p, p.amount - self.refunded_from(&p.id)))
+            .filter(|(_, left)| *left > zero)
+            .collect();
+        let tender = |payment: &Payment, amount| RefundTender {
+            payment_id: Some(payment.id.clone()),
+            method: payment.method.clone(),
+            amount,
+        };
+        if let Some((payment, _)) = left.iter().find(|(_, left)| *left >= total) {
+            return vec![tender(payment, total)];
+        }
+        let mut owed = total;
+        let mut tenders = Vec::new();
+        for (payment, left) in left {
+            if owed <= zero {
+                break;
+            }
+            let amount = if left < owed { left } else { owed };
+            tenders.push(tender(payment, amount));
+            owed -= amount;
+        }
+        tenders
+    }
+
+    pub fn refunded_total(&self) -> Money {
+        self.refunds.iter().fold(Money::zero(self.currency), |sum, r| sum + r.total)
+    }
+
+    // What the order is still worth after refunds; nothing once voided.
+    pub fn net_total(&self) -> Money {
+        match self.status {
+            OrderStatus::Paid | OrderStatus::PartiallyRefunded | OrderStatus::Refunded => {
+                self.total - self.refunded_total()
+            }
+            OrderStatus::Pending | OrderStatus::Cancelled => Money::zero(self.currency),
+        }
+    }
+
+    // Refunds `quantity` units of each listed line. When the last units of
+    // a line go back, its remaining tax is returned exactly so full refunds
+    // always reconcile to the cent with what was charged.
+    pub fn refund(
+        &mut self,
+        quantities: &[(String, u32)],
+        reason: ReasonCode,
+        note: Option<String>,
+        restock: bool,
+    ) -> Result<Refund, RefundError> {
+        if !matches!(self.status, OrderStatus::Paid | OrderStatus::PartiallyRefunded) {
+            return Err(RefundError::NotPaid);
+        }
+
+        let zero = Money::zero(self.currency);
+        let mut lines: Vec<RefundLine> = Vec::new();
+
+        for (line_id, quantity) in quantities.iter().filter(|(_, q)| *q > 0) {
+            let item = self
+                .item(line_id)
+                .ok_or_else(|| RefundError::UnknownItem(line_id.clone()))?;
+            let refundable = self.refundable_quantity(line_id);
+            if *quantity > refundable {
+                return Err(RefundError::ExceedsRefundable {
+                    product_name: item.label(),
+                    refundable,
+                });
+            }
+
+            // Units go back at what was paid for them after discounts.
+            let sum_tax = |taxes: Vec<Money>| taxes.into_iter().fold(zero, |sum, t| sum + t);
+            let (amount, tax) = if *quantity == refundable {
+                let earlier = self
+                    .refunds
+                    .iter()
+                    .flat_map(|r| &r.lines)
+                    .filter(|l| &l.line_id == line_id);
+                let returned = earlier.clone().fold(zero, |sum, l| sum + l.amount);
+                let returned_tax = earlier.fold(zero, |sum, l| sum + l.tax);
+                let charged = sum_tax(line_taxes(item.net_amount(), self.rates(item), self.tax_mode));
+                (item.net_amount() - returned, charged - returned_tax)
+            } else {
+                let amount = item.unit_share(*quantity);
+                (amount, sum_tax(line_taxes(amount, self.rates(item), self.tax_mode)))
+            };
+
+            lines.push(RefundLine {
+                line_id: line_id.clone(),
+                product_id: item.product_id.clone(),
+                product_name: item.label(),
+                quantity: *quantity,
+                amount,
+                tax,
+            });
+        }
+
+        if lines.is_empty() {
+            return Err(RefundError::NothingSelected);
+        }
+
+        let subtotal = lines.iter().fold(zero, |sum, l| sum + l.amount);
+        let tax = lines.iter().fold(zero, |sum, l| sum + l.tax);
+        let total = match self.tax_mode {
+            TaxMode::Exclusive => subtotal + tax,
+            TaxMode::Inclusive => subtotal,
+        };
+
+        let refund = Refund {
+            id: Uuid::new_v4().to_string(),
+            order_id: self.id.clone(),
+            tenders: self.refund_tenders(total),
+            lines,
+            subtotal,
+            tax,
+            total,
+            reason,
+            note,
+            restocked: restock,
+            store_credit: None,
+            created_at: Utc::now(),
+        };
+        self.refunds.push(refund.clone());
+        self.set_refund_status();
+        Ok(refund)
+    }
+
+    // Takes back refund `refund_id` when the money never left, e.g. the
+    // card processor refused it.
+    pub fn undo_refund(&mut self, refund_id: &str) {
+        self.refunds.retain(|r| r.id != refund_id);
+        self.set_refund_status();
+    }
+
+    fn set_refund_status(&mut self) {
+        let fully_refunded = self
+            .items
+            .iter()
+            .all(|i| self.refundable_quantity(&i.line_id) == 0);
+        self.status = if self.refunds.is_empty() {
+            OrderStatus::Paid
+        } else if fully_refunded {
+            OrderStatus::Refunded
+        } else {
+            OrderStatus::PartiallyRefunded
+        };
+    }
+
+    // Pays refund `refund_id` out as store credit on card `number` rather
+    // than back to the tender it came from.
+    pub fn credit_refund(&mut self, refund_id: &str, number: &str) {
+        if let Some(refund) = self.refunds.iter_mut().find(|r| r.id == refund_id) {
+            refund.tenders = vec![RefundTender {
+                payment_id: None,
+                method: PaymentMethod::StoreCredit,
+                amount: refund.total,
+            }];
+            refund.store_credit = Some(number.to_string());
+        }
+    }
+
+    // Changes the tip on a card payment after it was authorized, e.g. from
+    // the signed slip. The processor must be told first; this only records
+    // it. Returns the tip it replaced.
+    pub fn adjust_tip(&mut self, payment_id: &str, tip: Money) -> Result<Money, TipError> {
+        if self.status == OrderStatus::Cancelled {
+            return Err(TipError::NotAdjustable);
+        }
+        let payment = self
+            .payments
+            .iter_mut()
+            .find(|p| p.id == payment_id)
+            .ok_or(TipError::UnknownPayment)?;
+        if payment.card.is_none() {
+            return Err(TipError::NotCard);
+        }
+        if tip.minor() < 0 {
+            return Err(TipError::Negative);
+        }
+        let previous = payment.tip_amount();
+        payment.tip = Some(tip).filter(|t| t.minor() > 0);
+        Ok(previous)
+    }
+
+    pub fn tip_total(&self) -> Money {
+        self.payments.iter().fold(Money::zero(self.currency), |sum, p| sum + p.tip_amount())
+    }
+
+    // Cancels the whole order: a pending sale abandoned at the till, or a
+    // paid sale reversed in full before any refund was made against it.
+    pub fn void(&mut self, reason: ReasonCode, note: Option<String>) -> Result<(), RefundError> {
+        match self.status {
+            OrderStatus::Pending | OrderStatus::Paid => {}
+            OrderStatus::PartiallyRefunded | OrderStatus::Refunded => {
+                return Err(RefundError::AlreadyRefunded)
+            }
+            OrderStatus::Cancelled => return Err(RefundError::AlreadyVoided),
+        }
+
+        self.status = OrderStatus::Cancelled;
+        self.void = Some(VoidRecord {
+            reason,
+            note,
+            voided_at: Utc::now(),
+        });
+        Ok(())
+    }
+}
+
+impl OrderItem {
+    // Name with the chosen options, e.g. "Latte (Large, Oat Milk)".
+    pub fn label(&self) -> String {
+        if self.options.is_empty() {
+            return self.product_name.clone();
+        }
+        let names: Vec<&str> = self.options.iter().map(|o| o.name.as_str()).collect();
+        format!("{} ({})", self.product_name, names.join(", "))
+    }
+
+    // Before discounts.
+    pub fn line_total(&self) -> Money {
+        self.price * self.quantity
+    }
+
+    pub fn discount_total(&self) -> Money {
+        self.discounts
+            .iter()
+            .fold(self.order_discount, |sum, d| sum + d.amount)
+    }
+
+    // What the line costs after its discounts and its share of the order's.
+    pub fn net_amount(&self) -> Money {
+        self.line_total() - self.discount_total()
     }
 
-    pub fn complete_payment(&mut self, payment: Payment) {
-        self.payment = Some(payment);
-        self.status = OrderStatus::Paid;
+    // The discounted price of `quantity` units, rounded half up.
+    pub fn unit_share(&self, quantity: u32) -> Money {
+        let net = self.net_amount();
+        let minor = Rounding::HalfUp.divide(
+            net.minor() as i128 * i128::from(quantity),
+            i128::from(self.quantity.max(1)),
+        );
+        Money::new(minor as i64, net.currency())
     }
 }
 
@@ -93,6 +775,7 @@ impl OrderStatus {
         match self {
             OrderStatus::Pending => "Pending",
             OrderStatus::Paid => "Paid",
+            OrderStatus::PartiallyRefunded => "Partially Refunded",
             OrderStatus::Refunded => "Refunded",
             OrderStatus::Cancelled => "Cancelled",
         }
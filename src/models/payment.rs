@@ -1,38 +1,160 @@
+use std::fmt;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use super::money::Money;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Payment {
+    pub id: String,
     pub method: PaymentMethod,
-    pub amount: f64,
+    pub amount: Money,
+    // Cash only: what the customer handed over and what they got back.
+    #[serde(default)]
+    pub tendered: Option<Money>,
+    #[serde(default)]
+    pub change: Option<Money>,
+    // Cash rounding kept on top of `amount` when this payment settled the
+    // balance; `amount` is always what counts against the order.
+    #[serde(default)]
+    pub rounding: Option<Money>,
+    // Card only: what the processor approved. Its transaction is keyed by `id`.
+    #[serde(default)]
+    pub card: Option<CardDetails>,
+    // Gift card and store credit only: number of the card paid from.
+    #[serde(default)]
+    pub gift_card: Option<String>,
+    // Paid on top of `amount` and never part of the order total. A cash tip
+    // is included in `tendered`; a card tip in what the processor captured.
+    #[serde(default)]
+    pub tip: Option<Money>,
+    // Employee signed in when the payment was taken.
+    #[serde(default)]
+    pub operator_id: Option<String>,
     pub processed_at: DateTime<Utc>,
 }
 
+// Never the full card number: only brand, last digits and the approval.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CardDetails {
+    pub auth_code: String,
+    pub brand: CardBrand,
+    pub masked_pan: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum CardBrand {
+    Visa,
+    Mastercard,
+    Amex,
+    Discover,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaymentError {
+    NotPending,
+    NothingToPay,
+    ExceedsBalance(Money),
+    UnknownPayment,
+    // The order can't shrink below what was already paid.
+    PaymentsTaken,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum PaymentMethod {
     Cash,
     CreditCard,
     DebitCard,
     MobilePay,
+    // Loyalty points of the order's customer.
+    Points,
+    GiftCard,
+    StoreCredit,
 }
 
 impl Payment {
-    pub fn new(method: PaymentMethod, amount: f64) -> Self {
+    pub fn new(method: PaymentMethod, amount: Money) -> Self {
         Self {
+            id: Uuid::new_v4().to_string(),
             method,
             amount,
+            tendered: None,
+            change: None,
+            rounding: None,
+            card: None,
+            gift_card: None,
+            tip: None,
+            operator_id: None,
             processed_at: Utc::now(),
         }
     }
+
+    // Cash towards `target`, which the drawer collects as `due` after cash
+    // rounding. Enough to cover `due` settles the target and hands back the
+    // change; less is taken as a partial payment. `None` for nothing tendered.
+    pub fn cash(target: Money, due: Money, tendered: Money) -> Option<Self> {
+        let zero = Money::zero(target.currency());
+        if tendered <= zero || target <= zero {
+            return None;
+        }
+        let (amount, kept) = if tendered >= due {
+            (target, due)
+        } else if tendered >= target {
+            (target, target)
+        } else {
+            (tendered, tendered)
+        };
+        Some(Self {
+            tendered: Some(tendered),
+            change: Some(tendered - kept),
+            rounding: Some(kept - amount).filter(|r| *r != zero),
+            ..Self::new(PaymentMethod::Cash, amount)
+        })
+    }
+
+    // Adds `tip` on top of the payment. Cash tendered for the tip counts as
+    // tendered, so the change stays the same.
+    pub fn with_tip(self, tip: Money) -> Self {
+        if tip.minor() <= 0 {
+            return self;
+        }
+        Self {
+            tip: Some(tip),
+            tendered: self.tendered.map(|t| t + tip),
+            ..self
+        }
+    }
+
+    pub fn tip_amount(&self) -> Money {
+        self.tip.unwrap_or(Money::zero(self.amount.currency()))
+    }
 }
 
 impl PaymentMethod {
+    // Methods that must be authorized on the card terminal.
+    pub fn uses_terminal(&self) -> bool {
+        matches!(self, PaymentMethod::CreditCard | PaymentMethod::DebitCard)
+    }
+
+    // Tenders a tip can be added to. Points and stored value pay for the
+    // sale only.
+    pub fn takes_tip(&self) -> bool {
+        matches!(
+            self,
+            PaymentMethod::Cash | PaymentMethod::CreditCard | PaymentMethod::DebitCard | PaymentMethod::MobilePay
+        )
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             PaymentMethod::Cash => "Cash",
             PaymentMethod::CreditCard => "Credit Card",
             PaymentMethod::DebitCard => "Debit Card",
             PaymentMethod::MobilePay => "Mobile Pay",
+            PaymentMethod::Points => "Points",
+            PaymentMethod::GiftCard => "Gift Card",
+            PaymentMethod::StoreCredit => "Store Credit",
         }
     }
 
@@ -42,6 +164,43 @@ impl PaymentMethod {
             PaymentMethod::CreditCard => "💳",
             PaymentMethod::DebitCard => "💳",
             PaymentMethod::MobilePay => "📱",
+            PaymentMethod::Points => "⭐",
+            PaymentMethod::GiftCard => "🎁",
+            PaymentMethod::StoreCredit => "🧾",
+        }
+    }
+}
+
+impl CardBrand {
+    pub fn as_str(&self) -> &str {
+        match self {
+            CardBrand::Visa => "Visa",
+            CardBrand::Mastercard => "Mastercard",
+            CardBrand::Amex => "Amex",
+            CardBrand::Discover => "Discover",
+            CardBrand::Other => "Card",
         }
     }
 }
+
+impl CardDetails {
+    // "Visa •••• 4242"
+    pub fn summary(&self) -> String {
+        let last4 = &self.masked_pan[self.masked_pan.len().saturating_sub(4)..];
+        format!("{} •••• {last4}", self.brand.as_str())
+    }
+}
+
+impl fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentError::NotPending => write!(f, "the order is no longer open for payment"),
+            PaymentError::NothingToPay => write!(f, "enter an amount to pay"),
+            PaymentError::ExceedsBalance(balance) => write!(f, "only {balance} is left to pay"),
+            PaymentError::UnknownPayment => write!(f, "no such payment on this order"),
+            PaymentError::PaymentsTaken => write!(f, "payments were already taken; remove them or void the order"),
+        }
+    }
+}
+
+impl std::error::Error for PaymentError {}
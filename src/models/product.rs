@@ -1,26 +1,63 @@
+use std::fmt;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use super::barcode::{barcode_is_valid, same_barcode};
+use super::modifier::OptionGroup;
+use super::money::Money;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Product {
     pub id: String,
     pub name: String,
-    pub price: f64,
-    pub category: ProductCategory,
+    pub price: Money,
+    // Id of one of the store's `Category`s.
+    pub category: String,
     pub image_url: Option<String>,
-    pub stock: u32,
+    // Signed so an allowed oversell shows up as negative stock.
+    pub stock: i64,
+    // Overrides the category's tax class when set.
+    #[serde(default)]
+    pub tax_class: Option<String>,
+    // Variant and modifier groups offered when the product is rung up.
+    #[serde(default)]
+    pub options: Vec<OptionGroup>,
+    // Store's own stock keeping code; unique when set.
+    #[serde(default)]
+    pub sku: Option<String>,
+    // Codes printed on the packaging, or the seven-digit item code of an
+    // in-store scale label; each belongs to one product only.
+    #[serde(default)]
+    pub barcodes: Vec<String>,
+    // Every price change, oldest first.
+    #[serde(default)]
+    pub price_history: Vec<PriceChange>,
+    // Taken off sale but kept, so past orders and stock movements still find
+    // the product.
+    #[serde(default)]
+    pub archived: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ProductCategory {
-    Food,
-    Beverage,
-    Retail,
-    Service,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceChange {
+    pub at: DateTime<Utc>,
+    pub from: Money,
+    pub to: Money,
+    // Name of the employee who made it.
+    pub by: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProductError {
+    EmptyName,
+    NegativePrice,
+    DuplicateSku(String),
+    InvalidBarcode(String),
+    DuplicateBarcode(String),
 }
 
 impl Product {
-    pub fn new(name: String, price: f64, category: ProductCategory) -> Self {
+    pub fn new(name: String, price: Money, category: String) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             name,
@@ -28,26 +65,111 @@ impl Product {
             category,
             image_url: None,
             stock: 100,
+            tax_class: None,
+            options: Vec::new(),
+            sku: None,
+            barcodes: Vec::new(),
+            price_history: Vec::new(),
+            archived: false,
         }
     }
-}
 
-impl ProductCategory {
-    pub fn as_str(&self) -> &str {
-        match self {
-            ProductCategory::Food => "Food",
-            ProductCategory::Beverage => "Beverage",
-            ProductCategory::Retail => "Retail",
-            ProductCategory::Service => "Service",
+    // Checks a new or edited product against the rest of the catalog,
+    // archived products included.
+    pub fn validate(&self, catalog: &[Product]) -> Result<(), ProductError> {
+        if self.name.trim().is_empty() {
+            return Err(ProductError::EmptyName);
+        }
+        if self.price.minor() < 0 {
+            return Err(ProductError::NegativePrice);
+        }
+        let others = catalog.iter().filter(|p| p.id != self.id);
+        if let Some(sku) = &self.sku {
+            if others.clone().any(|p| p.sku.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(sku))) {
+                return Err(ProductError::DuplicateSku(sku.clone()));
+            }
+        }
+        for (i, code) in self.barcodes.iter().enumerate() {
+            if !barcode_is_valid(code) {
+                return Err(ProductError::InvalidBarcode(code.clone()));
+            }
+            let taken = |b: &String| same_barcode(b, code);
+            if self.barcodes[..i].iter().any(taken) || others.clone().any(|p| p.barcodes.iter().any(taken)) {
+                return Err(ProductError::DuplicateBarcode(code.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    // Takes over the fields of `edited`, logging a price change. Stock is
+    // left alone: the difference is returned so it can be posted as a
+    // stock movement.
+    pub fn apply_edit(&mut self, edited: Product, by: Option<String>) -> i64 {
+        if edited.price != self.price {
+            self.price_history.push(PriceChange {
+                at: Utc::now(),
+                from: self.price,
+                to: edited.price,
+                by,
+            });
+        }
+        let stock_change = edited.stock - self.stock;
+        let history = std::mem::take(&mut self.price_history);
+        let stock = self.stock;
+        *self = Product {
+            id: self.id.clone(),
+            stock,
+            price_history: history,
+            archived: self.archived,
+            ..edited
+        };
+        stock_change
+    }
+
+    // Short description of what an edit changes, e.g. "price $4.50 → $5.00".
+    pub fn changes(&self, edited: &Product) -> Vec<String> {
+        let mut changes = Vec::new();
+        if edited.name != self.name {
+            changes.push(format!("name {} → {}", self.name, edited.name));
+        }
+        if edited.price != self.price {
+            changes.push(format!("price {} → {}", self.price, edited.price));
+        }
+        if edited.category != self.category {
+            changes.push("category".to_string());
+        }
+        if edited.tax_class != self.tax_class {
+            changes.push("tax class".to_string());
+        }
+        if edited.stock != self.stock {
+            changes.push(format!("stock {} → {}", self.stock, edited.stock));
         }
+        if edited.sku != self.sku {
+            changes.push("SKU".to_string());
+        }
+        if edited.barcodes != self.barcodes {
+            changes.push("barcodes".to_string());
+        }
+        if edited.image_url != self.image_url {
+            changes.push("image".to_string());
+        }
+        if edited.options != self.options {
+            changes.push("options".to_string());
+        }
+        changes
     }
+}
 
-    pub fn color(&self) -> &str {
+impl fmt::Display for ProductError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ProductCategory::Food => "#FF6B6B",
-            ProductCategory::Beverage => "#4ECDC4",
-            ProductCategory::Retail => "#95E1D3",
-            ProductCategory::Service => "#FFA07A",
+            ProductError::EmptyName => write!(f, "product name is required"),
+            ProductError::NegativePrice => write!(f, "price can't be negative"),
+            ProductError::DuplicateSku(sku) => write!(f, "SKU {sku} is already used by another product"),
+            ProductError::InvalidBarcode(code) => write!(f, "barcode {code} has a wrong check digit"),
+            ProductError::DuplicateBarcode(code) => write!(f, "barcode {code} is already used by another product"),
         }
     }
 }
+
+impl std::error::Error for ProductError {}
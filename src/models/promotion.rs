@@ -0,0 +1,251 @@
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::category::{category_name, Category};
+use super::discount::{allocate, DiscountKind};
+use super::money::{Money, Rate, Rounding};
+use super::order::OrderItem;
+
+// A discount the till applies by itself when an order qualifies.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Promotion {
+    pub id: String,
+    pub name: String,
+    pub rule: PromotionRule,
+    // Lower numbers are applied first.
+    pub priority: u32,
+    // A promotion that doesn't stack never shares a line with another
+    // discount, or for order discounts, the order.
+    pub stackable: bool,
+    // Only applies once this code has been entered on the order.
+    pub coupon_code: Option<String>,
+    // Only applies at these times, e.g. a happy hour.
+    pub schedule: Option<Schedule>,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PromotionRule {
+    // Percentage off every qualifying line.
+    PercentOff { target: PromoTarget, off: Rate },
+    // For every `buy` units, `get` more are `off` (100% is free). BOGO is
+    // buy 1 get 1 at 100%.
+    BuyXGetY { target: PromoTarget, buy: u32, get: u32, off: Rate },
+    // Any `quantity` items from the category (by id, sub-categories
+    // included) for `price`.
+    MixAndMatch { category: String, quantity: u32, price: Money },
+    // Percentage off a line once its quantity reaches a tier.
+    QuantityBreak { target: PromoTarget, tiers: Vec<QuantityTier> },
+    // Off the whole order once its subtotal reaches `min_subtotal`.
+    OrderOff { kind: DiscountKind, min_subtotal: Option<Money> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PromoTarget {
+    All,
+    // Category id; covers its sub-categories too.
+    Category(String),
+    Product(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuantityTier {
+    pub min_quantity: u32,
+    pub off: Rate,
+}
+
+// Days of the week (empty for every day) and a local time window. A window
+// whose end is before its start runs past midnight.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Promotion {
+    pub fn new(name: String, rule: PromotionRule) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            rule,
+            priority: 10,
+            stackable: false,
+            coupon_code: None,
+            schedule: None,
+            active: true,
+        }
+    }
+
+    // Whether the promotion applies to an order priced at `at` (local time)
+    // with `coupons` entered.
+    pub fn in_effect(&self, at: NaiveDateTime, coupons: &[String]) -> bool {
+        let scheduled = match &self.schedule {
+            Some(schedule) => schedule.contains(at),
+            None => true,
+        };
+        let coupon_entered = match &self.coupon_code {
+            Some(code) => coupons.contains(code),
+            None => true,
+        };
+        self.active && scheduled && coupon_entered
+    }
+}
+
+impl PromotionRule {
+    pub fn is_order_rule(&self) -> bool {
+        matches!(self, PromotionRule::OrderOff { .. })
+    }
+
+    pub fn applies_to(&self, item: &OrderItem) -> bool {
+        match self {
+            PromotionRule::PercentOff { target, .. }
+            | PromotionRule::BuyXGetY { target, .. }
+            | PromotionRule::QuantityBreak { target, .. } => target.matches(item),
+            PromotionRule::MixAndMatch { category, .. } => item.category_ids.contains(category),
+            PromotionRule::OrderOff { .. } => false,
+        }
+    }
+
+    // Discount per line for the lines at `eligible`, before capping at what
+    // each line still costs.
+    pub(crate) fn line_discounts(&self, items: &[OrderItem], eligible: &[usize]) -> Vec<(usize, Money)> {
+        match self {
+            PromotionRule::PercentOff { off, .. } => eligible
+                .iter()
+                .map(|&i| (i, items[i].net_amount().apply_rate(*off, Rounding::HalfUp)))
+                .collect(),
+            PromotionRule::QuantityBreak { tiers, .. } => eligible
+                .iter()
+                .filter_map(|&i| {
+                    let tier = tiers
+                        .iter()
+                        .filter(|t| items[i].quantity >= t.min_quantity)
+                        .max_by_key(|t| t.min_quantity)?;
+                    Some((i, items[i].net_amount().apply_rate(tier.off, Rounding::HalfUp)))
+                })
+                .collect(),
+            PromotionRule::BuyXGetY { buy, get, off, .. } => {
+                // Units dearest first, in groups of buy + get; the cheapest
+                // `get` units of each full group are discounted.
+                let group = (*buy + *get) as usize;
+                let units = units_by_price(items, eligible);
+                let mut discounts = Vec::new();
+                for chunk in units.chunks_exact(group.max(1)) {
+                    for &(i, price) in &chunk[*buy as usize..] {
+                        discounts.push((i, price.apply_rate(*off, Rounding::HalfUp)));
+                    }
+                }
+                merge(discounts)
+            }
+            PromotionRule::MixAndMatch { quantity, price, .. } => {
+                // Each full group of `quantity` units, dearest first, sells
+                // for `price`; the saving is shared over the group's units in
+                // proportion to their prices.
+                let units = units_by_price(items, eligible);
+                let mut discounts = Vec::new();
+                for chunk in units.chunks_exact((*quantity as usize).max(1)) {
+                    let full: Vec<Money> = chunk.iter().map(|&(_, p)| p).collect();
+                    let saving = full.iter().fold(Money::zero(price.currency()), |sum, p| sum + *p) - *price;
+                    if saving.minor() <= 0 {
+                        continue;
+                    }
+                    for (&(i, _), share) in chunk.iter().zip(allocate(saving, &full)) {
+                        discounts.push((i, share));
+                    }
+                }
+                merge(discounts)
+            }
+            PromotionRule::OrderOff { .. } => Vec::new(),
+        }
+    }
+
+    // Short description for lists, e.g. "Buy 2 get 1 free".
+    pub fn summary(&self, categories: &[Category]) -> String {
+        match self {
+            PromotionRule::PercentOff { target, off } => format!("{off} off {}", target.label(categories)),
+            PromotionRule::BuyXGetY { target, buy, get, off } if off.0 == 1_000_000 => {
+                format!("Buy {buy} get {get} free, {}", target.label(categories))
+            }
+            PromotionRule::BuyXGetY { target, buy, get, off } => {
+                format!("Buy {buy} get {get} at {off} off, {}", target.label(categories))
+            }
+            PromotionRule::MixAndMatch { category, quantity, price } => {
+                format!("Any {quantity} {} for {price}", category_name(categories, category))
+            }
+            PromotionRule::QuantityBreak { target, tiers } => {
+                let tiers: Vec<String> = tiers.iter().map(|t| format!("{}+ {} off", t.min_quantity, t.off)).collect();
+                format!("{}, {}", tiers.join(" / "), target.label(categories))
+            }
+            PromotionRule::OrderOff { kind, min_subtotal } => match min_subtotal {
+                Some(min) => format!("{} order over {min}", kind.label()),
+                None => format!("{} order", kind.label()),
+            },
+        }
+    }
+}
+
+impl PromoTarget {
+    pub fn matches(&self, item: &OrderItem) -> bool {
+        match self {
+            PromoTarget::All => true,
+            PromoTarget::Category(id) => item.category_ids.contains(id),
+            PromoTarget::Product(id) => &item.product_id == id,
+        }
+    }
+
+    pub fn label(&self, categories: &[Category]) -> String {
+        match self {
+            PromoTarget::All => "all items".to_string(),
+            PromoTarget::Category(id) => category_name(categories, id).to_string(),
+            PromoTarget::Product(_) => "one product".to_string(),
+        }
+    }
+}
+
+impl Schedule {
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        let time = at.time();
+        let in_window = if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        };
+        // Past midnight the window still belongs to the day it started.
+        let day = if self.start > self.end && time < self.end {
+            at.weekday().pred()
+        } else {
+            at.weekday()
+        };
+        in_window && (self.days.is_empty() || self.days.contains(&day))
+    }
+}
+
+// Coupon codes are matched without regard to case or surrounding spaces.
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+// One entry per unit of the eligible lines, dearest first.
+// `iter::repeat_n` would need Rust 1.82.
+#[allow(clippy::manual_repeat_n)]
+fn units_by_price(items: &[OrderItem], eligible: &[usize]) -> Vec<(usize, Money)> {
+    let mut units: Vec<(usize, Money)> = eligible
+        .iter()
+        .flat_map(|&i| std::iter::repeat((i, items[i].price)).take(items[i].quantity as usize))
+        .collect();
+    units.sort_by_key(|u| std::cmp::Reverse(u.1.minor()));
+    units
+}
+
+// Sums per-unit discounts into one amount per line.
+fn merge(discounts: Vec<(usize, Money)>) -> Vec<(usize, Money)> {
+    let mut merged: Vec<(usize, Money)> = Vec::new();
+    for (i, amount) in discounts {
+        match merged.iter_mut().find(|(j, _)| *j == i) {
+            Some((_, sum)) => *sum += amount,
+            None => merged.push((i, amount)),
+        }
+    }
+    merged
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use super::money::Money;
+use super::payment::PaymentMethod;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReasonCode {
+    CustomerReturn,
+    Defective,
+    WrongItem,
+    PriceAdjustment,
+    Duplicate,
+    OperatorError,
+    Other,
+}
+
+// Money handed back against a paid order. Linked to the order it reverses
+// and to the payments it was returned through.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Refund {
+    pub id: String,
+    pub order_id: String,
+    // Where the money went, adding up to `total`. Empty only when the order
+    // had no payments to return it through.
+    #[serde(default)]
+    pub tenders: Vec<RefundTender>,
+    pub lines: Vec<RefundLine>,
+    pub subtotal: Money,
+    pub tax: Money,
+    pub total: Money,
+    pub reason: ReasonCode,
+    pub note: Option<String>,
+    pub restocked: bool,
+    // Number of the store credit the refund was paid out to, instead of
+    // the tenders it was taken with.
+    #[serde(default)]
+    pub store_credit: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// The part of a refund returned through one payment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RefundTender {
+    // `None` for store credit, which no payment on the order took.
+    pub payment_id: Option<String>,
+    pub method: PaymentMethod,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RefundLine {
+    // The order line refunded.
+    pub line_id: String,
+    pub product_id: String,
+    pub product_name: String,
+    pub quantity: u32,
+    pub amount: Money,
+    pub tax: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoidRecord {
+    pub reason: ReasonCode,
+    pub note: Option<String>,
+    pub voided_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefundError {
+    NotPaid,
+    NothingSelected,
+    UnknownItem(String),
+    ExceedsRefundable { product_name: String, refundable: u32 },
+    AlreadyRefunded,
+    AlreadyVoided,
+}
+
+impl Refund {
+    // What went back through `method`.
+    pub fn amount_to(&self, method: &PaymentMethod) -> Money {
+        self.tenders
+            .iter()
+            .filter(|t| &t.method == method)
+            .fold(Money::zero(self.total.currency()), |sum, t| sum + t.amount)
+    }
+
+    // "Credit Card", or "Credit Card 5.00, Cash 3.00" when split.
+    pub fn tender_summary(&self) -> String {
+        match self.tenders.as_slice() {
+            [] => "original tender".to_string(),
+            [tender] => tender.method.as_str().to_string(),
+            tenders => tenders
+                .iter()
+                .map(|t| format!("{} {}", t.method.as_str(), t.amount))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+impl ReasonCode {
+    pub const ALL: [ReasonCode; 7] = [
+        ReasonCode::CustomerReturn,
+        ReasonCode::Defective,
+        ReasonCode::WrongItem,
+        ReasonCode::PriceAdjustment,
+        ReasonCode::Duplicate,
+        ReasonCode::OperatorError,
+        ReasonCode::Other,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ReasonCode::CustomerReturn => "Customer Return",
+            ReasonCode::Defective => "Defective",
+            ReasonCode::WrongItem => "Wrong Item",
+            ReasonCode::PriceAdjustment => "Price Adjustment",
+            ReasonCode::Duplicate => "Duplicate",
+            ReasonCode::OperatorError => "Operator Error",
+            ReasonCode::Other => "Other",
+        }
+    }
+
+    pub fn from_label(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == value)
+    }
+}
+
+impl fmt::Display for RefundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefundError::NotPaid => write!(f, "only paid orders can be refunded"),
+            RefundError::NothingSelected => write!(f, "select at least one item to refund"),
+            RefundError::UnknownItem(id) => write!(f, "item {id} is not on this order"),
+            RefundError::ExceedsRefundable { product_name, refundable } => {
+                write!(f, "only {refundable} of {product_name} can still be refunded")
+            }
+            RefundError::AlreadyRefunded => write!(f, "order already has refunds and cannot be voided"),
+            RefundError::AlreadyVoided => write!(f, "order has already been voided"),
+        }
+    }
+}
+
+impl std::error::Error for RefundError {}
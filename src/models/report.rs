@@ -0,0 +1,380 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use super::money::{Currency, Money, Rate};
+use super::order::{Order, OrderItem};
+use super::payment::PaymentMethod;
+use super::tax::{line_taxes, TaxMode};
+use super::employee::Employee;
+use super::tip::{add_tip, TipTotal};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ReportKind {
+    // Mid-shift snapshot; changes nothing.
+    X,
+    // End of day; closes the period for good.
+    Z,
+}
+
+// Totals for one reporting period. Every figure counts when it happened: a
+// sale when it was paid, a refund or void when it was made. A refund today
+// against last week's sale lands in today's period, so a closed Z report
+// never changes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SalesReport {
+    pub kind: ReportKind,
+    // Z reports are numbered 1, 2, 3, ... without gaps.
+    pub number: Option<u32>,
+    // Exclusive; `None` for the very first period.
+    pub period_start: Option<DateTime<Utc>>,
+    pub period_end: DateTime<Utc>,
+    pub currency: Currency,
+    pub sales_count: u32,
+    pub refund_count: u32,
+    pub void_count: u32,
+    pub items_sold: i64,
+    // Sales figures are net of tax; tax is reported per rate.
+    pub gross_sales: Money,
+    pub discounts: Money,
+    pub refunds: Money,
+    pub voids: Money,
+    pub net_sales: Money,
+    pub taxes: Vec<RateTotal>,
+    pub tax_total: Money,
+    // Gift cards sold in the period less those voided. What they hold is
+    // owed to the holder, not earned, so it is left out of sales and counts
+    // as a sale only once spent.
+    #[serde(default)]
+    pub gift_cards_issued: Money,
+    // Net sales plus tax and gift cards issued: what the tenders below add
+    // up to.
+    pub total: Money,
+    pub cash_rounding: Money,
+    pub by_method: Vec<MethodTotal>,
+    pub by_category: Vec<CategoryTotal>,
+    // Each discount and promotion by name, as taken off the lines (so tax
+    // included when prices are).
+    #[serde(default)]
+    pub by_discount: Vec<DiscountTotal>,
+    // Tips on sales paid in the period, less those on sales voided in it.
+    // Outside `total` and the tenders' `collected`.
+    #[serde(default)]
+    pub tip_total: Money,
+    #[serde(default)]
+    pub tips: Vec<TipTotal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateTotal {
+    pub rate_id: String,
+    pub name: String,
+    pub rate: Rate,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MethodTotal {
+    pub method: PaymentMethod,
+    pub count: u32,
+    pub collected: Money,
+    pub refunded: Money,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiscountTotal {
+    pub name: String,
+    pub count: i64,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryTotal {
+    pub category: String,
+    pub quantity: i64,
+    pub amount: Money,
+}
+
+impl SalesReport {
+    pub fn build(
+        kind: ReportKind,
+        number: Option<u32>,
+        orders: &[Order],
+        employees: &[Employee],
+        period_start: Option<DateTime<Utc>>,
+        period_end: DateTime<Utc>,
+    ) -> Self {
+        let currency = orders.first().map(|o| o.currency).unwrap_or_default();
+        let zero = Money::zero(currency);
+        // `Option::is_none_or` would need Rust 1.82.
+        let in_period = |at: DateTime<Utc>| {
+            let after_start = match period_start {
+                Some(start) => at > start,
+                None => true,
+            };
+            after_start && at <= period_end
+        };
+
+        let mut report = Self {
+            kind,
+            number,
+            period_start,
+            period_end,
+            currency,
+            sales_count: 0,
+            refund_count: 0,
+            void_count: 0,
+            items_sold: 0,
+            gross_sales: zero,
+            discounts: zero,
+            refunds: zero,
+            voids: zero,
+            net_sales: zero,
+            taxes: Vec::new(),
+            tax_total: zero,
+            gift_cards_issued: zero,
+            total: zero,
+            cash_rounding: zero,
+            by_method: Vec::new(),
+            by_category: Vec::new(),
+            by_discount: Vec::new(),
+            tip_total: zero,
+            tips: Vec::new(),
+        };
+
+        for order in orders {
+            let paid_at = order.paid_at();
+            if paid_at.is_some_and(in_period) {
+                report.add_sale(order, employees);
+            }
+            for refund in order.refunds.iter().filter(|r| in_period(r.created_at)) {
+                report.refund_count += 1;
+                let gift_cards = refund
+                    .lines
+                    .iter()
+                    .filter(|l| order.item(&l.line_id).is_some_and(|i| i.gift_card.is_some()))
+                    .fold(zero, |sum, l| sum + l.amount);
+                report.refunds += refund.total - refund.tax - gift_cards;
+                report.gift_cards_issued -= gift_cards;
+                for tender in &refund.tenders {
+                    report.method(&tender.method).refunded += tender.amount;
+                }
+                for line in &refund.lines {
+                    let Some(item) = order.item(&line.line_id).filter(|i| i.gift_card.is_none()) else {
+                        continue;
+                    };
+                    let net = match order.tax_mode {
+                        TaxMode::Exclusive => line.amount,
+                        TaxMode::Inclusive => line.amount - line.tax,
+                    };
+                    report.items_sold -= i64::from(line.quantity);
+                    let category = report.category(item);
+                    category.quantity -= i64::from(line.quantity);
+                    category.amount -= net;
+                    // Split the line's tax across its rates; the last rate
+                    // takes any rounding difference so the total is exact.
+                    let rates = order.rates(item);
+                    let mut split = line_taxes(line.amount, rates, order.tax_mode);
+                    let allocated = split.iter().fold(zero, |sum, t| sum + *t);
+                    if let Some(last) = split.last_mut() {
+                        *last += line.tax - allocated;
+                    }
+                    for (rate, tax) in rates.iter().zip(split) {
+                        report.rate(&rate.id, &rate.name, rate.rate).amount -= tax;
+                    }
+                }
+            }
+            if order.void.as_ref().is_some_and(|v| in_period(v.voided_at)) {
+                report.void_count += 1;
+                // Only a paid order had anything to reverse.
+                if paid_at.is_some() {
+                    report.add_void(order, employees);
+                }
+            }
+        }
+
+        report.net_sales = report.gross_sales - report.discounts - report.refunds - report.voids;
+        report.tax_total = report.taxes.iter().fold(zero, |sum, t| sum + t.amount);
+        report.total = report.net_sales + report.tax_total + report.gift_cards_issued;
+        report.by_category.sort_by(|a, b| a.category.cmp(&b.category));
+        report.by_discount.sort_by(|a, b| a.name.cmp(&b.name));
+        report.tip_total = report.tips.iter().fold(zero, |sum, t| sum + t.cash + t.card);
+        report.tips.sort_by(|a, b| a.employee.cmp(&b.employee));
+        report
+    }
+
+    fn add_sale(&mut self, order: &Order, employees: &[Employee]) {
+        self.sales_count += 1;
+        // Gross is before discounts; what the discounts took is whatever
+        // separates it from the sale's own net.
+        let gross = sold_items(order)
+            .fold(Money::zero(self.currency), |sum, i| sum + net_of_tax(order, i, i.line_total()));
+        let gift_cards = gift_cards_sold(order);
+        self.gross_sales += gross;
+        self.discounts += gross - (order.total - order.tax - gift_cards);
+        self.gift_cards_issued += gift_cards;
+        for (name, amount) in applied_discounts(order) {
+            let total = self.discount(name);
+            total.count += 1;
+            total.amount += amount;
+        }
+        for line in &order.tax_lines {
+            self.rate(&line.rate_id, &line.name, line.rate).amount += line.amount;
+        }
+        for payment in &order.payments {
+            let method = self.method(&payment.method);
+            method.count += 1;
+            method.collected += payment.amount;
+            if let Some(rounding) = payment.rounding {
+                self.cash_rounding += rounding;
+            }
+            add_tip(&mut self.tips, payment, employees, false);
+        }
+        for item in sold_items(order) {
+            let net = item_net(order, item);
+            self.items_sold += i64::from(item.quantity);
+            let category = self.category(item);
+            category.quantity += i64::from(item.quantity);
+            category.amount += net;
+        }
+    }
+
+    // A void reverses the whole sale: every item, tax and payment.
+    fn add_void(&mut self, order: &Order, employees: &[Employee]) {
+        let gift_cards = gift_cards_sold(order);
+        self.voids += order.total - order.tax - gift_cards;
+        self.gift_cards_issued -= gift_cards;
+        for (name, amount) in applied_discounts(order) {
+            let total = self.discount(name);
+            total.count -= 1;
+            total.amount -= amount;
+        }
+        for line in &order.tax_lines {
+            self.rate(&line.rate_id, &line.name, line.rate).amount -= line.amount;
+        }
+        for payment in &order.payments {
+            self.method(&payment.method).refunded += payment.amount;
+            add_tip(&mut self.tips, payment, employees, true);
+        }
+        for item in sold_items(order) {
+            let net = item_net(order, item);
+            self.items_sold -= i64::from(item.quantity);
+            let category = self.category(item);
+            category.quantity -= i64::from(item.quantity);
+            category.amount -= net;
+        }
+    }
+
+    fn rate(&mut self, rate_id: &str, name: &str, rate: Rate) -> &mut RateTotal {
+        let pos = match self.taxes.iter().position(|t| t.rate_id == rate_id) {
+            Some(pos) => pos,
+            None => {
+                self.taxes.push(RateTotal {
+                    rate_id: rate_id.to_string(),
+                    name: name.to_string(),
+                    rate,
+                    amount: Money::zero(self.currency),
+                });
+                self.taxes.len() - 1
+            }
+        };
+        &mut self.taxes[pos]
+    }
+
+    fn method(&mut self, method: &PaymentMethod) -> &mut MethodTotal {
+        let pos = match self.by_method.iter().position(|m| &m.method == method) {
+            Some(pos) => pos,
+            None => {
+                self.by_method.push(MethodTotal {
+                    method: method.clone(),
+                    count: 0,
+                    collected: Money::zero(self.currency),
+                    refunded: Money::zero(self.currency),
+                });
+                self.by_method.len() - 1
+            }
+        };
+        &mut self.by_method[pos]
+    }
+
+    fn discount(&mut self, name: &str) -> &mut DiscountTotal {
+        let pos = match self.by_discount.iter().position(|d| d.name == name) {
+            Some(pos) => pos,
+            None => {
+                self.by_discount.push(DiscountTotal {
+                    name: name.to_string(),
+                    count: 0,
+                    amount: Money::zero(self.currency),
+                });
+                self.by_discount.len() - 1
+            }
+        };
+        &mut self.by_discount[pos]
+    }
+
+    fn category(&mut self, item: &OrderItem) -> &mut CategoryTotal {
+        let name = item.category.as_deref().unwrap_or("Uncategorized");
+        let pos = match self.by_category.iter().position(|c| c.category == name) {
+            Some(pos) => pos,
+            None => {
+                self.by_category.push(CategoryTotal {
+                    category: name.to_string(),
+                    quantity: 0,
+                    amount: Money::zero(self.currency),
+                });
+                self.by_category.len() - 1
+            }
+        };
+        &mut self.by_category[pos]
+    }
+}
+
+impl ReportKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ReportKind::X => "X",
+            ReportKind::Z => "Z",
+        }
+    }
+}
+
+// The lines that count as sales: all but gift cards sold.
+fn sold_items(order: &Order) -> impl Iterator<Item = &OrderItem> {
+    order.items.iter().filter(|i| i.gift_card.is_none())
+}
+
+// What the order loaded onto gift cards. Those lines are never taxed or
+// discounted.
+fn gift_cards_sold(order: &Order) -> Money {
+    order
+        .items
+        .iter()
+        .filter(|i| i.gift_card.is_some())
+        .fold(Money::zero(order.currency), |sum, i| sum + i.net_amount())
+}
+
+// An order line's sales value after discounts, net of tax.
+fn item_net(order: &Order, item: &OrderItem) -> Money {
+    net_of_tax(order, item, item.net_amount())
+}
+
+// `amount` of the line without the tax it carries.
+fn net_of_tax(order: &Order, item: &OrderItem, amount: Money) -> Money {
+    match order.tax_mode {
+        TaxMode::Exclusive => amount,
+        TaxMode::Inclusive => {
+            let tax = line_taxes(amount, order.rates(item), order.tax_mode)
+                .into_iter()
+                .fold(Money::zero(order.currency), |sum, t| sum + t);
+            amount - tax
+        }
+    }
+}
+
+// Every discount on the order with what it took off.
+fn applied_discounts(order: &Order) -> impl Iterator<Item = (&str, Money)> {
+    order
+        .items
+        .iter()
+        .flat_map(|i| &i.discounts)
+        .chain(&order.order_discounts)
+        .map(|d| (d.name.as_str(), d.amount))
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use super::order::{Order, OrderStatus};
+use super::product::Product;
+
+// Orders looked at for the top sellers, newest first.
+const TOP_SELLER_WINDOW: usize = 500;
+
+// Lower-cased search keys of the catalog, built once per catalog change so a
+// search only compares strings.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchIndex {
+    entries: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct IndexEntry {
+    // Position in the product list the index was built from.
+    position: usize,
+    name: String,
+    codes: Vec<String>,
+}
+
+impl SearchIndex {
+    // Archived products are left out.
+    pub fn build(products: &[Product]) -> Self {
+        let entries = products
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !p.archived)
+            .map(|(position, p)| IndexEntry {
+                position,
+                name: p.name.to_lowercase(),
+                codes: p
+                    .sku
+                    .iter()
+                    .chain(&p.barcodes)
+                    .map(|c| c.to_lowercase())
+                    .collect(),
+            })
+            .collect();
+        Self { entries }
+    }
+
+    // Positions of the matching products, best match first. Every word of
+    // the query has to match the name, SKU or a barcode.
+    pub fn search(&self, query: &str) -> Vec<usize> {
+        let query = query.to_lowercase();
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<(u32, &IndexEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                terms
+                    .iter()
+                    .map(|term| {
+                        let name = fuzzy_score(&entry.name, term);
+                        let code = entry.codes.iter().filter_map(|c| code_score(c, term)).max();
+                        name.max(code)
+                    })
+                    .sum::<Option<u32>>()
+                    .map(|score| (score, entry))
+            })
+            .collect();
+        // Ties go to the shorter name, then catalog order.
+        hits.sort_by(|(a, x), (b, y)| {
+            b.cmp(a)
+                .then_with(|| x.name.len().cmp(&y.name.len()))
+                .then_with(|| x.position.cmp(&y.position))
+        });
+        hits.into_iter().map(|(_, entry)| entry.position).collect()
+    }
+}
+
+// How well `term` matches a name: whole name, start, start of a word, inside
+// it, or its letters in order with gaps. `None` if not at all.
+fn fuzzy_score(name: &str, term: &str) -> Option<u32> {
+    if name == term {
+        return Some(1000);
+    }
+    if name.starts_with(term) {
+        return Some(800);
+    }
+    if let Some(at) = name.find(term) {
+        let word_start = name[..at].ends_with(|c: char| !c.is_alphanumeric());
+        return Some(if word_start { 600 } else { 400 });
+    }
+
+    // Subsequence: every letter of the term in order, fewer gaps scoring higher.
+    let mut gaps = 0u32;
+    let mut rest = name.chars().peekable();
+    for wanted in term.chars() {
+        loop {
+            match rest.next() {
+                Some(c) if c == wanted => break,
+                Some(_) => gaps += 1,
+                None => return None,
+            }
+        }
+    }
+    Some(200u32.saturating_sub(gaps * 5).max(1))
+}
+
+// Codes match whole, from the start, or anywhere.
+fn code_score(code: &str, term: &str) -> Option<u32> {
+    if code == term {
+        Some(1000)
+    } else if code.starts_with(term) {
+        Some(700)
+    } else if code.contains(term) {
+        Some(300)
+    } else {
+        None
+    }
+}
+
+fn sold(orders: &[Order]) -> impl DoubleEndedIterator<Item = &Order> {
+    orders
+        .iter()
+        .filter(|o| !matches!(o.status, OrderStatus::Pending | OrderStatus::Cancelled))
+}
+
+// Ids of the products sold most in the latest orders, most units first.
+pub fn top_sellers(orders: &[Order], limit: usize) -> Vec<String> {
+    let mut units: HashMap<&str, u32> = HashMap::new();
+    for order in sold(orders).rev().take(TOP_SELLER_WINDOW) {
+        for item in &order.items {
+            *units.entry(item.product_id.as_str()).or_default() += item.quantity;
+        }
+    }
+    let mut ranked: Vec<(&str, u32)> = units.into_iter().collect();
+    ranked.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+    ranked.into_iter().take(limit).map(|(id, _)| id.to_string()).collect()
+}
+
+// Ids of the products sold last, newest first, each once.
+pub fn recently_sold(orders: &[Order], limit: usize) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for order in sold(orders).rev() {
+        for item in order.items.iter().rev() {
+            if ids.len() == limit {
+                return ids;
+            }
+            if !ids.contains(&item.product_id) {
+                ids.push(item.product_id.clone());
+            }
+        }
+    }
+    ids
+}
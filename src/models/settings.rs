@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use super::barcode::{default_embedded_prefixes, EmbeddedPrefix};
+use super::inventory::OversellPolicy;
+use super::gift_card::GiftCardConfig;
+use super::loyalty::LoyaltyConfig;
+use super::money::{Currency, Money, Rounding};
+use super::tip::TipConfig;
+
+// Store-wide preferences, saved as `settings.json` in the data directory.
+// Every field has a serde default so older files keep loading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub oversell: OversellPolicy,
+    // Smallest coin in circulation, in minor units. Cash totals are rounded
+    // to a multiple of it (5 where the penny is withdrawn); 1 disables it.
+    pub cash_rounding: i64,
+    // Note values offered as one-tap tender amounts.
+    pub quick_cash: Vec<Money>,
+    // Notes and coins counted when a drawer is closed, largest first.
+    pub denominations: Vec<Money>,
+    // Address of the card terminal, e.g. "127.0.0.1:7878". Without one, card
+    // payments go to the built-in simulated processor.
+    pub card_terminal: Option<String>,
+    pub receipt: ReceiptSettings,
+    // In-store barcode prefixes that carry a price or a weight.
+    pub embedded_barcodes: Vec<EmbeddedPrefix>,
+    // Held orders not recalled within this many hours are discarded; 0 keeps
+    // them until recalled.
+    pub hold_expiry_hours: u32,
+    pub loyalty: LoyaltyConfig,
+    pub gift_cards: GiftCardConfig,
+    pub tips: TipConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReceiptSettings {
+    // Store name, address and the like, centered above every receipt.
+    pub header: Vec<String>,
+    pub footer: Vec<String>,
+    // Characters per line: 32 for 58 mm paper, 42 or 48 for 80 mm.
+    pub width: usize,
+    // Device path of an ESC/POS printer, e.g. "/dev/usb/lp0". Without one,
+    // receipts are saved as text files under `receipts/` in the data directory.
+    pub printer: Option<String>,
+}
+
+impl Settings {
+    pub fn cash_due(&self, total: Money) -> Money {
+        total.round_to(self.cash_rounding, Rounding::HalfUp)
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            oversell: OversellPolicy::default(),
+            cash_rounding: 1,
+            quick_cash: [500, 1000, 2000, 5000, 10000]
+                .into_iter()
+                .map(|minor| Money::new(minor, Currency::default()))
+                .collect(),
+            denominations: [10000, 5000, 2000, 1000, 500, 100, 25, 10, 5, 1]
+                .into_iter()
+                .map(|minor| Money::new(minor, Currency::default()))
+                .collect(),
+            card_terminal: None,
+            receipt: ReceiptSettings::default(),
+            embedded_barcodes: default_embedded_prefixes(),
+            hold_expiry_hours: 12,
+            loyalty: LoyaltyConfig::default(),
+            gift_cards: GiftCardConfig::default(),
+            tips: TipConfig::default(),
+        }
+    }
+}
+
+impl Default for ReceiptSettings {
+    fn default() -> Self {
+        Self {
+            header: vec!["TREZZA TERMINAL".to_string()],
+            footer: vec!["Thank you!".to_string()],
+            width: 42,
+            printer: None,
+        }
+    }
+}
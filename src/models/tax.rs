@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use super::money::{Money, Rate, Rounding};
+use super::category::{category_tax_class, Category};
+use super::product::Product;
+
+const PPM: i128 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum TaxMode {
+    // Shelf prices exclude tax; tax is added on top (US sales tax).
+    #[default]
+    Exclusive,
+    // Shelf prices already contain tax; it is extracted for reporting (VAT/GST).
+    Inclusive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxRate {
+    pub id: String,
+    pub name: String,
+    pub rate: Rate,
+    // A compound rate is levied on the price plus every rate listed before it
+    // in the same tax class, e.g. Quebec QST on top of GST.
+    #[serde(default)]
+    pub compound: bool,
+    #[serde(default)]
+    pub rounding: Rounding,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxClass {
+    pub id: String,
+    pub name: String,
+    // Rate ids, applied in this order.
+    pub rates: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryTaxClass {
+    // Category id.
+    pub category: String,
+    pub class_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxConfig {
+    pub mode: TaxMode,
+    pub rates: Vec<TaxRate>,
+    pub classes: Vec<TaxClass>,
+    // Per-category classes from before categories were stored. Only read to
+    // seed the categories the first time they are created; after that each
+    // category's own `tax_class` applies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub category_classes: Vec<CategoryTaxClass>,
+    // Class used when neither the product nor its category has one.
+    pub default_class: String,
+}
+
+// One tax as it applied to a whole order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaxLine {
+    pub rate_id: String,
+    pub name: String,
+    pub rate: Rate,
+    pub taxable: Money,
+    pub amount: Money,
+}
+
+impl TaxConfig {
+    pub fn class(&self, id: &str) -> Option<&TaxClass> {
+        self.classes.iter().find(|c| c.id == id)
+    }
+
+    // Product override, then the nearest category with a class, then the
+    // default class.
+    pub fn class_for(&self, product: &Product, categories: &[Category]) -> Option<&TaxClass> {
+        product
+            .tax_class
+            .as_deref()
+            .and_then(|id| self.class(id))
+            .or_else(|| category_tax_class(categories, &product.category).and_then(|id| self.class(id)))
+            .or_else(|| self.class(&self.default_class))
+    }
+
+    // Snapshot of the rates that apply to `product`, in application order.
+    // Stored on the order line so later config edits don't rewrite old sales.
+    pub fn rates_for(&self, product: &Product, categories: &[Category]) -> Vec<TaxRate> {
+        self.class_for(product, categories)
+            .map(|class| {
+                class
+                    .rates
+                    .iter()
+                    .filter_map(|id| self.rates.iter().find(|r| &r.id == id).cloned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Default for TaxConfig {
+    // Matches the original flat 8% sales tax on everything.
+    fn default() -> Self {
+        Self {
+            mode: TaxMode::Exclusive,
+            rates: vec![TaxRate::sales_tax()],
+            classes: vec![
+                TaxClass {
+                    id: "standard".to_string(),
+                    name: "Standard".to_string(),
+                    rates: vec!["sales-tax".to_string()],
+                },
+                TaxClass {
+                    id: "exempt".to_string(),
+                    name: "Exempt".to_string(),
+                    rates: Vec::new(),
+                },
+            ],
+            category_classes: Vec::new(),
+            default_class: "standard".to_string(),
+        }
+    }
+}
+
+impl TaxRate {
+    pub fn sales_tax() -> Self {
+        Self {
+            id: "sales-tax".to_string(),
+            name: "Sales Tax".to_string(),
+            rate: Rate(80_000),
+            compound: false,
+            rounding: Rounding::HalfUp,
+        }
+    }
+}
+
+// Tax owed on one order line, one entry per rate in `rates`. `amount` is the
+// line's shelf total: net of tax in exclusive mode, gross in inclusive mode.
+// Each tax is rounded to the minor unit at line level by its own rule.
+pub fn line_taxes(amount: Money, rates: &[TaxRate], mode: TaxMode) -> Vec<Money> {
+    // Effective multiplier of each rate against the net price, in ppm.
+    let mut factors: Vec<i128> = Vec::with_capacity(rates.len());
+    for rate in rates {
+        let r = rate.rate.0 as i128;
+        let factor = if rate.compound {
+            r * (PPM + factors.iter().sum::<i128>()) / PPM
+        } else {
+            r
+        };
+        factors.push(factor);
+    }
+
+    match mode {
+        TaxMode::Exclusive => {
+            let mut taxes: Vec<Money> = Vec::with_capacity(rates.len());
+            for rate in rates {
+                let base = if rate.compound {
+                    taxes.iter().fold(amount, |sum, t| sum + *t)
+                } else {
+                    amount
+                };
+                taxes.push(base.apply_rate(rate.rate, rate.rounding));
+            }
+            taxes
+        }
+        TaxMode::Inclusive => {
+            let total_factor = PPM + factors.iter().sum::<i128>();
+            let minor = amount.minor() as i128;
+            let rounding = rates.first().map(|r| r.rounding).unwrap_or_default();
+            let net = rounding.divide(minor * PPM, total_factor);
+
+            let mut taxes: Vec<Money> = rates
+                .iter()
+                .zip(&factors)
+                .map(|(rate, factor)| {
+                    Money::new(rate.rounding.divide(net * factor, PPM) as i64, amount.currency())
+                })
+                .collect();
+
+            // Net plus taxes must equal the shelf price exactly; any rounding
+            // residue lands on the last tax.
+            let residue = minor - net - taxes.iter().map(|t| t.minor() as i128).sum::<i128>();
+            if let Some(last) = taxes.last_mut() {
+                *last = Money::new(last.minor() + residue as i64, amount.currency());
+            }
+            taxes
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use super::employee::Employee;
+use super::money::{Money, Rate, Rounding};
+use super::order::{Order, OrderStatus};
+use super::payment::{Payment, PaymentMethod};
+
+// Tip prompts at the till; part of `settings.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TipConfig {
+    pub enabled: bool,
+    // Offered as one-tap percentages of the payment.
+    pub presets: Vec<Rate>,
+    // Largest tip accepted, as a share of the payment it is added to.
+    pub max_share: Rate,
+}
+
+// Tips taken by one employee. Tips are never part of a sale: they are kept
+// on the payment, outside the order total, so sales and tax don't include
+// them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TipTotal {
+    // `None` for payments taken before anyone signed in.
+    pub employee_id: Option<String>,
+    // Name when the total was made, so a closed report keeps it.
+    pub employee: String,
+    pub count: u32,
+    // Left in the drawer by cash customers.
+    pub cash: Money,
+    // Added to card and mobile payments; owed to the employee.
+    pub card: Money,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TipError {
+    Negative,
+    TooLarge(Money),
+    // Only card payments can have their tip changed after the fact.
+    NotCard,
+    NotAdjustable,
+    UnknownPayment,
+}
+
+impl Default for TipConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            presets: vec![Rate(150_000), Rate(180_000), Rate(200_000)],
+            max_share: Rate(1_000_000),
+        }
+    }
+}
+
+impl TipConfig {
+    // `rate` of `base`, to the nearest minor unit.
+    pub fn tip_for(&self, base: Money, rate: Rate) -> Money {
+        base.apply_rate(rate, Rounding::HalfUp)
+    }
+
+    pub fn check(&self, tip: Money, base: Money) -> Result<(), TipError> {
+        if tip.minor() < 0 {
+            return Err(TipError::Negative);
+        }
+        let max = base.apply_rate(self.max_share, Rounding::HalfUp);
+        if tip > max {
+            return Err(TipError::TooLarge(max));
+        }
+        Ok(())
+    }
+}
+
+// Tips by the employee who took each payment, largest first. Voided orders
+// are left out: their tips were handed back with the rest of the payment.
+pub fn tips_by_employee<'a>(
+    orders: impl IntoIterator<Item = &'a Order>,
+    employees: &[Employee],
+) -> Vec<TipTotal> {
+    let mut totals = Vec::new();
+    for order in orders.into_iter().filter(|o| o.status != OrderStatus::Cancelled) {
+        for payment in &order.payments {
+            add_tip(&mut totals, payment, employees, false);
+        }
+    }
+    totals.sort_by_key(|t| -(t.cash + t.card).minor());
+    totals
+}
+
+// Adds the tip on `payment` to its employee's total, or takes it back out
+// when `reverse`.
+pub(crate) fn add_tip(totals: &mut Vec<TipTotal>, payment: &Payment, employees: &[Employee], reverse: bool) {
+    let Some(tip) = payment.tip else {
+        return;
+    };
+    let pos = match totals.iter().position(|t| t.employee_id == payment.operator_id) {
+        Some(pos) => pos,
+        None => {
+            let employee = payment
+                .operator_id
+                .as_ref()
+                .and_then(|id| employees.iter().find(|e| &e.id == id))
+                .map_or_else(|| "Unknown".to_string(), |e| e.name.clone());
+            let zero = Money::zero(tip.currency());
+            totals.push(TipTotal {
+                employee_id: payment.operator_id.clone(),
+                employee,
+                count: 0,
+                cash: zero,
+                card: zero,
+            });
+            totals.len() - 1
+        }
+    };
+    let total = &mut totals[pos];
+    let tip = if reverse { -tip } else { tip };
+    total.count = if reverse { total.count.saturating_sub(1) } else { total.count + 1 };
+    if payment.method == PaymentMethod::Cash {
+        total.cash += tip;
+    } else {
+        total.card += tip;
+    }
+}
+
+impl fmt::Display for TipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TipError::Negative => write!(f, "a tip can't be negative"),
+            TipError::TooLarge(max) => write!(f, "the tip can be at most {max}"),
+            TipError::NotCard => write!(f, "only card payments can have their tip adjusted"),
+            TipError::NotAdjustable => write!(f, "this order can no longer be changed"),
+            TipError::UnknownPayment => write!(f, "no such payment on this order"),
+        }
+    }
+}
+
+impl std::error::Error for TipError {}
@@ -0,0 +1,6 @@
+pub mod processor;
+pub mod simulated;
+pub mod terminal;
+
+pub use processor::*;
+pub use terminal::*;
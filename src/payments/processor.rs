@@ -0,0 +1,238 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use serde::{Deserialize, Serialize};
+use crate::models::{CardDetails, Money, Payment, PaymentMethod};
+
+pub type ProcessorFuture<T> = Pin<Box<dyn Future<Output = Result<T, ProcessorError>> + Send>>;
+
+// One request to the processor. Transactions are keyed by the POS payment
+// id, so a lost reply can still be looked up or reversed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    Authorize { payment_id: String, amount: Money, method: PaymentMethod },
+    Capture { payment_id: String, amount: Money },
+    Void { payment_id: String },
+    Refund { payment_id: String, amount: Money },
+    // Changes a captured amount to `amount`, for a tip added after the
+    // card was charged.
+    Adjust { payment_id: String, amount: Money },
+    Status { payment_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "reply", rename_all = "snake_case")]
+pub enum Reply {
+    Approved { card: CardDetails },
+    Done,
+    Status { status: TransactionStatus },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum TransactionStatus {
+    Authorized,
+    Captured,
+    Voided,
+    Refunded,
+    Declined,
+}
+
+// A reversal of several card payments that stopped part-way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoidFailure {
+    // Reversed before the failure, including by an earlier attempt.
+    pub reversed: Vec<Payment>,
+    pub failed: Payment,
+    pub error: ProcessorError,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ProcessorError {
+    Declined(String),
+    // No answer in time; the transaction may or may not exist.
+    Timeout,
+    Unavailable(String),
+    UnknownTransaction(String),
+    Rejected(String),
+}
+
+// A card processor or terminal. Implementors only provide `submit`; the
+// typed operations are built on it.
+pub trait PaymentProcessor: Send + Sync {
+    fn submit(&self, command: Command) -> ProcessorFuture<Reply>;
+
+    fn authorize(&self, payment_id: &str, amount: Money, method: PaymentMethod) -> ProcessorFuture<CardDetails> {
+        let reply = self.submit(Command::Authorize {
+            payment_id: payment_id.to_string(),
+            amount,
+            method,
+        });
+        Box::pin(async move {
+            match reply.await? {
+                Reply::Approved { card } => Ok(card),
+                other => Err(unexpected(other)),
+            }
+        })
+    }
+
+    fn capture(&self, payment_id: &str, amount: Money) -> ProcessorFuture<()> {
+        done(self.submit(Command::Capture { payment_id: payment_id.to_string(), amount }))
+    }
+
+    fn void(&self, payment_id: &str) -> ProcessorFuture<()> {
+        done(self.submit(Command::Void { payment_id: payment_id.to_string() }))
+    }
+
+    fn refund(&self, payment_id: &str, amount: Money) -> ProcessorFuture<()> {
+        done(self.submit(Command::Refund { payment_id: payment_id.to_string(), amount }))
+    }
+
+    fn adjust(&self, payment_id: &str, amount: Money) -> ProcessorFuture<()> {
+        done(self.submit(Command::Adjust { payment_id: payment_id.to_string(), amount }))
+    }
+
+    fn status(&self, payment_id: &str) -> ProcessorFuture<TransactionStatus> {
+        let reply = self.submit(Command::Status { payment_id: payment_id.to_string() });
+        Box::pin(async move {
+            match reply.await? {
+                Reply::Status { status } => Ok(status),
+                other => Err(unexpected(other)),
+            }
+        })
+    }
+}
+
+fn done(reply: ProcessorFuture<Reply>) -> ProcessorFuture<()> {
+    Box::pin(async move {
+        match reply.await? {
+            Reply::Done => Ok(()),
+            other => Err(unexpected(other)),
+        }
+    })
+}
+
+fn unexpected(reply: Reply) -> ProcessorError {
+    ProcessorError::Rejected(format!("unexpected reply {reply:?}"))
+}
+
+// Authorizes and captures in one go, the way a retail sale settles; a tip
+// is charged on top of `amount`. A capture that fails releases the hold; a
+// timeout reverses whatever the processor may have approved.
+pub async fn charge(
+    processor: Arc<dyn PaymentProcessor>,
+    method: PaymentMethod,
+    amount: Money,
+    tip: Money,
+) -> Result<Payment, ProcessorError> {
+    let payment = Payment::new(method.clone(), amount).with_tip(tip);
+    let total = amount + payment.tip_amount();
+    let result = match processor.authorize(&payment.id, total, method).await {
+        Ok(card) => match processor.capture(&payment.id, total).await {
+            Ok(()) => return Ok(Payment { card: Some(card), ..payment }),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+
+    if !matches!(result, Err(ProcessorError::Declined(_))) {
+        if let Ok(TransactionStatus::Authorized | TransactionStatus::Captured) =
+            processor.status(&payment.id).await
+        {
+            if let Err(e) = processor.void(&payment.id).await {
+                eprintln!("failed to reverse payment {}: {e}", payment.id);
+            }
+        }
+    }
+    result
+}
+
+// Reverses every card payment in `payments` and returns those reversed,
+// stopping at the first failure so the order is not cancelled while money
+// is still held. Payments already voided are skipped, so a retry after a
+// failure finishes the rest.
+pub async fn void_card_payments(
+    processor: Arc<dyn PaymentProcessor>,
+    payments: Vec<Payment>,
+) -> Result<Vec<Payment>, VoidFailure> {
+    let mut reversed = Vec::new();
+    for payment in payments.into_iter().filter(|p| p.card.is_some()) {
+        if processor.status(&payment.id).await != Ok(TransactionStatus::Voided) {
+            if let Err(error) = processor.void(&payment.id).await {
+                return Err(VoidFailure { reversed, failed: payment, error });
+            }
+        }
+        reversed.push(payment);
+    }
+    Ok(reversed)
+}
+
+// Runs blocking terminal I/O on its own thread so the UI keeps rendering
+// while a card is being read.
+pub(crate) fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> impl Future<Output = T> + Send {
+    let shared = Arc::new(Mutex::new(Blocking { result: None, waker: None }));
+    let worker = shared.clone();
+    std::thread::spawn(move || {
+        let result = work();
+        let mut state = worker.lock().unwrap_or_else(|e| e.into_inner());
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+    BlockingFuture(shared)
+}
+
+struct Blocking<T> {
+    result: Option<T>,
+    waker: Option<Waker>,
+}
+
+struct BlockingFuture<T>(Arc<Mutex<Blocking<T>>>);
+
+impl<T> Future for BlockingFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl fmt::Display for ProcessorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessorError::Declined(reason) => write!(f, "declined: {reason}"),
+            ProcessorError::Timeout => write!(f, "no response from the card terminal"),
+            ProcessorError::Unavailable(msg) => write!(f, "card terminal unavailable: {msg}"),
+            ProcessorError::UnknownTransaction(id) => write!(f, "no transaction for payment {id}"),
+            ProcessorError::Rejected(msg) => write!(f, "processor rejected the request: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ProcessorError {}
+
+impl fmt::Display for VoidFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let card = |p: &Payment| p.card.as_ref().map_or_else(|| p.method.as_str().to_string(), |c| c.summary());
+        write!(f, "{} not reversed ({})", card(&self.failed), self.error)?;
+        if !self.reversed.is_empty() {
+            let reversed: Vec<String> = self.reversed.iter().map(card).collect();
+            write!(f, "; {} already reversed", reversed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for VoidFailure {}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::models::{CardBrand, CardDetails, Money};
+use super::processor::{run_blocking, Command, PaymentProcessor, ProcessorError, ProcessorFuture, Reply, TransactionStatus};
+
+// Stands in for a real processor, in process or behind the mock terminal.
+// Amounts ending in .51 are declined and amounts ending in .52 are approved
+// but never answered, so every terminal state can be tried at the till.
+#[derive(Clone)]
+pub struct SimulatedProcessor {
+    latency: Duration,
+    transactions: Arc<Mutex<HashMap<String, Transaction>>>,
+}
+
+#[derive(Debug, Clone)]
+struct Transaction {
+    status: TransactionStatus,
+    authorized: Money,
+    refunded: Money,
+}
+
+impl SimulatedProcessor {
+    pub fn new(latency: Duration) -> Self {
+        Self {
+            latency,
+            transactions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // Answers one command after the simulated card-read delay.
+    pub fn respond(&self, command: Command) -> Result<Reply, ProcessorError> {
+        thread::sleep(self.latency);
+        self.execute(command)
+    }
+
+    fn execute(&self, command: Command) -> Result<Reply, ProcessorError> {
+        let mut transactions = self.transactions.lock().unwrap_or_else(|e| e.into_inner());
+
+        match command {
+            Command::Authorize { payment_id, amount, .. } => {
+                if transactions.contains_key(&payment_id) {
+                    return Err(ProcessorError::Rejected(format!("payment {payment_id} already submitted")));
+                }
+                if amount.minor() <= 0 {
+                    return Err(ProcessorError::Rejected("amount must be positive".to_string()));
+                }
+                let (status, outcome) = match amount.minor() % 100 {
+                    51 => (
+                        TransactionStatus::Declined,
+                        Err(ProcessorError::Declined("insufficient funds".to_string())),
+                    ),
+                    52 => (TransactionStatus::Authorized, Err(ProcessorError::Timeout)),
+                    _ => (
+                        TransactionStatus::Authorized,
+                        Ok(Reply::Approved { card: simulated_card(&payment_id) }),
+                    ),
+                };
+                transactions.insert(
+                    payment_id,
+                    Transaction {
+                        status,
+                        authorized: amount,
+                        refunded: Money::zero(amount.currency()),
+                    },
+                );
+                outcome
+            }
+            Command::Capture { payment_id, amount } => {
+                let transaction = find(&mut transactions, &payment_id)?;
+                expect_status(transaction, &[TransactionStatus::Authorized])?;
+                if amount > transaction.authorized {
+                    return Err(ProcessorError::Rejected("capture exceeds authorization".to_string()));
+                }
+                transaction.authorized = amount;
+                transaction.status = TransactionStatus::Captured;
+                Ok(Reply::Done)
+            }
+            Command::Void { payment_id } => {
+                let transaction = find(&mut transactions, &payment_id)?;
+                expect_status(transaction, &[TransactionStatus::Authorized, TransactionStatus::Captured])?;
+                transaction.status = TransactionStatus::Voided;
+                Ok(Reply::Done)
+            }
+            Command::Refund { payment_id, amount } => {
+                let transaction = find(&mut transactions, &payment_id)?;
+                expect_status(transaction, &[TransactionStatus::Captured])?;
+                if transaction.refunded + amount > transaction.authorized {
+                    return Err(ProcessorError::Rejected("refund exceeds captured amount".to_string()));
+                }
+                transaction.refunded += amount;
+                if transaction.refunded == transaction.authorized {
+                    transaction.status = TransactionStatus::Refunded;
+                }
+                Ok(Reply::Done)
+            }
+            Command::Adjust { payment_id, amount } => {
+                let transaction = find(&mut transactions, &payment_id)?;
+                expect_status(transaction, &[TransactionStatus::Captured])?;
+                if amount < transaction.refunded {
+                    return Err(ProcessorError::Rejected("adjustment below the amount refunded".to_string()));
+                }
+                transaction.authorized = amount;
+                Ok(Reply::Done)
+            }
+            Command::Status { payment_id } => {
+                let transaction = find(&mut transactions, &payment_id)?;
+                Ok(Reply::Status { status: transaction.status })
+            }
+        }
+    }
+}
+
+fn find<'a>(
+    transactions: &'a mut HashMap<String, Transaction>,
+    payment_id: &str,
+) -> Result<&'a mut Transaction, ProcessorError> {
+    transactions
+        .get_mut(payment_id)
+        .ok_or_else(|| ProcessorError::UnknownTransaction(payment_id.to_string()))
+}
+
+fn expect_status(transaction: &Transaction, allowed: &[TransactionStatus]) -> Result<(), ProcessorError> {
+    if allowed.contains(&transaction.status) {
+        Ok(())
+    } else {
+        Err(ProcessorError::Rejected(format!("transaction is {:?}", transaction.status)))
+    }
+}
+
+impl PaymentProcessor for SimulatedProcessor {
+    fn submit(&self, command: Command) -> ProcessorFuture<Reply> {
+        let processor = self.clone();
+        Box::pin(run_blocking(move || processor.respond(command)))
+    }
+}
+
+// Brand and last four derived from the payment id, so a reprint of the same
+// payment always shows the same card.
+fn simulated_card(payment_id: &str) -> CardDetails {
+    let hash = payment_id
+        .bytes()
+        .fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+    let (brand, digits) = match hash % 4 {
+        0 => (CardBrand::Visa, 16),
+        1 => (CardBrand::Mastercard, 16),
+        2 => (CardBrand::Amex, 15),
+        _ => (CardBrand::Discover, 16),
+    };
+    let auth_code = Uuid::new_v4().simple().to_string()[..6].to_uppercase();
+    CardDetails {
+        auth_code,
+        brand,
+        masked_pan: format!("{}{:04}", "*".repeat(digits - 4), hash % 10_000),
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use super::processor::{run_blocking, Command, PaymentProcessor, ProcessorError, ProcessorFuture, Reply};
+use super::simulated::SimulatedProcessor;
+
+pub const DEFAULT_TERMINAL_ADDR: &str = "127.0.0.1:7878";
+
+// Long enough for the customer to insert a card and enter a PIN.
+const TERMINAL_TIMEOUT: Duration = Duration::from_secs(30);
+const SIMULATED_LATENCY: Duration = Duration::from_millis(800);
+const MOCK_TERMINAL_LATENCY: Duration = Duration::from_millis(1500);
+
+// The terminal at `addr`, or the in-process simulator when none is configured.
+pub fn connect(addr: Option<&str>) -> Arc<dyn PaymentProcessor> {
+    match addr {
+        Some(addr) => Arc::new(TerminalProcessor::new(addr, TERMINAL_TIMEOUT)),
+        None => Arc::new(SimulatedProcessor::new(SIMULATED_LATENCY)),
+    }
+}
+
+// A card terminal on the network. Each command is one JSON line on a fresh
+// connection, answered by one JSON line holding `Result<Reply, ProcessorError>`.
+pub struct TerminalProcessor {
+    addr: String,
+    timeout: Duration,
+}
+
+impl TerminalProcessor {
+    pub fn new(addr: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            addr: addr.into(),
+            timeout,
+        }
+    }
+}
+
+impl PaymentProcessor for TerminalProcessor {
+    fn submit(&self, command: Command) -> ProcessorFuture<Reply> {
+        let addr = self.addr.clone();
+        let timeout = self.timeout;
+        Box::pin(run_blocking(move || exchange(&addr, timeout, &command)))
+    }
+}
+
+fn exchange(addr: &str, timeout: Duration, command: &Command) -> Result<Reply, ProcessorError> {
+    let unavailable = |e: io::Error| ProcessorError::Unavailable(format!("{addr}: {e}"));
+    let socket = addr
+        .to_socket_addrs()
+        .map_err(unavailable)?
+        .next()
+        .ok_or_else(|| ProcessorError::Unavailable(format!("{addr}: no address")))?;
+    let stream = TcpStream::connect_timeout(&socket, timeout).map_err(unavailable)?;
+    stream.set_read_timeout(Some(timeout)).map_err(unavailable)?;
+    stream.set_write_timeout(Some(timeout)).map_err(unavailable)?;
+
+    let mut line = serde_json::to_vec(command)
+        .map_err(|e| ProcessorError::Rejected(e.to_string()))?;
+    line.push(b'\n');
+    (&stream).write_all(&line).map_err(unavailable)?;
+
+    let mut reply = String::new();
+    match BufReader::new(&stream).read_line(&mut reply) {
+        Ok(0) => Err(ProcessorError::Unavailable(format!("{addr}: connection closed"))),
+        Ok(_) => serde_json::from_str::<Result<Reply, ProcessorError>>(&reply)
+            .map_err(|e| ProcessorError::Rejected(format!("unreadable reply: {e}")))?,
+        Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+            Err(ProcessorError::Timeout)
+        }
+        Err(e) => Err(unavailable(e)),
+    }
+}
+
+// Serves a simulated terminal until the process is stopped, so card flows
+// can be exercised end to end without hardware. Started with
+// `pos_system --mock-terminal [ADDR]`.
+pub fn serve_mock_terminal(addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let processor = SimulatedProcessor::new(MOCK_TERMINAL_LATENCY);
+    eprintln!("mock terminal listening on {}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("mock terminal: failed to accept connection: {e}");
+                continue;
+            }
+        };
+        let processor = processor.clone();
+        thread::spawn(move || {
+            if let Err(e) = serve_connection(stream, &processor) {
+                eprintln!("mock terminal: connection error: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve_connection(stream: TcpStream, processor: &SimulatedProcessor) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Command>(&line) {
+            Ok(command) => processor.respond(command),
+            Err(e) => Err(ProcessorError::Rejected(format!("unreadable command: {e}"))),
+        };
+        // A simulated timeout is a terminal that never answers.
+        if reply == Err(ProcessorError::Timeout) {
+            continue;
+        }
+        let mut out = serde_json::to_vec(&reply)?;
+        out.push(b'\n');
+        writer.write_all(&out)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use super::layout::{Line, Receipt};
+use super::text::render_line;
+
+const ESC: u8 = 0x1b;
+const GS: u8 = 0x1d;
+const LF: u8 = b'\n';
+
+// Raw ESC/POS for a thermal printer: the text layout, a CODE128 barcode of
+// the order id, the drawer kick for cash sales and a partial cut. Printers
+// default to an ASCII code page, so anything else prints as '?'.
+pub fn to_escpos(receipt: &Receipt, width: usize) -> Vec<u8> {
+    let mut out = vec![ESC, b'@'];
+
+    for line in &receipt.lines {
+        let rows = render_line(line, width);
+        match line {
+            Line::Center(_) => {
+                out.extend([ESC, b'a', 1]);
+                push_rows(&mut out, rows.iter().map(|r| r.trim()));
+                out.extend([ESC, b'a', 0]);
+            }
+            Line::Total(..) => {
+                out.extend([ESC, b'E', 1]);
+                push_rows(&mut out, rows.iter().map(String::as_str));
+                out.extend([ESC, b'E', 0]);
+            }
+            _ => push_rows(&mut out, rows.iter().map(String::as_str)),
+        }
+    }
+
+    if !receipt.barcode.is_empty() {
+        let data: Vec<u8> = b"{B".iter().copied().chain(ascii(&receipt.barcode)).collect();
+        out.push(LF);
+        out.extend([ESC, b'a', 1]);
+        out.extend([GS, b'h', 80]); // height in dots
+        out.extend([GS, b'w', 2]); // module width
+        out.extend([GS, b'H', 2]); // human-readable text below
+        out.extend([GS, b'k', 73, data.len() as u8]);
+        out.extend(data);
+        out.extend([ESC, b'a', 0]);
+    }
+
+    if receipt.open_drawer {
+        // Pulse pin 2 for 50 ms on, 500 ms off.
+        out.extend([ESC, b'p', 0, 25, 250]);
+    }
+    out.extend([ESC, b'd', 4]);
+    out.extend([GS, b'V', 66, 0]);
+    out
+}
+
+fn push_rows<'a>(out: &mut Vec<u8>, rows: impl Iterator<Item = &'a str>) {
+    for row in rows {
+        out.extend(ascii(row));
+        out.push(LF);
+    }
+}
+
+fn ascii(text: &str) -> impl Iterator<Item = u8> + '_ {
+    text.chars().map(|c| if c.is_ascii() && !c.is_ascii_control() { c as u8 } else { b'?' })
+}
@@ -0,0 +1,36 @@
+use super::layout::{Line, Receipt};
+
+// An HTML fragment for on-screen display and the print view. Styled by
+// the `.receipt` rules in styles.css.
+pub fn to_html(receipt: &Receipt) -> String {
+    let mut out = String::from("<div class=\"receipt\">");
+    for line in &receipt.lines {
+        let row = match line {
+            Line::Center(text) => format!("<div class=\"receipt-center\">{}</div>", escape(text)),
+            Line::Text(text) => format!("<div class=\"receipt-text\">{}</div>", escape(text)),
+            Line::Pair(label, amount) => format!(
+                "<div class=\"receipt-pair\"><span>{}</span><span>{}</span></div>",
+                escape(label),
+                escape(amount)
+            ),
+            Line::Total(label, amount) => format!(
+                "<div class=\"receipt-pair receipt-total\"><span>{}</span><span>{}</span></div>",
+                escape(label),
+                escape(amount)
+            ),
+            Line::Rule => "<hr class=\"receipt-rule\">".to_string(),
+            Line::Blank => "<div class=\"receipt-blank\"></div>".to_string(),
+        };
+        out.push_str(&row);
+    }
+    out.push_str(&format!("<div class=\"receipt-barcode\">{}</div>", escape(&receipt.barcode)));
+    out.push_str("</div>");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
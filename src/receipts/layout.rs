@@ -0,0 +1,143 @@
+use crate::models::{group_number, mask_number, Order, PaymentMethod, ReceiptSettings, TaxMode};
+
+// A receipt as a list of device-neutral lines. The text, HTML and ESC/POS
+// renderers all work from the same layout so every copy reads alike.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Receipt {
+    pub order_id: String,
+    pub lines: Vec<Line>,
+    // Printed as a CODE128 barcode under the receipt; the short order id
+    // shown in history, so a scan finds the order.
+    pub barcode: String,
+    pub open_drawer: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Line {
+    Center(String),
+    Text(String),
+    // Label on the left, amount on the right.
+    Pair(String, String),
+    // A pair printed in bold.
+    Total(String, String),
+    Rule,
+    Blank,
+}
+
+// Lays out `order` with the store's header and footer. A reprint is marked
+// as such and never opens the cash drawer.
+pub fn build(order: &Order, store: &ReceiptSettings, reprint: bool) -> Receipt {
+    let short_id = order.id.chars().take(8).collect::<String>();
+    let mut lines: Vec<Line> = store.header.iter().cloned().map(Line::Center).collect();
+    lines.push(Line::Blank);
+    lines.push(Line::Text(format!("Order #{short_id}")));
+    lines.push(Line::Text(order.created_at.format("%b %d, %Y %I:%M %p").to_string()));
+    if reprint {
+        lines.push(Line::Center("** REPRINT **".to_string()));
+    }
+    if let Some(customer) = &order.customer {
+        lines.push(Line::Text(format!("Customer: {}", customer.name)));
+    }
+    lines.push(Line::Rule);
+
+    for item in &order.items {
+        lines.push(Line::Pair(
+            format!("{} x{}", item.product_name, item.quantity),
+            item.line_total().to_string(),
+        ));
+        for option in &item.options {
+            let text = match option.price_delta.minor() {
+                0 => format!("  {}", option.name),
+                d if d > 0 => format!("  {} +{}", option.name, option.price_delta),
+                _ => format!("  {} {}", option.name, option.price_delta),
+            };
+            lines.push(Line::Text(text));
+        }
+        if item.quantity > 1 {
+            lines.push(Line::Text(format!("  {} each", item.price)));
+        }
+        for discount in &item.discounts {
+            lines.push(Line::Pair(format!("  {}", discount.name), (-discount.amount).to_string()));
+        }
+    }
+    lines.push(Line::Rule);
+
+    lines.push(Line::Pair("Subtotal".to_string(), order.subtotal.to_string()));
+    for discount in &order.order_discounts {
+        lines.push(Line::Pair(discount.name.clone(), (-discount.amount).to_string()));
+    }
+    if order.discount.minor() != 0 {
+        lines.push(Line::Pair("You saved".to_string(), order.discount.to_string()));
+    }
+    for tax in &order.tax_lines {
+        let label = match order.tax_mode {
+            TaxMode::Exclusive => format!("{} ({})", tax.name, tax.rate),
+            TaxMode::Inclusive => format!("Incl. {} ({})", tax.name, tax.rate),
+        };
+        lines.push(Line::Pair(label, tax.amount.to_string()));
+    }
+    if let Some(certificate) = order.customer.as_ref().and_then(|c| c.exemption_certificate.as_ref()) {
+        lines.push(Line::Text(format!("Tax exempt, certificate {certificate}")));
+    }
+    lines.push(Line::Total("TOTAL".to_string(), order.total.to_string()));
+
+    if !order.payments.is_empty() {
+        lines.push(Line::Rule);
+    }
+    for payment in &order.payments {
+        lines.push(Line::Pair(payment.method.as_str().to_string(), payment.amount.to_string()));
+        if let Some(rounding) = payment.rounding {
+            lines.push(Line::Pair("  Cash rounding".to_string(), rounding.to_string()));
+        }
+        // Tips are paid on top of the total, so they're shown with the
+        // tender they came with.
+        if let Some(tip) = payment.tip {
+            lines.push(Line::Pair("  Tip".to_string(), tip.to_string()));
+            if payment.method != PaymentMethod::Cash {
+                lines.push(Line::Pair("  Charged".to_string(), (payment.amount + tip).to_string()));
+            }
+        }
+        if let Some(tendered) = payment.tendered {
+            lines.push(Line::Pair("  Tendered".to_string(), tendered.to_string()));
+        }
+        if let Some(change) = payment.change {
+            lines.push(Line::Pair("  Change".to_string(), change.to_string()));
+        }
+        if let Some(card) = &payment.card {
+            lines.push(Line::Text(format!("  {} {}", card.brand.as_str(), card.masked_pan)));
+            lines.push(Line::Text(format!("  Auth {}", card.auth_code)));
+        }
+        if let Some(number) = &payment.gift_card {
+            lines.push(Line::Text(format!("  Card {}", mask_number(number))));
+        }
+    }
+
+    for refund in &order.refunds {
+        lines.push(Line::Pair(
+            format!("Refund ({})", refund.reason.as_str()),
+            (-refund.total).to_string(),
+        ));
+        if let Some(number) = &refund.store_credit {
+            lines.push(Line::Text(format!("  Store credit {}", group_number(number))));
+        }
+    }
+    if !order.refunds.is_empty() {
+        lines.push(Line::Total("NET".to_string(), order.net_total().to_string()));
+    }
+    if let Some(void) = &order.void {
+        lines.push(Line::Blank);
+        lines.push(Line::Center(format!("*** VOIDED: {} ***", void.reason.as_str())));
+    }
+
+    if !store.footer.is_empty() {
+        lines.push(Line::Blank);
+        lines.extend(store.footer.iter().cloned().map(Line::Center));
+    }
+
+    Receipt {
+        order_id: order.id.clone(),
+        lines,
+        barcode: short_id,
+        open_drawer: !reprint && order.payments.iter().any(|p| p.method == PaymentMethod::Cash),
+    }
+}
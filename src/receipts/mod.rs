@@ -0,0 +1,9 @@
+pub mod layout;
+pub mod text;
+pub mod html;
+pub mod escpos;
+pub mod printer;
+
+pub use layout::*;
+pub use html::*;
+pub use printer::*;
@@ -0,0 +1,68 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use crate::models::ReceiptSettings;
+use super::escpos::to_escpos;
+use super::layout::Receipt;
+use super::text::to_text;
+
+// Where printed receipts go.
+pub trait PrinterOutput {
+    fn print(&self, receipt: &Receipt) -> io::Result<()>;
+}
+
+// Saves each receipt as a text file named after the order, for terminals
+// without a printer. A reprint replaces the earlier copy.
+pub struct FileOutput {
+    dir: PathBuf,
+    width: usize,
+}
+
+// Writes raw ESC/POS to a printer's device node, e.g. `/dev/usb/lp0`.
+pub struct DeviceOutput {
+    path: PathBuf,
+    width: usize,
+}
+
+impl FileOutput {
+    pub fn new(dir: impl Into<PathBuf>, width: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            width,
+        }
+    }
+}
+
+impl DeviceOutput {
+    pub fn new(path: impl Into<PathBuf>, width: usize) -> Self {
+        Self {
+            path: path.into(),
+            width,
+        }
+    }
+}
+
+impl PrinterOutput for FileOutput {
+    fn print(&self, receipt: &Receipt) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let name = format!("receipt-{}.txt", receipt.order_id);
+        fs::write(self.dir.join(name), to_text(receipt, self.width))
+    }
+}
+
+impl PrinterOutput for DeviceOutput {
+    fn print(&self, receipt: &Receipt) -> io::Result<()> {
+        // Device nodes exist already; never create a regular file in their place.
+        let mut device = OpenOptions::new().write(true).open(&self.path)?;
+        device.write_all(&to_escpos(receipt, self.width))?;
+        device.flush()
+    }
+}
+
+// The configured printer, or text files under `data_dir/receipts`.
+pub fn output_for(settings: &ReceiptSettings, data_dir: &Path) -> Box<dyn PrinterOutput> {
+    match &settings.printer {
+        Some(path) => Box::new(DeviceOutput::new(path, settings.width)),
+        None => Box::new(FileOutput::new(data_dir.join("receipts"), settings.width)),
+    }
+}
@@ -0,0 +1,76 @@
+use super::layout::{Line, Receipt};
+
+// Narrowest layout that still fits a label and an amount side by side.
+const MIN_WIDTH: usize = 24;
+
+// Monospaced text `width` characters wide, one receipt line per row.
+pub fn to_text(receipt: &Receipt, width: usize) -> String {
+    let mut out = String::new();
+    for line in &receipt.lines {
+        for row in render_line(line, width) {
+            out.push_str(&row);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+// The rows one layout line occupies. Long text wraps rather than being
+// cut off, and a wrapped pair keeps its amount on the label's last row.
+pub(crate) fn render_line(line: &Line, width: usize) -> Vec<String> {
+    let width = width.max(MIN_WIDTH);
+    match line {
+        Line::Center(text) => wrap(text, width)
+            .into_iter()
+            .map(|row| format!("{:^width$}", row).trim_end().to_string())
+            .collect(),
+        Line::Text(text) => wrap(text, width),
+        Line::Pair(label, amount) | Line::Total(label, amount) => {
+            let used = label.chars().count() + amount.chars().count();
+            if used < width {
+                vec![format!("{label}{}{amount}", " ".repeat(width - used))]
+            } else {
+                let mut rows = wrap(label, width);
+                let last = rows.pop().unwrap_or_default();
+                let used = last.chars().count() + amount.chars().count();
+                if used < width {
+                    rows.push(format!("{last}{}{amount}", " ".repeat(width - used)));
+                } else {
+                    rows.push(last);
+                    rows.push(format!("{amount:>width$}"));
+                }
+                rows
+            }
+        }
+        Line::Rule => vec!["-".repeat(width)],
+        Line::Blank => vec![String::new()],
+    }
+}
+
+// Breaks at spaces; only a word longer than the line is split.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let indent: String = text.chars().take_while(|c| *c == ' ').collect();
+    let mut rows: Vec<String> = Vec::new();
+    let mut row = indent.clone();
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let len = row.chars().count();
+        let gap = usize::from(len > indent.len());
+        if len + gap + word.len() > width && len > indent.len() {
+            rows.push(std::mem::replace(&mut row, indent.clone()));
+        } else if gap == 1 {
+            row.push(' ');
+        }
+        while row.chars().count() + word.len() > width {
+            let take = width - row.chars().count();
+            row.extend(word.drain(..take));
+            rows.push(std::mem::take(&mut row));
+        }
+        row.extend(word);
+    }
+    if row.len() > indent.len() || rows.is_empty() {
+        rows.push(row);
+    }
+    rows
+}
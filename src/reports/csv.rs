@@ -0,0 +1,73 @@
+use crate::models::SalesReport;
+
+// One row per figure: `section,label,count,amount`. Amounts are plain
+// decimals without a currency symbol so spreadsheets read them as numbers.
+pub fn to_csv(report: &SalesReport) -> String {
+    let mut rows: Vec<[String; 4]> = vec![[
+        "section".to_string(),
+        "label".to_string(),
+        "count".to_string(),
+        "amount".to_string(),
+    ]];
+    let mut row = |section: &str, label: &str, count: Option<i64>, amount: Option<String>| {
+        rows.push([
+            section.to_string(),
+            label.to_string(),
+            count.map(|c| c.to_string()).unwrap_or_default(),
+            amount.unwrap_or_default(),
+        ]);
+    };
+
+    row("report", report.kind.as_str(), report.number.map(i64::from), None);
+    row("report", "currency", None, Some(report.currency.code().to_string()));
+    row("report", "period_start", None, report.period_start.map(|at| at.to_rfc3339()));
+    row("report", "period_end", None, Some(report.period_end.to_rfc3339()));
+
+    row("summary", "gross_sales", Some(report.sales_count.into()), Some(report.gross_sales.plain_string()));
+    row("summary", "discounts", None, Some(report.discounts.plain_string()));
+    row("summary", "refunds", Some(report.refund_count.into()), Some(report.refunds.plain_string()));
+    row("summary", "voids", Some(report.void_count.into()), Some(report.voids.plain_string()));
+    row("summary", "net_sales", None, Some(report.net_sales.plain_string()));
+    row("summary", "tax", None, Some(report.tax_total.plain_string()));
+    row("summary", "gift_cards_issued", None, Some(report.gift_cards_issued.plain_string()));
+    row("summary", "total", None, Some(report.total.plain_string()));
+    row("summary", "cash_rounding", None, Some(report.cash_rounding.plain_string()));
+    row("summary", "items_sold", Some(report.items_sold), None);
+    row("summary", "tips", None, Some(report.tip_total.plain_string()));
+
+    for tax in &report.taxes {
+        row("tax", &format!("{} ({})", tax.name, tax.rate), None, Some(tax.amount.plain_string()));
+    }
+    for method in &report.by_method {
+        let name = method.method.as_str();
+        row("payment", name, Some(method.count.into()), Some(method.collected.plain_string()));
+        row("payment_refunded", name, None, Some(method.refunded.plain_string()));
+    }
+    for category in &report.by_category {
+        row("category", &category.category, Some(category.quantity), Some(category.amount.plain_string()));
+    }
+    for discount in &report.by_discount {
+        row("discount", &discount.name, Some(discount.count), Some(discount.amount.plain_string()));
+    }
+    for tip in &report.tips {
+        row("tip_cash", &tip.employee, Some(tip.count.into()), Some(tip.cash.plain_string()));
+        row("tip_card", &tip.employee, None, Some(tip.card.plain_string()));
+    }
+
+    let mut out = String::new();
+    for fields in rows {
+        let fields: Vec<String> = fields.iter().map(|f| escape(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+// Quotes a field containing a separator, quote or line break.
+pub fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
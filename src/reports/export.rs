@@ -0,0 +1,21 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::models::SalesReport;
+use super::csv::to_csv;
+use super::text::to_text;
+
+// Writes the report to `dir` as text and CSV and returns both paths. Z
+// reports are named after their number, X reports after the time taken.
+pub fn export(report: &SalesReport, dir: &Path, width: usize) -> io::Result<(PathBuf, PathBuf)> {
+    fs::create_dir_all(dir)?;
+    let stem = match report.number {
+        Some(number) => format!("z-report-{number:04}"),
+        None => format!("x-report-{}", report.period_end.format("%Y%m%d-%H%M%S")),
+    };
+    let text = dir.join(format!("{stem}.txt"));
+    let csv = dir.join(format!("{stem}.csv"));
+    fs::write(&text, to_text(report, width))?;
+    fs::write(&csv, to_csv(report))?;
+    Ok((text, csv))
+}
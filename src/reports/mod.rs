@@ -0,0 +1,6 @@
+pub mod text;
+pub mod csv;
+pub mod export;
+
+pub use text::*;
+pub use export::*;
@@ -0,0 +1,102 @@
+use crate::models::SalesReport;
+use crate::receipts::text::render_line;
+use crate::receipts::Line;
+
+// The report as printed on the receipt printer or saved as a text file,
+// `width` characters wide. Uses the receipt layout so both print alike.
+pub fn to_text(report: &SalesReport, width: usize) -> String {
+    let mut out = String::new();
+    for line in layout(report) {
+        for row in render_line(&line, width) {
+            out.push_str(&row);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+pub(crate) fn title(report: &SalesReport) -> String {
+    match report.number {
+        Some(number) => format!("{} REPORT #{number:04}", report.kind.as_str()),
+        None => format!("{} REPORT", report.kind.as_str()),
+    }
+}
+
+fn layout(report: &SalesReport) -> Vec<Line> {
+    let format = "%b %d, %Y %I:%M %p";
+    let mut lines = vec![Line::Center(title(report)), Line::Blank];
+    let from = report
+        .period_start
+        .map_or("first sale".to_string(), |at| at.format(format).to_string());
+    lines.push(Line::Text(format!("From {from}")));
+    lines.push(Line::Text(format!("To   {}", report.period_end.format(format))));
+    lines.push(Line::Rule);
+
+    lines.push(Line::Pair(format!("Gross sales ({})", report.sales_count), report.gross_sales.to_string()));
+    lines.push(Line::Pair("Discounts".to_string(), (-report.discounts).to_string()));
+    lines.push(Line::Pair(format!("Refunds ({})", report.refund_count), (-report.refunds).to_string()));
+    lines.push(Line::Pair(format!("Voids ({})", report.void_count), (-report.voids).to_string()));
+    lines.push(Line::Total("NET SALES".to_string(), report.net_sales.to_string()));
+    lines.push(Line::Rule);
+
+    for tax in &report.taxes {
+        lines.push(Line::Pair(format!("{} ({})", tax.name, tax.rate), tax.amount.to_string()));
+    }
+    lines.push(Line::Total("TAX".to_string(), report.tax_total.to_string()));
+    if report.gift_cards_issued.minor() != 0 {
+        lines.push(Line::Pair("Gift cards issued".to_string(), report.gift_cards_issued.to_string()));
+    }
+    lines.push(Line::Total("TOTAL".to_string(), report.total.to_string()));
+    lines.push(Line::Rule);
+
+    lines.push(Line::Text("Payments".to_string()));
+    for method in &report.by_method {
+        lines.push(Line::Pair(
+            format!("  {} ({})", method.method.as_str(), method.count),
+            method.collected.to_string(),
+        ));
+        if method.refunded.minor() != 0 {
+            lines.push(Line::Pair("    Refunded".to_string(), (-method.refunded).to_string()));
+        }
+    }
+    if report.cash_rounding.minor() != 0 {
+        lines.push(Line::Pair("  Cash rounding".to_string(), report.cash_rounding.to_string()));
+    }
+    lines.push(Line::Rule);
+
+    lines.push(Line::Text("Categories".to_string()));
+    for category in &report.by_category {
+        lines.push(Line::Pair(
+            format!("  {} x{}", category.category, category.quantity),
+            category.amount.to_string(),
+        ));
+    }
+    lines.push(Line::Pair("Items sold".to_string(), report.items_sold.to_string()));
+
+    if !report.by_discount.is_empty() {
+        lines.push(Line::Rule);
+        lines.push(Line::Text("Discounts".to_string()));
+        for discount in &report.by_discount {
+            lines.push(Line::Pair(
+                format!("  {} ({})", discount.name, discount.count),
+                (-discount.amount).to_string(),
+            ));
+        }
+    }
+
+    if !report.tips.is_empty() {
+        lines.push(Line::Rule);
+        lines.push(Line::Text("Tips".to_string()));
+        for tip in &report.tips {
+            lines.push(Line::Pair(format!("  {} ({})", tip.employee, tip.count), (tip.cash + tip.card).to_string()));
+            if tip.cash.minor() != 0 {
+                lines.push(Line::Pair("    Cash".to_string(), tip.cash.to_string()));
+            }
+            if tip.card.minor() != 0 {
+                lines.push(Line::Pair("    Card".to_string(), tip.card.to_string()));
+            }
+        }
+        lines.push(Line::Total("TIPS".to_string(), report.tip_total.to_string()));
+    }
+    lines
+}
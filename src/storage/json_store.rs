@@ -0,0 +1,383 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::Serialize;
+use crate::models::{AuditEntry, CardEntry, Category, Customer, DrawerSession, GENESIS_HASH, Employee, GiftCard, HeldOrder, Order, PointsEntry, Product, Promotion, ReportKind, SalesReport, Settings, StockMovement, TaxConfig};
+use super::repository::{Repository, StorageError};
+use super::schema::{decode, Record, Versioned};
+
+const PRODUCTS_FILE: &str = "products.json";
+const TAX_CONFIG_FILE: &str = "tax.json";
+const SETTINGS_FILE: &str = "settings.json";
+const EMPLOYEES_FILE: &str = "employees.json";
+const PROMOTIONS_FILE: &str = "promotions.json";
+const CATEGORIES_FILE: &str = "categories.json";
+const CUSTOMERS_FILE: &str = "customers.json";
+const GIFT_CARDS_FILE: &str = "gift_cards.json";
+const CURRENT_ORDER_FILE: &str = "current_order.json";
+const HELD_ORDERS_FILE: &str = "held_orders.json";
+const ORDER_JOURNAL_FILE: &str = "orders.jsonl";
+const INVENTORY_JOURNAL_FILE: &str = "inventory.jsonl";
+const REPORT_JOURNAL_FILE: &str = "reports.jsonl";
+const DRAWER_JOURNAL_FILE: &str = "drawer.jsonl";
+const AUDIT_JOURNAL_FILE: &str = "audit.jsonl";
+const LOYALTY_JOURNAL_FILE: &str = "loyalty.jsonl";
+const GIFT_CARD_JOURNAL_FILE: &str = "gift_cards.jsonl";
+const JOURNALS: [&str; 7] = [
+    ORDER_JOURNAL_FILE,
+    INVENTORY_JOURNAL_FILE,
+    REPORT_JOURNAL_FILE,
+    DRAWER_JOURNAL_FILE,
+    AUDIT_JOURNAL_FILE,
+    LOYALTY_JOURNAL_FILE,
+    GIFT_CARD_JOURNAL_FILE,
+];
+
+// Snapshots are replaced atomically (write temp, fsync, rename); order
+// history, the inventory ledger, Z reports, drawer sessions, the audit
+// log and the points and gift card ledgers are append-only journals of
+// JSON lines. In the order and drawer journals a later line for the same id
+// supersedes earlier ones.
+pub struct JsonStore {
+    dir: PathBuf,
+    // What appends to the checked journals are checked against, read once
+    // when the store opens so an append never reads the journal back.
+    tails: Mutex<Tails>,
+}
+
+struct Tails {
+    // Sequence number and hash of the last audit entry.
+    audit: (u64, String),
+    last_z_report: u32,
+    closed_drawers: HashSet<String>,
+}
+
+impl Default for Tails {
+    fn default() -> Self {
+        Self {
+            audit: (0, GENESIS_HASH.to_string()),
+            last_z_report: 0,
+            closed_drawers: HashSet::new(),
+        }
+    }
+}
+
+impl JsonStore {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, StorageError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let store = Self {
+            dir,
+            tails: Mutex::new(Tails::default()),
+        };
+        for journal in JOURNALS {
+            store.repair_journal(journal)?;
+        }
+        let tails = Tails {
+            audit: store.load_audit_log()?.pop().map_or((0, GENESIS_HASH.to_string()), |e| (e.seq, e.hash)),
+            last_z_report: store.load_reports()?.last().and_then(|r| r.number).unwrap_or(0),
+            closed_drawers: store
+                .load_drawer_sessions()?
+                .into_iter()
+                .filter(|s| !s.is_open())
+                .map(|s| s.id)
+                .collect(),
+        };
+        *store.tails() = tails;
+        Ok(store)
+    }
+
+    fn tails(&self) -> std::sync::MutexGuard<'_, Tails> {
+        self.tails.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // `TREZZA_DATA_DIR` if set, otherwise `./data`.
+    pub fn default_dir() -> PathBuf {
+        std::env::var_os("TREZZA_DATA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("data"))
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn read_snapshot<T: serde::de::DeserializeOwned>(
+        &self,
+        record: Record,
+        name: &str,
+    ) -> Result<Option<T>, StorageError> {
+        match fs::read_to_string(self.path(name)) {
+            Ok(raw) => decode(record, &raw).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_snapshot<T: Serialize>(&self, name: &str, data: &T) -> Result<(), StorageError> {
+        let target = self.path(name);
+        let tmp = self.path(&format!("{name}.tmp"));
+        let bytes = serde_json::to_vec_pretty(&Versioned::current(data))?;
+
+        let mut file = File::create(&tmp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp, &target)?;
+        self.sync_dir();
+        Ok(())
+    }
+
+    fn read_journal<T: serde::de::DeserializeOwned>(
+        &self,
+        record: Record,
+        name: &str,
+    ) -> Result<Vec<T>, StorageError> {
+        let raw = match fs::read_to_string(self.path(name)) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        raw.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                decode(record, line).map_err(|e| {
+                    StorageError::Corrupt(format!("{name} line {}: {e}", idx + 1))
+                })
+            })
+            .collect()
+    }
+
+    fn append_journal<T: Serialize>(&self, name: &str, data: &T) -> Result<(), StorageError> {
+        let mut line = serde_json::to_vec(&Versioned::current(data))?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(name))?;
+        file.write_all(&line)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    // A crash mid-append leaves a torn final line. The order it carried was
+    // never acknowledged, so cut it off before anything else is appended.
+    fn repair_journal(&self, name: &str) -> Result<(), StorageError> {
+        let path = self.path(name);
+        let raw = match fs::read(&path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        if raw.is_empty() || raw.ends_with(b"\n") {
+            return Ok(());
+        }
+
+        let keep = raw.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        let file = OpenOptions::new().write(true).open(&path)?;
+        file.set_len(keep as u64)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    // Makes a rename durable on filesystems that need the directory entry
+    // flushed. Not supported everywhere, so failures are ignored.
+    fn sync_dir(&self) {
+        if let Ok(dir) = File::open(&self.dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+impl Repository for JsonStore {
+    fn load_products(&self) -> Result<Option<Vec<Product>>, StorageError> {
+        self.read_snapshot(Record::Products, PRODUCTS_FILE)
+    }
+
+    fn save_products(&self, products: &[Product]) -> Result<(), StorageError> {
+        self.write_snapshot(PRODUCTS_FILE, &products)
+    }
+
+    fn load_tax_config(&self) -> Result<Option<TaxConfig>, StorageError> {
+        self.read_snapshot(Record::TaxConfig, TAX_CONFIG_FILE)
+    }
+
+    fn save_tax_config(&self, config: &TaxConfig) -> Result<(), StorageError> {
+        self.write_snapshot(TAX_CONFIG_FILE, config)
+    }
+
+    fn load_settings(&self) -> Result<Option<Settings>, StorageError> {
+        self.read_snapshot(Record::Settings, SETTINGS_FILE)
+    }
+
+    fn save_settings(&self, settings: &Settings) -> Result<(), StorageError> {
+        self.write_snapshot(SETTINGS_FILE, settings)
+    }
+
+    fn load_employees(&self) -> Result<Option<Vec<Employee>>, StorageError> {
+        self.read_snapshot(Record::Employees, EMPLOYEES_FILE)
+    }
+
+    fn save_employees(&self, employees: &[Employee]) -> Result<(), StorageError> {
+        self.write_snapshot(EMPLOYEES_FILE, &employees)
+    }
+
+    fn load_promotions(&self) -> Result<Option<Vec<Promotion>>, StorageError> {
+        self.read_snapshot(Record::Promotions, PROMOTIONS_FILE)
+    }
+
+    fn save_promotions(&self, promotions: &[Promotion]) -> Result<(), StorageError> {
+        self.write_snapshot(PROMOTIONS_FILE, &promotions)
+    }
+
+    fn load_categories(&self) -> Result<Option<Vec<Category>>, StorageError> {
+        self.read_snapshot(Record::Categories, CATEGORIES_FILE)
+    }
+
+    fn save_categories(&self, categories: &[Category]) -> Result<(), StorageError> {
+        self.write_snapshot(CATEGORIES_FILE, &categories)
+    }
+
+    fn load_customers(&self) -> Result<Option<Vec<Customer>>, StorageError> {
+        self.read_snapshot(Record::Customers, CUSTOMERS_FILE)
+    }
+
+    fn save_customers(&self, customers: &[Customer]) -> Result<(), StorageError> {
+        self.write_snapshot(CUSTOMERS_FILE, &customers)
+    }
+
+    fn load_gift_cards(&self) -> Result<Option<Vec<GiftCard>>, StorageError> {
+        self.read_snapshot(Record::GiftCards, GIFT_CARDS_FILE)
+    }
+
+    fn save_gift_cards(&self, cards: &[GiftCard]) -> Result<(), StorageError> {
+        self.write_snapshot(GIFT_CARDS_FILE, &cards)
+    }
+
+    fn load_current_order(&self) -> Result<Option<Order>, StorageError> {
+        self.read_snapshot(Record::CurrentOrder, CURRENT_ORDER_FILE)
+    }
+
+    fn save_current_order(&self, order: &Order) -> Result<(), StorageError> {
+        self.write_snapshot(CURRENT_ORDER_FILE, order)
+    }
+
+    fn load_held_orders(&self) -> Result<Option<Vec<HeldOrder>>, StorageError> {
+        self.read_snapshot(Record::HeldOrders, HELD_ORDERS_FILE)
+    }
+
+    fn save_held_orders(&self, held: &[HeldOrder]) -> Result<(), StorageError> {
+        self.write_snapshot(HELD_ORDERS_FILE, &held)
+    }
+
+    fn load_order_history(&self) -> Result<Vec<Order>, StorageError> {
+        let orders = self.read_journal::<Order>(Record::Order, ORDER_JOURNAL_FILE)?;
+        Ok(latest_by_id(orders, |o| &o.id))
+    }
+
+    fn record_order(&self, order: &Order) -> Result<(), StorageError> {
+        self.append_journal(ORDER_JOURNAL_FILE, order)
+    }
+
+    fn load_stock_movements(&self) -> Result<Vec<StockMovement>, StorageError> {
+        self.read_journal(Record::StockMovement, INVENTORY_JOURNAL_FILE)
+    }
+
+    fn record_stock_movement(&self, movement: &StockMovement) -> Result<(), StorageError> {
+        self.append_journal(INVENTORY_JOURNAL_FILE, movement)
+    }
+
+    fn load_points_ledger(&self) -> Result<Vec<PointsEntry>, StorageError> {
+        self.read_journal(Record::PointsEntry, LOYALTY_JOURNAL_FILE)
+    }
+
+    fn record_points_entry(&self, entry: &PointsEntry) -> Result<(), StorageError> {
+        self.append_journal(LOYALTY_JOURNAL_FILE, entry)
+    }
+
+    fn load_card_ledger(&self) -> Result<Vec<CardEntry>, StorageError> {
+        self.read_journal(Record::CardEntry, GIFT_CARD_JOURNAL_FILE)
+    }
+
+    fn record_card_entry(&self, entry: &CardEntry) -> Result<(), StorageError> {
+        self.append_journal(GIFT_CARD_JOURNAL_FILE, entry)
+    }
+
+    fn load_reports(&self) -> Result<Vec<SalesReport>, StorageError> {
+        self.read_journal(Record::Report, REPORT_JOURNAL_FILE)
+    }
+
+    fn record_report(&self, report: &SalesReport) -> Result<(), StorageError> {
+        if report.kind != ReportKind::Z {
+            return Err(StorageError::Conflict("only Z reports are stored".to_string()));
+        }
+        let mut tails = self.tails();
+        let last = tails.last_z_report;
+        if report.number != Some(last + 1) {
+            return Err(StorageError::Conflict(format!(
+                "Z report {} does not follow Z report {last}",
+                report.number.unwrap_or(0)
+            )));
+        }
+        self.append_journal(REPORT_JOURNAL_FILE, report)?;
+        tails.last_z_report = last + 1;
+        Ok(())
+    }
+
+    fn load_drawer_sessions(&self) -> Result<Vec<DrawerSession>, StorageError> {
+        let sessions = self.read_journal::<DrawerSession>(Record::DrawerSession, DRAWER_JOURNAL_FILE)?;
+        Ok(latest_by_id(sessions, |s| &s.id))
+    }
+
+    fn record_drawer_session(&self, session: &DrawerSession) -> Result<(), StorageError> {
+        let mut tails = self.tails();
+        if tails.closed_drawers.contains(&session.id) {
+            return Err(StorageError::Conflict(format!("drawer session {} is closed", session.id)));
+        }
+        self.append_journal(DRAWER_JOURNAL_FILE, session)?;
+        if !session.is_open() {
+            tails.closed_drawers.insert(session.id.clone());
+        }
+        Ok(())
+    }
+
+    fn load_audit_log(&self) -> Result<Vec<AuditEntry>, StorageError> {
+        self.read_journal(Record::AuditEntry, AUDIT_JOURNAL_FILE)
+    }
+
+    fn record_audit(&self, entry: &AuditEntry) -> Result<(), StorageError> {
+        let mut tails = self.tails();
+        let (seq, hash) = &tails.audit;
+        if entry.seq != seq + 1 || &entry.prev_hash != hash {
+            return Err(StorageError::Conflict(format!(
+                "audit entry {} does not follow entry {seq}",
+                entry.seq
+            )));
+        }
+        self.append_journal(AUDIT_JOURNAL_FILE, entry)?;
+        tails.audit = (entry.seq, entry.hash.clone());
+        Ok(())
+    }
+}
+
+// Collapses a journal to the last line per id, in order of first appearance.
+fn latest_by_id<T>(records: Vec<T>, id: impl Fn(&T) -> &String) -> Vec<T> {
+    let mut latest: Vec<T> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for record in records {
+        match index.get(id(&record)) {
+            Some(&pos) => latest[pos] = record,
+            None => {
+                index.insert(id(&record).clone(), latest.len());
+                latest.push(record);
+            }
+        }
+    }
+    latest
+}
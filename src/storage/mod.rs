@@ -0,0 +1,6 @@
+pub mod repository;
+pub mod json_store;
+pub mod schema;
+
+pub use repository::*;
+pub use json_store::*;
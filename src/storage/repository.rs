@@ -0,0 +1,112 @@
+use std::fmt;
+use std::io;
+use crate::models::{AuditEntry, CardEntry, Category, Customer, DrawerSession, Employee, GiftCard, HeldOrder, Order, PointsEntry, Product, Promotion, SalesReport, Settings, StockMovement, TaxConfig};
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Serde(serde_json::Error),
+    UnsupportedVersion { found: u32, supported: u32 },
+    Corrupt(String),
+    Conflict(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "storage I/O error: {e}"),
+            StorageError::Serde(e) => write!(f, "storage format error: {e}"),
+            StorageError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "data written by schema version {found}, this build supports up to {supported}"
+            ),
+            StorageError::Corrupt(msg) => write!(f, "corrupt data file: {msg}"),
+            StorageError::Conflict(msg) => write!(f, "rejected write: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> Self {
+        StorageError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(e: serde_json::Error) -> Self {
+        StorageError::Serde(e)
+    }
+}
+
+/// Durable backing store for the terminal's state.
+///
+/// `load_*` methods return `None` when nothing has been saved yet so the
+/// caller can decide how to seed. Every `save_*`/`record_*` call must be
+/// durable by the time it returns.
+pub trait Repository {
+    fn load_products(&self) -> Result<Option<Vec<Product>>, StorageError>;
+    fn save_products(&self, products: &[Product]) -> Result<(), StorageError>;
+
+    fn load_tax_config(&self) -> Result<Option<TaxConfig>, StorageError>;
+    fn save_tax_config(&self, config: &TaxConfig) -> Result<(), StorageError>;
+
+    fn load_settings(&self) -> Result<Option<Settings>, StorageError>;
+    fn save_settings(&self, settings: &Settings) -> Result<(), StorageError>;
+
+    fn load_employees(&self) -> Result<Option<Vec<Employee>>, StorageError>;
+    fn save_employees(&self, employees: &[Employee]) -> Result<(), StorageError>;
+
+    fn load_promotions(&self) -> Result<Option<Vec<Promotion>>, StorageError>;
+    fn save_promotions(&self, promotions: &[Promotion]) -> Result<(), StorageError>;
+
+    fn load_categories(&self) -> Result<Option<Vec<Category>>, StorageError>;
+    fn save_categories(&self, categories: &[Category]) -> Result<(), StorageError>;
+
+    fn load_customers(&self) -> Result<Option<Vec<Customer>>, StorageError>;
+    fn save_customers(&self, customers: &[Customer]) -> Result<(), StorageError>;
+
+    // Gift cards and store credit accounts; their balances are in the
+    // card ledger.
+    fn load_gift_cards(&self) -> Result<Option<Vec<GiftCard>>, StorageError>;
+    fn save_gift_cards(&self, cards: &[GiftCard]) -> Result<(), StorageError>;
+
+    fn load_current_order(&self) -> Result<Option<Order>, StorageError>;
+    fn save_current_order(&self, order: &Order) -> Result<(), StorageError>;
+
+    // Orders parked at the till, oldest first.
+    fn load_held_orders(&self) -> Result<Option<Vec<HeldOrder>>, StorageError>;
+    fn save_held_orders(&self, held: &[HeldOrder]) -> Result<(), StorageError>;
+
+    fn load_order_history(&self) -> Result<Vec<Order>, StorageError>;
+    // Inserts or replaces the order with the same id.
+    fn record_order(&self, order: &Order) -> Result<(), StorageError>;
+
+    // The inventory ledger is append-only.
+    fn load_stock_movements(&self) -> Result<Vec<StockMovement>, StorageError>;
+    fn record_stock_movement(&self, movement: &StockMovement) -> Result<(), StorageError>;
+
+    // The loyalty points ledger is append-only too.
+    fn load_points_ledger(&self) -> Result<Vec<PointsEntry>, StorageError>;
+    fn record_points_entry(&self, entry: &PointsEntry) -> Result<(), StorageError>;
+
+    // So is the gift card and store credit ledger.
+    fn load_card_ledger(&self) -> Result<Vec<CardEntry>, StorageError>;
+    fn record_card_entry(&self, entry: &CardEntry) -> Result<(), StorageError>;
+
+    // Closed Z reports, oldest first. They are never rewritten: a report
+    // is refused unless its number follows the last one recorded.
+    fn load_reports(&self) -> Result<Vec<SalesReport>, StorageError>;
+    fn record_report(&self, report: &SalesReport) -> Result<(), StorageError>;
+
+    // Inserts or replaces the session with the same id. A closed session
+    // is final and can't be replaced.
+    fn load_drawer_sessions(&self) -> Result<Vec<DrawerSession>, StorageError>;
+    fn record_drawer_session(&self, session: &DrawerSession) -> Result<(), StorageError>;
+
+    // The audit log is append-only and hash-chained. An entry is refused
+    // unless it links to the last one recorded.
+    fn load_audit_log(&self) -> Result<Vec<AuditEntry>, StorageError>;
+    fn record_audit(&self, entry: &AuditEntry) -> Result<(), StorageError>;
+}
@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::models::TaxRate;
+use super::repository::StorageError;
+
+pub const SCHEMA_VERSION: u32 = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub schema_version: u32,
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    pub fn current(data: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Record {
+    Products,
+    TaxConfig,
+    Settings,
+    CurrentOrder,
+    // Added at version 7; a later change to `Order` has to migrate the
+    // orders inside it too.
+    HeldOrders,
+    Order,
+    StockMovement,
+    PointsEntry,
+    CardEntry,
+    Report,
+    DrawerSession,
+    Employees,
+    Promotions,
+    Categories,
+    Customers,
+    GiftCards,
+    // Hashed as written, so no migration may change it.
+    AuditEntry,
+}
+
+pub fn decode<T: serde::de::DeserializeOwned>(record: Record, raw: &str) -> Result<T, StorageError> {
+    let envelope: Versioned<Value> = serde_json::from_str(raw)?;
+    let data = migrate(record, envelope.schema_version, envelope.data)?;
+    Ok(serde_json::from_value(data)?)
+}
+
+fn migrate(record: Record, version: u32, mut data: Value) -> Result<Value, StorageError> {
+    if version > SCHEMA_VERSION {
+        return Err(StorageError::UnsupportedVersion {
+            found: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    for from in version..SCHEMA_VERSION {
+        data = upgrade(record, from, data)?;
+    }
+    Ok(data)
+}
+
+// One step per schema bump: rewrites a payload from `from` to `from + 1`.
+fn upgrade(record: Record, from: u32, data: Value) -> Result<Value, StorageError> {
+    match from {
+        // v1 stored amounts as f64 dollars. `Money` still deserializes a bare
+        // number as legacy USD, so the payload converts as-is.
+        1 => Ok(data),
+        2 => Ok(match record {
+            Record::Order | Record::CurrentOrder => add_flat_sales_tax(data)?,
+            _ => data,
+        }),
+        3 => Ok(match record {
+            Record::Order | Record::CurrentOrder => add_payment_id(data),
+            _ => data,
+        }),
+        4 => Ok(match record {
+            Record::Order | Record::CurrentOrder => payment_to_payments(data),
+            _ => data,
+        }),
+        5 => Ok(match record {
+            Record::Order | Record::CurrentOrder => add_line_ids(data),
+            _ => data,
+        }),
+        6 => Ok(match record {
+            Record::Products => categories_to_ids(data),
+            Record::TaxConfig => tax_categories_to_ids(data),
+            Record::Order | Record::CurrentOrder => add_category_ids(data),
+            Record::Promotions => promotion_categories_to_ids(data),
+            _ => data,
+        }),
+        // Held orders are never paid, so have no refunds to move.
+        7 => Ok(match record {
+            Record::Order | Record::CurrentOrder => refund_tenders(data),
+            _ => data,
+        }),
+        _ => Err(StorageError::Corrupt(format!(
+            "no migration for {record:?} from schema version {from}"
+        ))),
+    }
+}
+
+// v2 orders carried a single `tax` figure from the hard-coded 8% rate. Give
+// every line that rate explicitly and record the order's tax as one tax line
+// so the order recalculates and reports the same way it did before.
+fn add_flat_sales_tax(mut order: Value) -> Result<Value, StorageError> {
+    let sales_tax = TaxRate::sales_tax();
+    let rate_json = serde_json::to_value(&sales_tax)?;
+    let obj = order
+        .as_object_mut()
+        .ok_or_else(|| StorageError::Corrupt("order is not an object".to_string()))?;
+
+    if let Some(Value::Array(items)) = obj.get_mut("items") {
+        for item in items.iter_mut().filter_map(Value::as_object_mut) {
+            item.entry("taxes").or_insert_with(|| json!([rate_json.clone()]));
+        }
+    }
+
+    let subtotal = obj.get("subtotal").cloned().unwrap_or(Value::Null);
+    let tax = obj.get("tax").cloned().unwrap_or(Value::Null);
+    obj.entry("tax_lines").or_insert_with(|| {
+        json!([{
+            "rate_id": sales_tax.id,
+            "name": sales_tax.name,
+            "rate": sales_tax.rate,
+            "taxable": subtotal,
+            "amount": tax,
+        }])
+    });
+    Ok(order)
+}
+
+// v3 payments had no id. Refunds link back to the payment by id, so derive a
+// stable one from the order (each order had at most one payment).
+fn add_payment_id(mut order: Value) -> Value {
+    let order_id = order.get("id").cloned().unwrap_or(Value::Null);
+    if let Some(payment) = order.get_mut("payment").and_then(Value::as_object_mut) {
+        payment.entry("id").or_insert(order_id);
+    }
+    order
+}
+
+// v4 orders held a single optional `payment`; split tender keeps a list.
+fn payment_to_payments(mut order: Value) -> Value {
+    if let Some(obj) = order.as_object_mut() {
+        let payments = match obj.remove("payment") {
+            Some(Value::Null) | None => json!([]),
+            Some(payment) => json!([payment]),
+        };
+        obj.entry("payments").or_insert(payments);
+    }
+    order
+}
+
+// v5 lines were keyed by product id; with variants and modifiers a product
+// can have several lines. A line without options keeps the product id as its
+// line id, and refund lines point at it the same way.
+fn add_line_ids(mut order: Value) -> Value {
+    let with_line_id = |line: &mut Value| {
+        if let Some(obj) = line.as_object_mut() {
+            let product_id = obj.get("product_id").cloned().unwrap_or(Value::Null);
+            obj.entry("line_id").or_insert(product_id);
+        }
+    };
+    if let Some(Value::Array(items)) = order.get_mut("items") {
+        items.iter_mut().for_each(with_line_id);
+    }
+    if let Some(Value::Array(refunds)) = order.get_mut("refunds") {
+        for refund in refunds.iter_mut() {
+            if let Some(Value::Array(lines)) = refund.get_mut("lines") {
+                lines.iter_mut().for_each(with_line_id);
+            }
+        }
+    }
+    order
+}
+
+// v6 categories were a fixed enum stored by name ("Food"). They became the
+// built-in categories whose ids are the lowercase names.
+fn legacy_category_id(value: &Value) -> Option<Value> {
+    match value.as_str()? {
+        name @ ("Food" | "Beverage" | "Retail" | "Service") => Some(json!(name.to_lowercase())),
+        _ => None,
+    }
+}
+
+fn rename_category(value: &mut Value) {
+    if let Some(id) = legacy_category_id(value) {
+        *value = id;
+    }
+}
+
+fn categories_to_ids(mut products: Value) -> Value {
+    if let Value::Array(list) = &mut products {
+        for product in list.iter_mut() {
+            if let Some(category) = product.get_mut("category") {
+                rename_category(category);
+            }
+        }
+    }
+    products
+}
+
+fn tax_categories_to_ids(mut config: Value) -> Value {
+    if let Some(Value::Array(classes)) = config.get_mut("category_classes") {
+        for class in classes.iter_mut() {
+            if let Some(category) = class.get_mut("category") {
+                rename_category(category);
+            }
+        }
+    }
+    config
+}
+
+// Lines kept the category name; promotions now match on ids. Orders also
+// carry a copy of their promotions.
+fn add_category_ids(mut order: Value) -> Value {
+    if let Some(Value::Array(items)) = order.get_mut("items") {
+        for item in items.iter_mut().filter_map(Value::as_object_mut) {
+            let ids: Vec<Value> = item.get("category").and_then(legacy_category_id).into_iter().collect();
+            item.entry("category_ids").or_insert(Value::Array(ids));
+        }
+    }
+    if let Some(promotions) = order.get_mut("promotions") {
+        *promotions = promotion_categories_to_ids(promotions.take());
+    }
+    order
+}
+
+// v7 refunds went back through a single `payment_id` and `method`; a
+// refund can now be split over several tenders.
+fn refund_tenders(mut order: Value) -> Value {
+    if let Some(Value::Array(refunds)) = order.get_mut("refunds") {
+        for refund in refunds.iter_mut().filter_map(Value::as_object_mut) {
+            let payment_id = refund.remove("payment_id").unwrap_or(Value::Null);
+            let tenders = match refund.remove("method") {
+                Some(Value::Null) | None => json!([]),
+                Some(method) => json!([{
+                    "payment_id": payment_id,
+                    "method": method,
+                    "amount": refund.get("total").cloned().unwrap_or(Value::Null),
+                }]),
+            };
+            refund.entry("tenders").or_insert(tenders);
+        }
+    }
+    order
+}
+
+fn promotion_categories_to_ids(mut promotions: Value) -> Value {
+    if let Value::Array(list) = &mut promotions {
+        for rule in list.iter_mut().filter_map(|p| p.get_mut("rule")).filter_map(Value::as_object_mut) {
+            // Externally tagged: {"MixAndMatch": {"category": "Food", ..}}
+            // or {"PercentOff": {"target": {"Category": "Food"}, ..}}.
+            for body in rule.values_mut() {
+                if let Some(category) = body.get_mut("category") {
+                    rename_category(category);
+                }
+                if let Some(category) = body.get_mut("target").and_then(|t| t.get_mut("Category")) {
+                    rename_category(category);
+                }
+            }
+        }
+    }
+    promotions
+}